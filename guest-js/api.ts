@@ -4,11 +4,36 @@
 
 import { invoke } from "@tauri-apps/api/core";
 import type {
+  BellMode,
+  BufferStats,
+  Capabilities,
+  Cell,
+  Color,
+  CursorPosition,
+  EventCategory,
+  ExportFormat,
+  ImagePlacement,
+  KeyEvent,
+  LogLevel,
+  Mark,
+  MatchRange,
+  PixelSize,
+  Row,
   Screen,
   ScreenUpdate,
+  ScriptLineResult,
+  SearchDirection,
+  SearchMatch,
   SessionConfig,
+  SessionDiagnostics,
+  SessionFilter,
   SessionInfo,
+  SessionSnapshot,
+  Size,
+  SpawnSpec,
   Theme,
+  TimeoutAction,
+  Viewport,
 } from "./types";
 
 const PLUGIN_NAME = "terminal";
@@ -24,6 +49,15 @@ export async function createSession(config: SessionConfig = {}): Promise<string>
   return invoke<string>(cmd("create_session"), { config });
 }
 
+/**
+ * Close a session gracefully: send its close sequence (`exit` by default),
+ * wait for the program to exit so shells can save history, then destroy it.
+ * Programs still running after the close timeout are killed.
+ */
+export async function closeSession(sessionId: string): Promise<void> {
+  return invoke(cmd("close_session"), { sessionId });
+}
+
 /**
  * Destroy a terminal session.
  */
@@ -38,6 +72,13 @@ export async function listSessions(): Promise<SessionInfo[]> {
   return invoke<SessionInfo[]>(cmd("list_sessions"));
 }
 
+/**
+ * List the sessions matching a filter; an empty filter matches all.
+ */
+export async function listSessionsFiltered(filter: SessionFilter): Promise<SessionInfo[]> {
+  return invoke<SessionInfo[]>(cmd("list_sessions_filtered"), { filter });
+}
+
 /**
  * Get session info.
  */
@@ -45,6 +86,78 @@ export async function getSession(sessionId: string): Promise<SessionInfo> {
   return invoke<SessionInfo>(cmd("get_session"), { sessionId });
 }
 
+/**
+ * Get the logical time of a session's last screen update (0 if none yet).
+ */
+export async function getLastUpdate(sessionId: string): Promise<number> {
+  return invoke<number>(cmd("get_last_update"), { sessionId });
+}
+
+/**
+ * List sessions with a screen update after the logical time `since`.
+ */
+export async function sessionsChangedSince(since: number): Promise<string[]> {
+  return invoke<string[]>(cmd("sessions_changed_since"), { since });
+}
+
+/**
+ * Get counters and state for troubleshooting a session.
+ */
+export async function sessionDiagnostics(sessionId: string): Promise<SessionDiagnostics> {
+  return invoke<SessionDiagnostics>(cmd("session_diagnostics"), { sessionId });
+}
+
+/**
+ * Get the approximate memory held by a session's scrollback, screen and
+ * inline images.
+ */
+export async function getBufferStats(sessionId: string): Promise<BufferStats> {
+  return invoke<BufferStats>(cmd("get_buffer_stats"), { sessionId });
+}
+
+/**
+ * Get buffer memory summed over all sessions.
+ */
+export async function getTotalBufferStats(): Promise<BufferStats> {
+  return invoke<BufferStats>(cmd("get_total_buffer_stats"));
+}
+
+/**
+ * Create a named group of sessions (e.g. the panes of a tab) to resize,
+ * type into or destroy together.
+ */
+export async function createGroup(name: string, sessionIds: string[]): Promise<void> {
+  return invoke(cmd("create_group"), { name, sessionIds });
+}
+
+/**
+ * Add a session to a group.
+ */
+export async function addToGroup(name: string, sessionId: string): Promise<void> {
+  return invoke(cmd("add_to_group"), { name, sessionId });
+}
+
+/**
+ * Resize every session in a group.
+ */
+export async function groupResize(name: string, cols: number, rows: number): Promise<void> {
+  return invoke(cmd("group_resize"), { name, cols, rows });
+}
+
+/**
+ * Write the same string to every session in a group.
+ */
+export async function groupBroadcast(name: string, data: string): Promise<void> {
+  return invoke(cmd("group_broadcast"), { name, data });
+}
+
+/**
+ * Destroy every session in a group and the group itself.
+ */
+export async function destroyGroup(name: string): Promise<void> {
+  return invoke(cmd("destroy_group"), { name });
+}
+
 /**
  * Write string data to a session.
  */
@@ -52,6 +165,16 @@ export async function writeToSession(sessionId: string, data: string): Promise<v
   return invoke(cmd("write_to_session"), { sessionId, data });
 }
 
+/**
+ * Run bytes through the emulator as if the program had written them,
+ * without touching the PTY; for replaying recordings or canned content.
+ * Needs the `terminal:allow-feed-session` permission, which isn't in
+ * `terminal:default`.
+ */
+export async function feedSession(sessionId: string, data: number[]): Promise<void> {
+  return invoke(cmd("feed_session"), { sessionId, data });
+}
+
 /**
  * Write binary data to a session.
  */
@@ -59,6 +182,138 @@ export async function writeBytesToSession(sessionId: string, data: number[]): Pr
   return invoke(cmd("write_bytes_to_session"), { sessionId, data });
 }
 
+/**
+ * Get the full environment the shell was spawned with (inherited variables
+ * plus overrides), e.g. to debug PATH. Empty for attached PTYs.
+ */
+export async function getSessionEnv(sessionId: string): Promise<Record<string, string>> {
+  return invoke<Record<string, string>>(cmd("get_session_env"), { sessionId });
+}
+
+/**
+ * Capture a session as a portable snapshot: config, environment, screen,
+ * scrollback, marks, working directory, size and theme.
+ */
+export async function exportSnapshot(sessionId: string): Promise<SessionSnapshot> {
+  return invoke<SessionSnapshot>(cmd("export_snapshot"), { sessionId });
+}
+
+/**
+ * Create a session from a snapshot, respawning its shell and replaying its
+ * scrollback. A new id is generated unless `sessionId` is given.
+ */
+export async function importSnapshot(snapshot: SessionSnapshot, sessionId?: string): Promise<string> {
+  return invoke<string>(cmd("import_snapshot"), { snapshot, sessionId });
+}
+
+/**
+ * Get the program, arguments, directory and environment the shell was
+ * spawned with, to open an equivalent shell in the system terminal. `null`
+ * for attached PTYs.
+ */
+export async function exportSpawnCommand(sessionId: string): Promise<SpawnSpec | null> {
+  return invoke<SpawnSpec | null>(cmd("export_spawn_command"), { sessionId });
+}
+
+/**
+ * Set an environment variable for the session's future children. With
+ * `applyNow`, also export it in the running shell.
+ */
+export async function setSessionEnv(
+  sessionId: string,
+  key: string,
+  value: string,
+  applyNow?: boolean
+): Promise<void> {
+  return invoke(cmd("set_session_env"), { sessionId, key, value, applyNow });
+}
+
+/** Type a line into the session and submit it; pair with `expect`. */
+export async function sendLine(sessionId: string, line: string): Promise<void> {
+  return invoke(cmd("send_line"), { sessionId, line });
+}
+
+/**
+ * Wait for output matching the regex `pattern`, searching after the previous
+ * match with escape sequences stripped. Rejects with code `expect_timeout`
+ * and the unmatched output if nothing matches within `timeoutMs` (default 10s).
+ */
+export async function expect(
+  sessionId: string,
+  pattern: string,
+  timeoutMs?: number
+): Promise<MatchRange> {
+  return invoke<MatchRange>(cmd("expect"), { sessionId, pattern, timeoutMs });
+}
+
+/**
+ * Submit lines one at a time, waiting for each to finish. Uses OSC 133
+ * marks when the shell emits them, otherwise waits for output to go quiet
+ * and leaves the line's `success` unset. Stops at the first failing line.
+ * A line still running at its deadline rejects with code `command_timeout`,
+ * carrying its output so far; `onTimeout` chooses whether it is left
+ * running (default), interrupted or killed.
+ */
+export async function runScript(
+  sessionId: string,
+  lines: string[],
+  timeoutMs?: number,
+  onTimeout?: TimeoutAction
+): Promise<ScriptLineResult[]> {
+  return invoke<ScriptLineResult[]>(cmd("run_script"), {
+    sessionId,
+    lines,
+    timeoutMs,
+    onTimeout,
+  });
+}
+
+/**
+ * Get the text of a command run under shell integration, found by the row
+ * of its `command_start` mark (the latest command when omitted). Only the
+ * output is returned unless `includeCommand` or `includeTrailingPrompt` is
+ * set; `null` when no matching command was marked.
+ */
+export async function getCommandOutput(
+  sessionId: string,
+  commandRow?: number,
+  includeCommand?: boolean,
+  includeTrailingPrompt?: boolean
+): Promise<string | null> {
+  return invoke<string | null>(cmd("get_command_output"), {
+    sessionId,
+    commandRow,
+    includeCommand,
+    includeTrailingPrompt,
+  });
+}
+
+/**
+ * Get the app metadata attached to a session.
+ */
+export async function getMetadata(sessionId: string): Promise<Record<string, string>> {
+  return invoke<Record<string, string>>(cmd("get_metadata"), { sessionId });
+}
+
+/**
+ * Set a session metadata entry; `null` removes it.
+ */
+export async function setMetadata(
+  sessionId: string,
+  key: string,
+  value: string | null
+): Promise<void> {
+  return invoke(cmd("set_metadata"), { sessionId, key, value });
+}
+
+/**
+ * Send a key press, encoded for the terminal's current modes. Keys matching
+ * a session key binding emit a `binding` event instead.
+ */
+export async function sendKey(sessionId: string, key: KeyEvent): Promise<void> {
+  return invoke(cmd("send_key"), { sessionId, key });
+}
+
 /**
  * Resize a session.
  */
@@ -66,6 +321,25 @@ export async function resizeSession(sessionId: string, cols: number, rows: numbe
   return invoke(cmd("resize_session"), { sessionId, cols, rows });
 }
 
+/**
+ * Get the window size in pixels reported to the session's child.
+ */
+export async function getPixelSize(sessionId: string): Promise<PixelSize> {
+  return invoke<PixelSize>(cmd("get_pixel_size"), { sessionId });
+}
+
+/**
+ * Set the window size in pixels reported to the session's child without
+ * changing the cell grid.
+ */
+export async function setPixelSize(
+  sessionId: string,
+  widthPx: number,
+  heightPx: number
+): Promise<void> {
+  return invoke(cmd("set_pixel_size"), { sessionId, widthPx, heightPx });
+}
+
 /**
  * Get the full screen state.
  */
@@ -73,6 +347,118 @@ export async function getScreen(sessionId: string): Promise<Screen> {
   return invoke<Screen>(cmd("get_screen"), { sessionId });
 }
 
+/**
+ * Get a fingerprint (hex string) of the visible grid, covering text and
+ * styles. Compare against a previous value to decide whether to re-fetch.
+ */
+export async function getScreenHash(sessionId: string): Promise<string> {
+  return invoke<string>(cmd("get_screen_hash"), { sessionId });
+}
+
+/**
+ * Get the visible screen as newline-joined, right-trimmed text.
+ */
+export async function getScreenText(sessionId: string): Promise<string> {
+  return invoke<string>(cmd("get_screen_text"), { sessionId });
+}
+
+/**
+ * Get a single cell of the visible screen, for hit-testing. Out-of-range
+ * positions return an empty cell.
+ */
+export async function getCell(sessionId: string, row: number, col: number): Promise<Cell> {
+  return invoke<Cell>(cmd("get_cell"), { sessionId, row, col });
+}
+
+/**
+ * Get the cells from `top`/`left` to `bottom`/`right` (inclusive) of the
+ * visible screen, clamped to the grid; edges widen rather than split a
+ * wide character.
+ */
+export async function getRegion(
+  sessionId: string,
+  top: number,
+  left: number,
+  bottom: number,
+  right: number
+): Promise<Row[]> {
+  return invoke<Row[]>(cmd("get_region"), { sessionId, top, left, bottom, right });
+}
+
+/**
+ * List the inline images in a session's buffer, without their data.
+ */
+export async function getImages(sessionId: string): Promise<ImagePlacement[]> {
+  return invoke<ImagePlacement[]>(cmd("get_images"), { sessionId });
+}
+
+/**
+ * Get an inline image's base64 data, or null once it has been evicted.
+ */
+export async function getImageData(sessionId: string, imageId: number): Promise<string | null> {
+  return invoke<string | null>(cmd("get_image_data"), { sessionId, imageId });
+}
+
+/**
+ * Copy the text between two screen positions to the clipboard. Emits a
+ * `clipboard_request` event like a program's OSC 52 write, so one handler
+ * covers both. Returns the copied text.
+ */
+export async function copySelectionOsc52(
+  sessionId: string,
+  start: CursorPosition,
+  end: CursorPosition,
+  rectangular?: boolean
+): Promise<string> {
+  return invoke<string>(cmd("copy_selection_osc52"), { sessionId, start, end, rectangular });
+}
+
+/**
+ * Export a range of buffer lines (0 = oldest scrollback line) as ANSI-colored
+ * text. Lines end in `\r\n` unless `normalizeNewlines` is set, which gives
+ * plain `\n` endings for log files and editors.
+ */
+export async function exportAnsiRange(
+  sessionId: string,
+  startLine: number,
+  count: number,
+  normalizeNewlines?: boolean
+): Promise<string> {
+  return invoke<string>(cmd("export_ansi_range"), {
+    sessionId,
+    startLine,
+    count,
+    normalizeNewlines,
+  });
+}
+
+/**
+ * Export the whole buffer as plain text. Soft-wrapped rows are rejoined
+ * into their logical lines unless `preserveWrapping` is set.
+ */
+export async function exportText(
+  sessionId: string,
+  preserveWrapping?: boolean
+): Promise<string> {
+  return invoke<string>(cmd("export_text"), { sessionId, preserveWrapping });
+}
+
+/**
+ * Write the whole buffer to the file at `path` as plain text (default) or
+ * ANSI-colored text, a page at a time on a worker thread, for sessions with
+ * too much scrollback to export in memory. Resolves to the lines written.
+ *
+ * Not in `terminal:default`; the app must grant `terminal:allow-export-to-file`.
+ */
+export async function exportToFile(
+  sessionId: string,
+  path: string,
+  format?: ExportFormat,
+  preserveWrapping?: boolean
+): Promise<number> {
+  return invoke<number>(cmd("export_to_file"), { sessionId, path, format, preserveWrapping });
+}
+
 /**
  * Poll for pending output and get updates.
  */
@@ -80,6 +466,22 @@ export async function pollSession(sessionId: string): Promise<ScreenUpdate | nul
   return invoke<ScreenUpdate | null>(cmd("poll_session"), { sessionId });
 }
 
+/**
+ * Check whether output is waiting, without processing it, so a poller can
+ * skip `pollSession` when nothing arrived.
+ */
+export async function hasPendingOutput(sessionId: string): Promise<boolean> {
+  return invoke<boolean>(cmd("has_pending_output"), { sessionId });
+}
+
+/**
+ * Re-emit the last screen update and return it, to recover from a missed
+ * event without fetching the whole screen. `null` before the first update.
+ */
+export async function replayLastUpdate(sessionId: string): Promise<ScreenUpdate | null> {
+  return invoke<ScreenUpdate | null>(cmd("replay_last_update"), { sessionId });
+}
+
 /**
  * Get the theme for a session.
  */
@@ -87,6 +489,14 @@ export async function getTheme(sessionId: string): Promise<Theme> {
   return invoke<Theme>(cmd("get_theme"), { sessionId });
 }
 
+/**
+ * Get the session's 256-color palette resolved to RGB: 0-15 from the theme,
+ * 16-255 from the color cube and grayscale ramp.
+ */
+export async function getPalette(sessionId: string): Promise<Color[]> {
+  return invoke<Color[]>(cmd("get_palette"), { sessionId });
+}
+
 /**
  * Set the theme for a session.
  */
@@ -94,6 +504,198 @@ export async function setTheme(sessionId: string, themeName: string): Promise<vo
   return invoke(cmd("set_theme"), { sessionId, themeName });
 }
 
+/**
+ * Override the cursor color independently of the theme; `null` restores the
+ * theme's cursor. OSC 12 from the program updates the same override.
+ */
+export async function setCursorColor(sessionId: string, color: Color | null): Promise<void> {
+  return invoke(cmd("set_cursor_color"), { sessionId, color });
+}
+
+/**
+ * Render indexed color `index` (e.g. 1 for red) as `color` on top of the
+ * theme, for color-blind remapping; `null` removes the override. The screen
+ * is re-sent as a `screen_refresh`.
+ */
+export async function setColorOverride(
+  sessionId: string,
+  index: number,
+  color: Color | null
+): Promise<void> {
+  return invoke(cmd("set_color_override"), { sessionId, index, color });
+}
+
+/**
+ * Mute categories of events for a session, e.g. `["screen_update"]` for a
+ * backgrounded tab. `process_exit` and other lifecycle events still arrive.
+ * Pass `[]` to unmute, then refetch the screen.
+ */
+export async function setEventFilter(sessionId: string, mask: EventCategory[]): Promise<void> {
+  return invoke(cmd("set_event_filter"), { sessionId, mask });
+}
+
+/**
+ * Transition to a theme over `durationMs`, emitting `steps` intermediate
+ * theme-change events for a crossfade.
+ */
+export async function setThemeAnimated(
+  sessionId: string,
+  themeName: string,
+  durationMs: number,
+  steps: number
+): Promise<void> {
+  return invoke(cmd("set_theme_animated"), { sessionId, themeName, durationMs, steps });
+}
+
+/**
+ * Set how the bell is surfaced for a session.
+ */
+export async function setBellMode(sessionId: string, mode: BellMode): Promise<void> {
+  return invoke(cmd("set_bell_mode"), { sessionId, mode });
+}
+
+/**
+ * Set how much a session logs: `warn` (the default) reports recoverable
+ * failures, `debug` adds spawn and exit, `trace` every read and update.
+ */
+export async function setLogLevel(sessionId: string, level: LogLevel): Promise<void> {
+  return invoke(cmd("set_log_level"), { sessionId, level });
+}
+
+/**
+ * Soft-reset the terminal (DECSTR): clears stuck modes such as mouse
+ * tracking, margins and attributes, keeping the screen and scrollback.
+ */
+export async function softReset(sessionId: string): Promise<void> {
+  return invoke(cmd("soft_reset"), { sessionId });
+}
+
+/**
+ * Turn software flow control on or off: Ctrl-S stops reading output (the
+ * program blocks once the PTY fills) and Ctrl-Q resumes. Neither byte is
+ * sent to the program.
+ */
+export async function setFlowControl(sessionId: string, enabled: boolean): Promise<void> {
+  return invoke(cmd("set_flow_control"), { sessionId, enabled });
+}
+
+/**
+ * Force line wrapping on or off regardless of DECAWM; `null` follows the
+ * program again. Truncated text isn't restored by widening the terminal.
+ */
+export async function setWrapMode(sessionId: string, wrap: boolean | null): Promise<void> {
+  return invoke(cmd("set_wrap_mode"), { sessionId, wrap });
+}
+
+/**
+ * Get the shell's current working directory, from OSC 7 when the shell
+ * reports it and otherwise from the OS (Linux and macOS only). `null` when
+ * neither is available.
+ */
+export async function getCwd(sessionId: string): Promise<string | null> {
+  return invoke<string | null>(cmd("get_cwd"), { sessionId });
+}
+
+/**
+ * Describe what a session supports (images, OSC 52, truecolor, ...) and the
+ * modes its program has on (mouse reporting, bracketed paste, alt screen).
+ */
+export async function getCapabilities(sessionId: string): Promise<Capabilities> {
+  return invoke<Capabilities>(cmd("get_capabilities"), { sessionId });
+}
+
+/**
+ * Whether the alternate screen is active (full-screen programs such as vim
+ * or less), which has no scrollback.
+ */
+export async function isAltScreen(sessionId: string): Promise<boolean> {
+  return invoke<boolean>(cmd("is_alt_screen"), { sessionId });
+}
+
+/**
+ * Whether the program has turned on insert mode (IRM), for frontends that
+ * echo or predict input locally.
+ */
+export async function isInsertMode(sessionId: string): Promise<boolean> {
+  return invoke<boolean>(cmd("is_insert_mode"), { sessionId });
+}
+
+/**
+ * Get the rows visible through the backend-tracked scrollback viewport.
+ */
+export async function getViewport(sessionId: string): Promise<Viewport> {
+  return invoke<Viewport>(cmd("get_viewport"), { sessionId });
+}
+
+/**
+ * Scroll the viewport `deltaLines` (negative is up) and get the rows now
+ * visible. Output that autoscrolls snaps it back to the bottom.
+ */
+export async function scrollViewport(sessionId: string, deltaLines: number): Promise<Viewport> {
+  return invoke<Viewport>(cmd("scroll_viewport"), { sessionId, deltaLines });
+}
+
+/**
+ * Find `query` in the scrollback and screen. Searches forward (default)
+ * from after `fromLine`/`fromCol`, or backward from before it; without
+ * `fromLine` the search starts at the top or bottom of the buffer. Pass the
+ * previous match's position to find the next one. Case-insensitive unless
+ * `caseSensitive`.
+ */
+export async function searchScrollback(
+  sessionId: string,
+  query: string,
+  fromLine?: number,
+  fromCol?: number,
+  direction?: SearchDirection,
+  caseSensitive?: boolean
+): Promise<SearchMatch | null> {
+  return invoke<SearchMatch | null>(cmd("search_scrollback"), {
+    sessionId,
+    query,
+    fromLine,
+    fromCol,
+    direction,
+    caseSensitive,
+  });
+}
+
+/**
+ * Put buffer line `line` (0 = oldest scrollback line) at the top of the
+ * viewport, clamped to the scrollback, and get the rows now visible.
+ */
+export async function scrollTo(sessionId: string, line: number): Promise<Viewport> {
+  return invoke<Viewport>(cmd("scroll_to"), { sessionId, line });
+}
+
+/**
+ * Get the shell integration (OSC 133) marks still in the buffer, oldest
+ * first. Rows index the buffer as `exportAnsiRange` does (0 = oldest line).
+ */
+export async function getMarks(sessionId: string): Promise<Mark[]> {
+  return invoke<Mark[]>(cmd("get_marks"), { sessionId });
+}
+
+/**
+ * Get the columns with a tab stop set (zero-based).
+ */
+export async function getTabStops(sessionId: string): Promise<number[]> {
+  return invoke<number[]>(cmd("get_tab_stops"), { sessionId });
+}
+
+/**
+ * Largest grid that fits a `widthPx` × `heightPx` area with cells of the
+ * given (possibly fractional) pixel size; never smaller than 2×1.
+ */
+export async function fitCells(
+  widthPx: number,
+  heightPx: number,
+  cellWidth: number,
+  cellHeight: number
+): Promise<Size> {
+  return invoke<Size>(cmd("fit_cells"), { widthPx, heightPx, cellWidth, cellHeight });
+}
+
 /**
  * List available themes.
  */
@@ -108,23 +710,91 @@ export async function getSessionCount(): Promise<number> {
   return invoke<number>(cmd("get_session_count"));
 }
 
+/**
+ * Get how many more sessions can be created (null if unlimited).
+ */
+export async function getRemainingCapacity(): Promise<number | null> {
+  return invoke<number | null>(cmd("get_remaining_capacity"));
+}
+
 /**
  * Terminal API object for convenient access.
  */
 export const terminal = {
   createSession,
+  closeSession,
   destroySession,
+  createGroup,
+  addToGroup,
+  groupResize,
+  groupBroadcast,
+  destroyGroup,
   listSessions,
+  listSessionsFiltered,
   getSession,
+  getLastUpdate,
+  sessionsChangedSince,
+  sessionDiagnostics,
+  getBufferStats,
+  getTotalBufferStats,
   writeToSession,
   writeBytesToSession,
+  feedSession,
+  getSessionEnv,
+  exportSpawnCommand,
+  exportSnapshot,
+  importSnapshot,
+  setSessionEnv,
+  getMetadata,
+  setMetadata,
+  sendKey,
+  runScript,
+  sendLine,
+  expect,
+  getCommandOutput,
   resizeSession,
+  getPixelSize,
+  setPixelSize,
   getScreen,
+  getScreenHash,
+  getScreenText,
+  getCell,
+  getRegion,
+  getImages,
+  getImageData,
+  copySelectionOsc52,
+  exportAnsiRange,
+  exportText,
+  exportToFile,
   pollSession,
+  hasPendingOutput,
+  replayLastUpdate,
   getTheme,
+  getPalette,
   setTheme,
+  setThemeAnimated,
+  setCursorColor,
+  setColorOverride,
+  setEventFilter,
+  setBellMode,
+  setLogLevel,
+  setFlowControl,
+  setWrapMode,
+  softReset,
+  getViewport,
+  scrollViewport,
+  scrollTo,
+  searchScrollback,
+  getMarks,
+  getTabStops,
+  isAltScreen,
+  isInsertMode,
+  getCapabilities,
+  getCwd,
+  fitCells,
   listThemes,
   getSessionCount,
+  getRemainingCapacity,
 };
 
 export default terminal;
@@ -15,7 +15,7 @@ import type {
   TerminalEvent,
   Theme,
 } from "./types";
-import { TERMINAL_EVENTS } from "./types";
+import { TERMINAL_EVENTS, TerminalError } from "./types";
 
 /**
  * Hook for managing a terminal session.
@@ -57,7 +57,7 @@ export function useTerminal(sessionId: string | null) {
           const newRow = [];
           for (let col = 0; col <= maxCol; col++) {
             // Copy existing cell or create empty
-            newRow.push(prev.cells[row]?.[col] || { char: '', fg: { r: 255, g: 255, b: 255 }, bg: { r: 0, g: 0, b: 0 }, attrs: {} });
+            newRow.push(prev.cells[row]?.[col] || { char: '', fg: { r: 255, g: 255, b: 255 }, bg: { r: 0, g: 0, b: 0 }, attrs: {}, width: 1 });
           }
           newCells.push(newRow);
         }
@@ -80,6 +80,19 @@ export function useTerminal(sessionId: string | null) {
 
       // Normal incremental update
       const newCells = [...prev.cells.map((row) => [...row])];
+
+      // Apply a scroll-region shift before the cell changes
+      if (update.scroll) {
+        const { top, bottom, lines } = update.scroll;
+        const moved = prev.cells.slice(top, bottom + 1);
+        for (let row = top; row <= bottom; row++) {
+          const src = row - top + lines;
+          if (src >= 0 && src < moved.length) {
+            newCells[row] = [...moved[src]];
+          }
+        }
+      }
+
       for (const change of update.changes) {
         if (newCells[change.row]) {
           newCells[change.row][change.col] = change.cell;
@@ -115,6 +128,20 @@ export function useTerminal(sessionId: string | null) {
       );
       unlistenRef.current.push(unlistenScreen);
 
+      // Full refresh, sent instead of an update when most of the grid changed
+      const unlistenRefresh = await listen<{ session_id: string; screen: Screen }>(
+        TERMINAL_EVENTS.SCREEN_REFRESH,
+        (event) => {
+          if (event.payload.session_id === sessionId) {
+            setScreen(event.payload.screen);
+            if (event.payload.screen.title) {
+              setTitle(event.payload.screen.title);
+            }
+          }
+        }
+      );
+      unlistenRef.current.push(unlistenRefresh);
+
       // Title change
       const unlistenTitle = await listen<{ session_id: string; title: string }>(
         TERMINAL_EVENTS.TITLE_CHANGE,
@@ -162,7 +189,7 @@ export function useTerminal(sessionId: string | null) {
         setIsReady(true);
         setError(null);
       } catch (e) {
-        setError(e instanceof Error ? e : new Error(String(e)));
+        setError(TerminalError.from(e));
         setIsReady(false);
       }
     };
@@ -177,7 +204,7 @@ export function useTerminal(sessionId: string | null) {
       try {
         await api.writeToSession(sessionId, data);
       } catch (e) {
-        setError(e instanceof Error ? e : new Error(String(e)));
+        setError(TerminalError.from(e));
       }
     },
     [sessionId]
@@ -247,7 +274,7 @@ export function useTerminal(sessionId: string | null) {
         }
       } catch (e) {
         console.error(`[useTerminal] resize error:`, e);
-        setError(e instanceof Error ? e : new Error(String(e)));
+        setError(TerminalError.from(e));
       }
     },
     [sessionId]
@@ -261,7 +288,7 @@ export function useTerminal(sessionId: string | null) {
       setScreen(s);
       setTitle(s.title);
     } catch (e) {
-      setError(e instanceof Error ? e : new Error(String(e)));
+      setError(TerminalError.from(e));
     }
   }, [sessionId]);
 
@@ -298,7 +325,7 @@ export function useTerminalSession(config: SessionConfig = {}) {
       setSessionId(id);
       return id;
     } catch (e) {
-      const error = e instanceof Error ? e : new Error(String(e));
+      const error = TerminalError.from(e);
       setCreateError(error);
       throw error;
     } finally {
@@ -313,7 +340,7 @@ export function useTerminalSession(config: SessionConfig = {}) {
       await api.destroySession(sessionId);
       setSessionId(null);
     } catch (e) {
-      setCreateError(e instanceof Error ? e : new Error(String(e)));
+      setCreateError(TerminalError.from(e));
     }
   }, [sessionId]);
 
@@ -350,7 +377,7 @@ export function useTerminalSessions() {
       setSessions(list);
       setError(null);
     } catch (e) {
-      setError(e instanceof Error ? e : new Error(String(e)));
+      setError(TerminalError.from(e));
     }
   }, []);
 
@@ -413,7 +440,7 @@ export function useTerminalTheme(sessionId: string | null) {
         const t = await api.getTheme(sessionId);
         setThemeState(t);
       } catch (e) {
-        setError(e instanceof Error ? e : new Error(String(e)));
+        setError(TerminalError.from(e));
       }
     };
 
@@ -428,7 +455,7 @@ export function useTerminalTheme(sessionId: string | null) {
         setThemes(list);
         setIsLoading(false);
       } catch (e) {
-        setError(e instanceof Error ? e : new Error(String(e)));
+        setError(TerminalError.from(e));
         setIsLoading(false);
       }
     };
@@ -445,7 +472,7 @@ export function useTerminalTheme(sessionId: string | null) {
         const t = await api.getTheme(sessionId);
         setThemeState(t);
       } catch (e) {
-        setError(e instanceof Error ? e : new Error(String(e)));
+        setError(TerminalError.from(e));
       }
     },
     [sessionId]
@@ -24,41 +24,131 @@
 // Types
 export type {
   Size,
+  PixelSize,
   CursorPosition,
   CursorShape,
   Cursor,
+  BellMode,
+  LogLevel,
+  C1Mode,
+  ExportFormat,
+  DeviceReports,
   Color,
   CellAttributes,
   Cell,
   Row,
   Screen,
+  LinkSpan,
+  Viewport,
+  SearchDirection,
+  SearchMatch,
   CellChange,
+  ScrollRegionShift,
   ScreenUpdate,
+  ImagePlacement,
+  BufferStats,
+  Capabilities,
+  SpawnSpec,
+  MouseMode,
+  MouseEncoding,
   MarkType,
   Mark,
+  ScriptLineResult,
+  MatchRange,
+  TimeoutAction,
+  ExitReason,
+  KeyEvent,
+  KeyEventType,
+  BindingAction,
+  KeyBinding,
   SessionConfig,
+  SessionDiagnostics,
+  SessionFilter,
   SessionInfo,
+  SessionSnapshot,
   Theme,
   TerminalEvent,
+  EventCategory,
 } from "./types";
 
-export { TERMINAL_EVENTS, colorToCss, colorToHex } from "./types";
+export { TERMINAL_EVENTS, TerminalError, colorToCss, colorToHex } from "./types";
 
 // API
 export {
   createSession,
+  closeSession,
   destroySession,
+  createGroup,
+  addToGroup,
+  groupResize,
+  groupBroadcast,
+  destroyGroup,
   listSessions,
+  listSessionsFiltered,
   getSession,
+  getLastUpdate,
+  sessionsChangedSince,
+  sessionDiagnostics,
+  getBufferStats,
+  getTotalBufferStats,
   writeToSession,
   writeBytesToSession,
+  feedSession,
+  getSessionEnv,
+  exportSpawnCommand,
+  exportSnapshot,
+  importSnapshot,
+  setSessionEnv,
+  getMetadata,
+  setMetadata,
+  sendKey,
+  runScript,
+  sendLine,
+  expect,
+  getCommandOutput,
   resizeSession,
+  getPixelSize,
+  setPixelSize,
   getScreen,
+  getScreenHash,
+  getScreenText,
+  getCell,
+  getRegion,
+  getImages,
+  getImageData,
+  copySelectionOsc52,
+  exportAnsiRange,
+  exportText,
+  exportToFile,
   pollSession,
+  hasPendingOutput,
+  replayLastUpdate,
   getTheme,
+  getPalette,
   setTheme,
+  setThemeAnimated,
+  setCursorColor,
+  setColorOverride,
+  setEventFilter,
+  setBellMode,
+  setLogLevel,
+  setFlowControl,
+  setWrapMode,
+  softReset,
+  getViewport,
+  scrollViewport,
+  scrollTo,
+  searchScrollback,
+  getMarks,
+  getTabStops,
+  isAltScreen,
+  isInsertMode,
+  getCapabilities,
+  getCwd,
+  fitCells,
   listThemes,
   getSessionCount,
+  getRemainingCapacity,
   terminal,
 } from "./api";
 
@@ -8,6 +8,12 @@ export interface Size {
   rows: number;
 }
 
+/** Window size in pixels reported to the child; 0 means unknown. */
+export interface PixelSize {
+  width: number;
+  height: number;
+}
+
 /** Cursor position. */
 export interface CursorPosition {
   row: number;
@@ -22,6 +28,35 @@ export interface Cursor {
   position: CursorPosition;
   visible: boolean;
   shape: CursorShape;
+  /** Cursor color overriding the theme's, if one is set. */
+  color?: Color;
+  /** Position relative to the top margin while origin mode (DECOM) is on, as CPR reports it. */
+  origin_position?: CursorPosition;
+}
+
+/**
+ * How bytes 0x80–0x9F are interpreted: as UTF-8, or as 8-bit C1 controls
+ * for legacy programs (which also enables S8C1T replies).
+ */
+export type C1Mode = "utf8" | "eight_bit";
+
+/** Output format of `exportToFile`. */
+export type ExportFormat = "text" | "ansi";
+
+/** How the terminal bell is surfaced. */
+export type BellMode = "audible" | "visual" | "both" | "none";
+
+/** How much a session logs; `trace` includes every read and update. */
+export type LogLevel = "off" | "error" | "warn" | "info" | "debug" | "trace";
+
+/** Identity reported in reply to device queries (DA, XTVERSION). */
+export interface DeviceReports {
+  /** Primary DA parameters (default "62;22"). */
+  primary_da?: string;
+  /** Secondary DA parameters (default "1;10;0"). */
+  secondary_da?: string;
+  /** XTVERSION name. */
+  version?: string;
 }
 
 /** RGB color. */
@@ -48,6 +83,10 @@ export interface Cell {
   fg: Color;
   bg: Color;
   attrs: CellAttributes;
+  /** Columns occupied: 2 for a wide character, 0 for its continuation cell. */
+  width: number;
+  /** `char` is the replacement character standing in for a control character or noncharacter. */
+  substituted?: boolean;
 }
 
 /** A row of cells. */
@@ -60,6 +99,40 @@ export interface Screen {
   size: Size;
   scrollback_len: number;
   title: string;
+  /** Hyperlinks on the screen, from OSC 8 and URLs detected in the text. */
+  links?: LinkSpan[];
+}
+
+/** A hyperlink over part of a screen row. */
+export interface LinkSpan {
+  row: number;
+  start_col: number;
+  /** Column just past the end of the link. */
+  end_col: number;
+  url: string;
+  /** OSC 8 `id`, shared by the spans of one link; absent for detected URLs. */
+  id?: string;
+}
+
+/** Rows visible through the backend-tracked scrollback viewport. */
+export interface Viewport {
+  /** Buffer line of the first visible row; equals `scrollback_len` at the bottom. */
+  top_line: number;
+  scrollback_len: number;
+  rows: Row[];
+}
+
+/** Which way `searchScrollback` looks from its starting position. */
+export type SearchDirection = "forward" | "backward";
+
+/** A `searchScrollback` hit. */
+export interface SearchMatch {
+  /** Buffer line of the match (0 = oldest scrollback line). */
+  line: number;
+  /** Column of the first matched character. */
+  start_col: number;
+  /** Column just past the last matched character. */
+  end_col: number;
 }
 
 /** A change to a single cell. */
@@ -69,12 +142,106 @@ export interface CellChange {
   cell: Cell;
 }
 
+/** A scroll within the DECSTBM region, applied before `changes`. */
+export interface ScrollRegionShift {
+  top: number;
+  bottom: number;
+  /** Lines moved; positive scrolls content up. */
+  lines: number;
+}
+
 /** Incremental screen update. */
 export interface ScreenUpdate {
   session_id: string;
   changes: CellChange[];
   cursor: Cursor;
   title?: string;
+  scroll?: ScrollRegionShift;
+  /** Hint that the viewport should stick to the bottom. */
+  should_autoscroll: boolean;
+}
+
+/** Where an inline image (iTerm2 OSC 1337) sits; `row` is absolute like `Mark.row`. */
+export interface ImagePlacement {
+  id: number;
+  row: number;
+  col: number;
+  /** Size in cells. */
+  width: number;
+  height: number;
+  /** Native image size; 0 when the format isn't recognized. */
+  pixel_size: PixelSize;
+}
+
+/** Mouse events the running program asked to receive. */
+export type MouseMode = "none" | "press" | "press_release" | "button_motion" | "any_motion";
+
+/** How mouse reports are encoded. */
+export type MouseEncoding = "default" | "utf8" | "sgr";
+
+/** What a session supports and which modes the running program has on. */
+export interface Capabilities {
+  /** iTerm2 inline images (OSC 1337). */
+  inline_images: boolean;
+  sixel: boolean;
+  /** Clipboard writes through OSC 52. */
+  clipboard_write: boolean;
+  truecolor: boolean;
+  /** Whether the shell has emitted OSC 133 marks. */
+  shell_integration: boolean;
+  /** Maximum lines kept in scrollback. */
+  scrollback_lines: number;
+  /** `TERM` reported to the program. */
+  term: string;
+  mouse_mode: MouseMode;
+  mouse_encoding: MouseEncoding;
+  alt_screen: boolean;
+  bracketed_paste: boolean;
+  application_cursor: boolean;
+  /** Insert mode (IRM): typed text should push the rest of the line right. */
+  insert_mode: boolean;
+}
+
+/** The command a session's shell was started with, to relaunch it elsewhere. */
+export interface SpawnSpec {
+  program: string;
+  /** Arguments after the program name, login flags included. */
+  args: string[];
+  /** Directory the shell started in. */
+  cwd?: string;
+  /** Full environment, inherited variables included. */
+  env: Record<string, string>;
+}
+
+/**
+ * Portable copy of a session, for duplicating it elsewhere or attaching to
+ * bug reports. `env` and `screen` are informational; importing respawns the
+ * shell from `config` in `cwd` and replays `scrollback`.
+ */
+export interface SessionSnapshot {
+  config: SessionConfig;
+  env: Record<string, string>;
+  screen: Screen;
+  /** The whole buffer (scrollback and screen) as ANSI text. */
+  scrollback: string;
+  marks: Mark[];
+  cwd?: string;
+  size: Size;
+  theme: string;
+}
+
+/**
+ * Approximate memory held by a session's buffers, in bytes unless noted.
+ * Grid sizes are estimated from cell counts.
+ */
+export interface BufferStats {
+  /** Lines retained in scrollback. */
+  scrollback_lines: number;
+  scrollback_bytes: number;
+  /** Base64 payloads of retained inline images. */
+  image_bytes: number;
+  /** Primary and alternate screen grids. */
+  grid_bytes: number;
 }
 
 /** Shell integration mark type. */
@@ -89,22 +256,143 @@ export interface Mark {
   exit_code?: number;
 }
 
+/** Outcome of one line submitted by `runScript`. */
+export interface ScriptLineResult {
+  command: string;
+  exit_code?: number;
+  /** Unset when the line was taken as finished because output went quiet (no OSC 133), so its result is unknown. */
+  success?: boolean;
+  /** The command's output; only with OSC 133 shell integration. */
+  output?: string;
+}
+
+/** What `runScript` does to a line still running at its timeout. */
+export type TimeoutAction = "leave" | "interrupt" | "kill";
+
+/** Text found by `expect`; offsets count the session's output with escape sequences stripped. */
+export interface MatchRange {
+  start: number;
+  end: number;
+  text: string;
+}
+
+/** A key press; `key` uses DOM `KeyboardEvent.key` values. */
+export interface KeyEvent {
+  key: string;
+  ctrl?: boolean;
+  shift?: boolean;
+  alt?: boolean;
+  meta?: boolean;
+  /** Repeats and releases are only sent when the program enabled the kitty keyboard protocol. */
+  event_type?: KeyEventType;
+}
+
+/** Kind of key event. */
+export type KeyEventType = "press" | "repeat" | "release";
+
+/** Action triggered by a key binding. */
+export type BindingAction = "copy" | "paste" | "interrupt" | { custom: string };
+
+/** Maps a key and exact modifier set to an action handled by the app. */
+export interface KeyBinding extends KeyEvent {
+  action: BindingAction;
+}
+
+/** Why a session's process ended. */
+export type ExitReason =
+  | "normal"
+  | "signal"
+  | "killed"
+  | "idle_timeout"
+  | "output_limit"
+  | "reader_error";
+
 /** Session configuration. */
 export interface SessionConfig {
   /** Optional session ID (generated if not provided). */
   id?: string;
   /** Working directory. */
   cwd?: string;
+  /** Start in the home directory instead of failing when `cwd` is invalid. */
+  cwd_fallback?: boolean;
   /** Shell to use. */
   shell?: string;
   /** Environment variables. */
   env?: Record<string, string>;
+  /** TERM value (default "xterm-256color"); `env` takes precedence. */
+  term?: string;
   /** Initial columns. */
   cols?: number;
   /** Initial rows. */
   rows?: number;
   /** Theme name. */
   theme?: string;
+  /** How the bell is surfaced (default "audible"). */
+  bell_mode?: BellMode;
+  /** Minimum milliseconds between bell events; bursts surface once (default 100). */
+  bell_interval_ms?: number;
+  /** Identity reported in reply to device queries. */
+  device_reports?: DeviceReports;
+  /** Diff scrolls inside a scroll region as shifts (default true). */
+  scroll_region_diffing?: boolean;
+  /** Minimum WCAG contrast ratio between text and background (e.g. 4.5). */
+  a11y_contrast?: number;
+  /** Shown in place of control characters and noncharacters (default `"\uFFFD"`). */
+  replacement_char?: string;
+  /** RGB values replacing indexed colors on top of the theme, keyed by index (e.g. `{ "1": orange }`). */
+  color_overrides?: Record<number, Color>;
+  /** Total output sizes (bytes) at which an `output_threshold` event fires. */
+  output_byte_thresholds?: number[];
+  /** Kill the session once its total output exceeds this many bytes. */
+  output_byte_limit?: number;
+  /** Shortcuts handled by the app instead of being sent to the shell. */
+  key_bindings?: KeyBinding[];
+  /** Destroy the session after this many milliseconds without input or output. */
+  idle_timeout_ms?: number;
+  /** Remove the session this many milliseconds after it exits; by default it stays until closed. */
+  exit_retention_ms?: number;
+  /** Merge output from the first this-many milliseconds into one update (off by default). */
+  initial_coalesce_ms?: number;
+  /** Advise following new output to the bottom (default true). */
+  scroll_on_output?: boolean;
+  /** Advise jumping to the bottom when the user types (default true). */
+  scroll_on_input?: boolean;
+  /** Ctrl-S stops reading output and Ctrl-Q resumes it; neither byte reaches the program. */
+  flow_control?: boolean;
+  /** Force wrapping on or off, ignoring DECAWM. Truncated text isn't restored on resize. */
+  wrap_override?: boolean;
+  /** Draw the DEC special graphics charset (ncurses borders) as box-drawing characters (default true). */
+  line_drawing?: boolean;
+  /** Interpret bytes 0x80–0x9F as UTF-8 (default) or 8-bit C1 controls. */
+  c1_controls?: C1Mode;
+  /** Index the scrollback's text for fast repeated searches, at about twice its memory. */
+  search_index?: boolean;
+  /**
+   * Emulate output in Rust (default true). When false, output arrives only
+   * as base64 `raw_output` events and screen queries return an empty screen.
+   */
+  emulate?: boolean;
+  /**
+   * Template for the session title, e.g. `"[app] {title} - {cwd}"`, with
+   * `{title}`, `{cwd}`, `{shell}` and `{id}` placeholders.
+   */
+  title_template?: string;
+  /** Arbitrary app data kept with the session (tab group, project id, ...). */
+  metadata?: Record<string, string>;
+  /** Start the shell as an interactive login shell so profile files load PATH and aliases. */
+  login_shell?: boolean;
+  /** Extra arguments for the shell, after any login flags. */
+  shell_args?: string[];
+  /** Sent by `closeSession` to ask the program to exit (default `"exit\n"`; `"\u0004"` for EOF). */
+  close_sequence?: string;
+  /** How long `closeSession` waits before killing the program (default 3000). */
+  close_timeout_ms?: number;
+  /** Emit an `input_audit` event for every write. Events contain everything typed. */
+  audit_input?: boolean;
+  /** Withhold audited bytes while echo is off (password prompts; Unix only). */
+  redact_passwords?: boolean;
+  /** How much the session logs (default `warn`). */
+  log_level?: LogLevel;
 }
 
 /** Session information. */
@@ -116,6 +404,35 @@ export interface SessionInfo {
   size: Size;
   is_alive: boolean;
   created_at: number;
+  /** Logical time of the last screen update (0 if none yet). */
+  last_update: number;
+  metadata: Record<string, string>;
+}
+
+/** Conditions for `listSessionsFiltered`; all given conditions must hold. */
+export interface SessionFilter {
+  /** Only sessions whose process is still running. */
+  alive_only?: boolean;
+  /** Metadata entries that must be present with these exact values. */
+  metadata?: Record<string, string>;
+  /** Shell executable name (e.g. `zsh`), without its path. */
+  shell?: string;
+}
+
+/** Health snapshot of a session, for debugging stuck terminals. */
+export interface SessionDiagnostics {
+  id: string;
+  is_alive: boolean;
+  pid?: number;
+  bytes_read: number;
+  bytes_written: number;
+  /** Screen updates emitted so far. */
+  frames_emitted: number;
+  /** Milliseconds since the Unix epoch of the last output. */
+  last_output_at?: number;
+  size: Size;
+  alternate_screen: boolean;
+  mark_count: number;
 }
 
 /** Terminal theme. */
@@ -150,29 +467,58 @@ export type TerminalEvent =
   | { type: "session_created"; session_id: string }
   | { type: "session_destroyed"; session_id: string }
   | { type: "terminal_resized"; session_id: string; cols: number; rows: number }
-  | { type: "screen_update"; session_id: string; changes: CellChange[]; cursor: Cursor; title?: string }
-  | { type: "screen_refresh"; session_id: string; screen: string }
+  | { type: "screen_update"; session_id: string; changes: CellChange[]; cursor: Cursor; title?: string; scroll?: ScrollRegionShift; should_autoscroll: boolean }
+  | { type: "raw_output"; session_id: string; data: string }
+  | { type: "screen_refresh"; session_id: string; screen: Screen }
   | { type: "bell"; session_id: string }
+  | { type: "visual_bell"; session_id: string }
   | { type: "title_change"; session_id: string; title: string }
+  | { type: "theme_change"; session_id: string; theme: Theme }
   | { type: "directory_change"; session_id: string; cwd: string }
   | { type: "mark"; session_id: string; mark: Mark }
-  | { type: "process_exit"; session_id: string; exit_code?: number }
+  | { type: "output_threshold"; session_id: string; bytes: number }
+  | { type: "binding"; session_id: string; action: BindingAction }
+  | { type: "input_audit"; session_id: string; timestamp: number; data: string; len: number; redacted: boolean }
+  | { type: "process_exit"; session_id: string; exit_code?: number; signal?: number; reason: ExitReason }
   | { type: "cursor_move"; session_id: string; cursor: Cursor }
   | { type: "selection_change"; session_id: string; text?: string }
   | { type: "clipboard_request"; session_id: string; content: string }
   | { type: "hyperlink"; session_id: string; url: string; row: number; start_col: number; end_col: number };
 
+/**
+ * Groups of events that can be muted per session. Lifecycle events
+ * (`session_created`, `session_destroyed`, `terminal_resized`,
+ * `process_exit`) can't be muted.
+ */
+export type EventCategory =
+  | "screen_update"
+  | "cursor_move"
+  | "mark"
+  | "hyperlink"
+  | "activity"
+  | "title"
+  | "theme"
+  | "selection"
+  | "clipboard"
+  | "input";
+
 /** Event names for Tauri event listeners. */
 export const TERMINAL_EVENTS = {
   SESSION_CREATED: "terminal://session-created",
   SESSION_DESTROYED: "terminal://session-destroyed",
   TERMINAL_RESIZED: "terminal://terminal-resized",
   SCREEN_UPDATE: "terminal://screen-update",
+  RAW_OUTPUT: "terminal://raw-output",
   SCREEN_REFRESH: "terminal://screen-refresh",
   BELL: "terminal://bell",
+  VISUAL_BELL: "terminal://visual-bell",
   TITLE_CHANGE: "terminal://title-change",
+  THEME_CHANGE: "terminal://theme-change",
   DIRECTORY_CHANGE: "terminal://directory-change",
   MARK: "terminal://mark",
+  OUTPUT_THRESHOLD: "terminal://output-threshold",
+  BINDING: "terminal://binding",
+  INPUT_AUDIT: "terminal://input-audit",
   PROCESS_EXIT: "terminal://process-exit",
   CURSOR_MOVE: "terminal://cursor-move",
   SELECTION_CHANGE: "terminal://selection-change",
@@ -180,6 +526,25 @@ export const TERMINAL_EVENTS = {
   HYPERLINK: "terminal://hyperlink",
 } as const;
 
+/** Error returned by plugin commands: a stable code plus a message. */
+export class TerminalError extends Error {
+  constructor(public code: string, message: string) {
+    super(message);
+    this.name = "TerminalError";
+  }
+
+  /**
+   * Normalize a rejected command value into an Error. Commands reject with
+   * a string ending in the code, e.g. `Session not found: abc [session_not_found]`.
+   */
+  static from(e: unknown): Error {
+    if (e instanceof Error) return e;
+    const match = typeof e === "string" ? /^([\s\S]*) \[([a-z_]+)\]$/.exec(e) : null;
+    if (match) return new TerminalError(match[2], match[1]);
+    return new Error(String(e));
+  }
+}
+
 /** Convert Color to CSS rgb string. */
 export function colorToCss(color: Color): string {
   return `rgb(${color.r}, ${color.g}, ${color.b})`;
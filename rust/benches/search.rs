@@ -0,0 +1,40 @@
+//! Scrollback search over a full 10k-line buffer, with and without the
+//! search index.
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use tauri_plugin_terminal::{SearchDirection, Terminal};
+
+const LINES: usize = 10_000;
+
+fn filled_terminal(index: bool) -> Terminal {
+    let mut terminal = Terminal::new(80, 24);
+    terminal.set_search_index(index);
+    let output: String = (0..LINES)
+        .map(|n| format!("{:05} compiling crate-{} v0.{}.{} (/src/crates/crate-{})\r\n", n, n % 97, n % 13, n % 7, n))
+        .collect();
+    terminal.process(output.as_bytes());
+    terminal
+}
+
+fn bench_search(c: &mut Criterion) {
+    for (name, index) in [("scan", false), ("index", true)] {
+        let mut terminal = filled_terminal(index);
+        // Build the index outside the timed loop, as the first find would
+        terminal.search("warmup", None, SearchDirection::Backward, false);
+
+        let mut group = c.benchmark_group(format!("search_10k/{}", name));
+        group.bench_function("oldest_line", |b| {
+            b.iter(|| terminal.search(black_box("00000 compiling"), None, SearchDirection::Backward, true))
+        });
+        group.bench_function("no_match", |b| {
+            b.iter(|| terminal.search(black_box("error[E0308]"), None, SearchDirection::Backward, true))
+        });
+        group.bench_function("case_insensitive", |b| {
+            b.iter(|| terminal.search(black_box("CRATE-9999 V0"), None, SearchDirection::Forward, false))
+        });
+        group.finish();
+    }
+}
+
+criterion_group!(benches, bench_search);
+criterion_main!(benches);
@@ -14,6 +14,67 @@ const COMMANDS: &[&str] = &[
     "set_theme",
     "list_themes",
     "get_session_count",
+    "set_bell_mode",
+    "export_ansi_range",
+    "set_theme_animated",
+    "get_tab_stops",
+    "export_text",
+    "session_diagnostics",
+    "get_pixel_size",
+    "set_pixel_size",
+    "get_screen_text",
+    "send_key",
+    "set_session_env",
+    "get_remaining_capacity",
+    "get_last_update",
+    "sessions_changed_since",
+    "get_screen_hash",
+    "run_script",
+    "copy_selection_osc52",
+    "set_wrap_mode",
+    "get_cell",
+    "get_images",
+    "get_image_data",
+    "get_palette",
+    "set_cursor_color",
+    "get_command_output",
+    "set_event_filter",
+    "get_buffer_stats",
+    "get_total_buffer_stats",
+    "soft_reset",
+    "is_alt_screen",
+    "get_cwd",
+    "get_metadata",
+    "set_metadata",
+    "close_session",
+    "get_capabilities",
+    "send_line",
+    "expect",
+    "set_color_override",
+    "fit_cells",
+    "create_group",
+    "add_to_group",
+    "group_resize",
+    "group_broadcast",
+    "destroy_group",
+    "export_to_file",
+    "get_marks",
+    "set_flow_control",
+    "feed_session",
+    "get_viewport",
+    "scroll_viewport",
+    "scroll_to",
+    "get_session_env",
+    "get_region",
+    "list_sessions_filtered",
+    "replay_last_update",
+    "has_pending_output",
+    "search_scrollback",
+    "is_insert_mode",
+    "export_spawn_command",
+    "export_snapshot",
+    "import_snapshot",
+    "set_log_level",
 ];
 
 fn main() {
@@ -0,0 +1,206 @@
+//! WebSocket bridge for rendering sessions outside the Tauri webview.
+//!
+//! Enabled with the `bridge` feature. Clients exchange JSON messages:
+//! requests are [`BridgeRequest`]s, replies and forwarded events are
+//! [`BridgeMessage`]s. The server binds to localhost by default and rejects
+//! non-loopback peers unless `allow_remote` is set.
+
+use crate::error::{Error, Result};
+use crate::events::{EventReceiver, EventSender, TerminalEvent};
+use crate::session::SessionManager;
+use crate::types::Screen;
+use futures_util::{SinkExt, StreamExt};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::broadcast;
+use tokio_tungstenite::tungstenite::Message;
+
+/// Capacity of the event fan-out channel; slower clients resync on lag.
+const EVENT_CAPACITY: usize = 1024;
+
+/// Bridge server configuration.
+#[derive(Debug, Clone)]
+pub struct BridgeConfig {
+    /// Address to listen on (default `127.0.0.1:7681`).
+    pub addr: SocketAddr,
+    /// Accept connections from non-loopback peers.
+    pub allow_remote: bool,
+}
+
+impl Default for BridgeConfig {
+    fn default() -> Self {
+        Self {
+            addr: SocketAddr::from(([127, 0, 0, 1], 7681)),
+            allow_remote: false,
+        }
+    }
+}
+
+/// Messages accepted from bridge clients.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum BridgeRequest {
+    /// Start receiving a session's events; replies with its screen.
+    Attach { session_id: String },
+    /// Stop receiving a session's events.
+    Detach { session_id: String },
+    /// Write input to a session.
+    Write { session_id: String, data: String },
+    /// Resize a session.
+    Resize { session_id: String, cols: u16, rows: u16 },
+    /// Fetch a session's full screen.
+    GetScreen { session_id: String },
+}
+
+/// Messages sent to bridge clients.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum BridgeMessage {
+    /// Full screen state for a session.
+    Screen { session_id: String, screen: Screen },
+    /// A terminal event for an attached session.
+    Event { event: TerminalEvent },
+    /// A request failed.
+    Error { message: String },
+}
+
+/// Fans terminal events out to WebSocket clients.
+pub struct Bridge {
+    config: BridgeConfig,
+    events: broadcast::Sender<TerminalEvent>,
+}
+
+impl Bridge {
+    /// Create a bridge with the given configuration.
+    pub fn new(config: BridgeConfig) -> Self {
+        let (events, _) = broadcast::channel(EVENT_CAPACITY);
+        Self { config, events }
+    }
+
+    /// Publish an event to connected clients.
+    pub fn publish(&self, event: &TerminalEvent) {
+        // No subscribers is fine
+        let _ = self.events.send(event.clone());
+    }
+
+    /// Publish every event from `receiver` and pass it on to `sender`.
+    pub async fn tee(self: Arc<Self>, mut receiver: EventReceiver, sender: EventSender) {
+        while let Some(event) = receiver.recv().await {
+            self.publish(&event);
+            if sender.send(event).is_err() {
+                break;
+            }
+        }
+    }
+
+    /// Accept clients until the listener fails.
+    pub async fn serve(self: Arc<Self>, manager: Arc<SessionManager>) -> Result<()> {
+        let listener = TcpListener::bind(self.config.addr).await?;
+        log::info!("Terminal bridge listening on {}", self.config.addr);
+
+        loop {
+            let (stream, peer) = listener.accept().await?;
+            if !self.config.allow_remote && !peer.ip().is_loopback() {
+                log::warn!("Rejected non-local bridge client {}", peer);
+                continue;
+            }
+
+            let bridge = self.clone();
+            let manager = manager.clone();
+            tokio::spawn(async move {
+                if let Err(e) = bridge.handle_client(stream, manager).await {
+                    log::warn!("Bridge client {} disconnected: {}", peer, e);
+                }
+            });
+        }
+    }
+
+    async fn handle_client(&self, stream: TcpStream, manager: Arc<SessionManager>) -> Result<()> {
+        let ws = tokio_tungstenite::accept_async(stream)
+            .await
+            .map_err(|e| Error::IoError(e.to_string()))?;
+        let (mut sink, mut source) = ws.split();
+        let mut events = self.events.subscribe();
+        let mut attached: HashSet<String> = HashSet::new();
+
+        loop {
+            let outgoing: Vec<BridgeMessage> = tokio::select! {
+                incoming = source.next() => {
+                    let Some(incoming) = incoming else { break };
+                    match incoming.map_err(|e| Error::IoError(e.to_string()))? {
+                        Message::Text(text) => {
+                            handle_request(&text, &manager, &mut attached).into_iter().collect()
+                        }
+                        Message::Close(_) => break,
+                        _ => Vec::new(),
+                    }
+                }
+                event = events.recv() => match event {
+                    Ok(event) if attached.contains(event.session_id()) => {
+                        vec![BridgeMessage::Event { event }]
+                    }
+                    Ok(_) => Vec::new(),
+                    // Missed events; resend full screens so the client can resync
+                    Err(broadcast::error::RecvError::Lagged(_)) => attached
+                        .iter()
+                        .filter_map(|id| screen_message(&manager, id).ok())
+                        .collect(),
+                    Err(broadcast::error::RecvError::Closed) => break,
+                },
+            };
+
+            for message in outgoing {
+                let text = serde_json::to_string(&message)
+                    .map_err(|e| Error::TerminalError(e.to_string()))?;
+                sink.send(Message::Text(text))
+                    .await
+                    .map_err(|e| Error::IoError(e.to_string()))?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn handle_request(
+    text: &str,
+    manager: &SessionManager,
+    attached: &mut HashSet<String>,
+) -> Option<BridgeMessage> {
+    let request: BridgeRequest = match serde_json::from_str(text) {
+        Ok(request) => request,
+        Err(e) => return Some(BridgeMessage::Error { message: e.to_string() }),
+    };
+
+    let result = match request {
+        BridgeRequest::Attach { session_id } => {
+            screen_message(manager, &session_id).map(|message| {
+                attached.insert(session_id);
+                Some(message)
+            })
+        }
+        BridgeRequest::Detach { session_id } => {
+            attached.remove(&session_id);
+            Ok(None)
+        }
+        BridgeRequest::Write { session_id, data } => {
+            manager.write(&session_id, data.as_bytes()).map(|_| None)
+        }
+        BridgeRequest::Resize { session_id, cols, rows } => {
+            manager.resize(&session_id, cols, rows).map(|_| None)
+        }
+        BridgeRequest::GetScreen { session_id } => screen_message(manager, &session_id).map(Some),
+    };
+
+    result.unwrap_or_else(|e| Some(BridgeMessage::Error { message: e.to_string() }))
+}
+
+fn screen_message(manager: &SessionManager, session_id: &str) -> Result<BridgeMessage> {
+    Ok(BridgeMessage::Screen {
+        session_id: session_id.to_string(),
+        screen: manager.get_screen(session_id)?,
+    })
+}
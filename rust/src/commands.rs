@@ -1,16 +1,20 @@
 //! Tauri commands for the terminal plugin.
 
-use crate::error::Result;
-use crate::session::{SessionConfig, SessionId, SessionInfo, SessionManager};
+use crate::error::{Error, Result};
+use crate::events::{EventCategory, EventFilters};
+use crate::session::{SCRIPT_POLL_INTERVAL, SessionConfig, SessionDiagnostics, SessionFilter, SessionId, SessionInfo, SessionManager, SessionSnapshot};
 use crate::theme::Theme;
-use crate::types::{Screen, ScreenUpdate};
-use serde::{Deserialize, Serialize};
+use crate::types::{BellMode, BufferStats, Capabilities, Cell, Color, ExportFormat, Size, CursorPosition, ImagePlacement, KeyEvent, LogLevel, Mark, MatchRange, PixelSize, Row, Screen, ScreenUpdate, ScriptLineResult, SearchDirection, SearchMatch, SpawnSpec, TimeoutAction, Viewport};
+use regex::Regex;
+use std::collections::HashMap;
 use std::sync::Arc;
+use std::time::Duration;
 use tauri::{command, AppHandle, Runtime, State};
 
 /// Plugin state.
 pub struct TerminalState {
     pub manager: Arc<SessionManager>,
+    pub event_filters: Arc<EventFilters>,
 }
 
 /// Create a new terminal session.
@@ -23,6 +27,30 @@ pub async fn create_session<R: Runtime>(
     state.manager.create(config)
 }
 
+/// Capture a session as a portable JSON snapshot (config, environment,
+/// screen, scrollback, marks, working directory, size and theme), for
+/// duplicating it elsewhere or attaching to bug reports.
+#[command]
+pub async fn export_snapshot<R: Runtime>(
+    _app: AppHandle<R>,
+    state: State<'_, TerminalState>,
+    session_id: String,
+) -> Result<SessionSnapshot> {
+    state.manager.export_snapshot(&session_id)
+}
+
+/// Create a session from a snapshot, respawning its shell and replaying
+/// its scrollback. A new id is generated unless `session_id` is given.
+#[command]
+pub async fn import_snapshot<R: Runtime>(
+    _app: AppHandle<R>,
+    state: State<'_, TerminalState>,
+    snapshot: SessionSnapshot,
+    session_id: Option<String>,
+) -> Result<SessionId> {
+    state.manager.import_snapshot(snapshot, session_id)
+}
+
 /// Destroy a terminal session.
 #[command]
 pub async fn destroy_session<R: Runtime>(
@@ -33,6 +61,101 @@ pub async fn destroy_session<R: Runtime>(
     state.manager.destroy(&session_id)
 }
 
+/// Create a named group of sessions, e.g. the panes of a tab, so they can
+/// be resized, typed into or destroyed together.
+#[command]
+pub async fn create_group<R: Runtime>(
+    _app: AppHandle<R>,
+    state: State<'_, TerminalState>,
+    name: String,
+    session_ids: Vec<String>,
+) -> Result<()> {
+    state.manager.create_group(&name, session_ids)
+}
+
+/// Add a session to a group.
+#[command]
+pub async fn add_to_group<R: Runtime>(
+    _app: AppHandle<R>,
+    state: State<'_, TerminalState>,
+    name: String,
+    session_id: String,
+) -> Result<()> {
+    state.manager.add_to_group(&name, &session_id)
+}
+
+/// Resize every session in a group.
+#[command]
+pub async fn group_resize<R: Runtime>(
+    _app: AppHandle<R>,
+    state: State<'_, TerminalState>,
+    name: String,
+    cols: u16,
+    rows: u16,
+) -> Result<()> {
+    state.manager.group_resize(&name, cols, rows)
+}
+
+/// Write the same data to every session in a group.
+#[command]
+pub async fn group_broadcast<R: Runtime>(
+    _app: AppHandle<R>,
+    state: State<'_, TerminalState>,
+    name: String,
+    data: String,
+) -> Result<()> {
+    state.manager.group_broadcast(&name, data.as_bytes())
+}
+
+/// Destroy every session in a group and the group itself.
+#[command]
+pub async fn destroy_group<R: Runtime>(
+    _app: AppHandle<R>,
+    state: State<'_, TerminalState>,
+    name: String,
+) -> Result<()> {
+    state.manager.destroy_group(&name)
+}
+
+/// How often `close_session` checks whether the program has exited.
+const CLOSE_POLL_INTERVAL: Duration = Duration::from_millis(20);
+
+/// Close a session gracefully: write its close sequence (`exit` by default)
+/// and wait for the program to exit, so shells can save their history,
+/// before destroying it. Programs still running after the close timeout are
+/// killed as with `destroy_session`.
+#[command]
+pub async fn close_session<R: Runtime>(
+    _app: AppHandle<R>,
+    state: State<'_, TerminalState>,
+    session_id: String,
+) -> Result<()> {
+    let manager = &state.manager;
+    match manager.request_close(&session_id) {
+        Ok(timeout) => {
+            let deadline = tokio::time::Instant::now() + timeout;
+            while tokio::time::Instant::now() < deadline {
+                match manager.poll_exit(&session_id) {
+                    Ok(true) => break,
+                    Ok(false) => tokio::time::sleep(CLOSE_POLL_INTERVAL).await,
+                    // Reaped by the background cleanup after exiting
+                    Err(Error::SessionNotFound(_)) => return Ok(()),
+                    Err(e) => return Err(e),
+                }
+            }
+        }
+        Err(e @ Error::SessionNotFound(_)) => return Err(e),
+        // The program already exited and can't take the sequence
+        Err(_) => {}
+    }
+
+    match manager.destroy(&session_id) {
+        // Reaped by the background cleanup since the last poll
+        Err(Error::SessionNotFound(_)) => Ok(()),
+        result => result,
+    }
+}
+
 /// List all sessions.
 #[command]
 pub async fn list_sessions<R: Runtime>(
@@ -42,6 +165,16 @@ pub async fn list_sessions<R: Runtime>(
     Ok(state.manager.list())
 }
 
+/// List sessions matching a filter on liveness, metadata and shell.
+#[command]
+pub async fn list_sessions_filtered<R: Runtime>(
+    _app: AppHandle<R>,
+    state: State<'_, TerminalState>,
+    filter: SessionFilter,
+) -> Result<Vec<SessionInfo>> {
+    Ok(state.manager.list_filtered(&filter))
+}
+
 /// Get session info.
 #[command]
 pub async fn get_session<R: Runtime>(
@@ -52,6 +185,56 @@ pub async fn get_session<R: Runtime>(
     state.manager.get_info(&session_id)
 }
 
+/// Get the logical time of a session's last screen update (0 if none yet).
+#[command]
+pub async fn get_last_update<R: Runtime>(
+    _app: AppHandle<R>,
+    state: State<'_, TerminalState>,
+    session_id: String,
+) -> Result<u64> {
+    state.manager.last_update(&session_id)
+}
+
+/// List sessions with a screen update after the logical time `since`.
+#[command]
+pub async fn sessions_changed_since<R: Runtime>(
+    _app: AppHandle<R>,
+    state: State<'_, TerminalState>,
+    since: u64,
+) -> Result<Vec<SessionId>> {
+    Ok(state.manager.changed_since(since))
+}
+
+/// Get counters and state for troubleshooting a session.
+#[command]
+pub async fn session_diagnostics<R: Runtime>(
+    _app: AppHandle<R>,
+    state: State<'_, TerminalState>,
+    session_id: String,
+) -> Result<SessionDiagnostics> {
+    state.manager.diagnostics(&session_id)
+}
+
+/// Get the approximate memory held by a session's scrollback, screen and
+/// inline images.
+#[command]
+pub async fn get_buffer_stats<R: Runtime>(
+    _app: AppHandle<R>,
+    state: State<'_, TerminalState>,
+    session_id: String,
+) -> Result<BufferStats> {
+    state.manager.buffer_stats(&session_id)
+}
+
+/// Get buffer memory summed over all sessions.
+#[command]
+pub async fn get_total_buffer_stats<R: Runtime>(
+    _app: AppHandle<R>,
+    state: State<'_, TerminalState>,
+) -> Result<BufferStats> {
+    Ok(state.manager.total_buffer_stats())
+}
+
 /// Write data to a session.
 #[command]
 pub async fn write_to_session<R: Runtime>(
@@ -63,6 +246,21 @@ pub async fn write_to_session<R: Runtime>(
     state.manager.write(&session_id, data.as_bytes())
 }
 
+/// Run bytes through a session's emulator as if the program had written
+/// them, without writing anything to the PTY. For replaying recorded output
+/// and priming a terminal with canned content; not in the default
+/// permission set.
+#[command]
+pub async fn feed_session<R: Runtime>(
+    _app: AppHandle<R>,
+    state: State<'_, TerminalState>,
+    session_id: String,
+    data: Vec<u8>,
+) -> Result<()> {
+    state.manager.feed(&session_id, &data)?;
+    Ok(())
+}
+
 /// Write binary data to a session.
 #[command]
 pub async fn write_bytes_to_session<R: Runtime>(
@@ -74,6 +272,80 @@ pub async fn write_bytes_to_session<R: Runtime>(
     state.manager.write(&session_id, &data)
 }
 
+/// Set an environment variable for a session's future children, optionally
+/// exporting it in the running shell right away.
+#[command]
+pub async fn set_session_env<R: Runtime>(
+    _app: AppHandle<R>,
+    state: State<'_, TerminalState>,
+    session_id: String,
+    key: String,
+    value: String,
+    apply_now: Option<bool>,
+) -> Result<()> {
+    state
+        .manager
+        .set_env(&session_id, &key, &value, apply_now.unwrap_or(false))
+}
+
+/// Get the full environment the session's shell was spawned with, after
+/// inheritance and overrides, for debugging things like a wrong PATH.
+/// Empty for sessions attached to an external PTY.
+#[command]
+pub async fn get_session_env<R: Runtime>(
+    _app: AppHandle<R>,
+    state: State<'_, TerminalState>,
+    session_id: String,
+) -> Result<HashMap<String, String>> {
+    state.manager.spawn_env(&session_id)
+}
+
+/// Get the program, arguments, directory and environment the session's
+/// shell was spawned with, so the app can open an equivalent shell in the
+/// system terminal. `None` for sessions attached to an external PTY.
+#[command]
+pub async fn export_spawn_command<R: Runtime>(
+    _app: AppHandle<R>,
+    state: State<'_, TerminalState>,
+    session_id: String,
+) -> Result<Option<SpawnSpec>> {
+    state.manager.export_spawn_command(&session_id)
+}
+
+/// Get the app metadata attached to a session.
+#[command]
+pub async fn get_metadata<R: Runtime>(
+    _app: AppHandle<R>,
+    state: State<'_, TerminalState>,
+    session_id: String,
+) -> Result<HashMap<String, String>> {
+    state.manager.metadata(&session_id)
+}
+
+/// Set a session metadata entry; a `None` value removes it.
+#[command]
+pub async fn set_metadata<R: Runtime>(
+    _app: AppHandle<R>,
+    state: State<'_, TerminalState>,
+    session_id: String,
+    key: String,
+    value: Option<String>,
+) -> Result<()> {
+    state.manager.set_metadata(&session_id, key, value)
+}
+
+/// Send a key press, encoded for the terminal's current modes. Keys that
+/// match a session key binding emit a `Binding` event instead.
+#[command]
+pub async fn send_key<R: Runtime>(
+    _app: AppHandle<R>,
+    state: State<'_, TerminalState>,
+    session_id: String,
+    key: KeyEvent,
+) -> Result<()> {
+    state.manager.send_key(&session_id, &key)
+}
+
 /// Resize a session.
 #[command]
 pub async fn resize_session<R: Runtime>(
@@ -89,6 +361,33 @@ pub async fn resize_session<R: Runtime>(
     result
 }
 
+/// Get the window size in pixels reported to a session's child.
+#[command]
+pub async fn get_pixel_size<R: Runtime>(
+    _app: AppHandle<R>,
+    state: State<'_, TerminalState>,
+    session_id: String,
+) -> Result<PixelSize> {
+    state.manager.pixel_size(&session_id)
+}
+
+/// Set the window size in pixels reported to a session's child, leaving
+/// the cell grid unchanged.
+#[command]
+pub async fn set_pixel_size<R: Runtime>(
+    _app: AppHandle<R>,
+    state: State<'_, TerminalState>,
+    session_id: String,
+    width_px: u16,
+    height_px: u16,
+) -> Result<()> {
+    let pixels = PixelSize {
+        width: width_px,
+        height: height_px,
+    };
+    state.manager.set_pixel_size(&session_id, pixels)
+}
+
 /// Get the full screen state.
 #[command]
 pub async fn get_screen<R: Runtime>(
@@ -103,6 +402,139 @@ pub async fn get_screen<R: Runtime>(
     Ok(screen)
 }
 
+/// Get a fingerprint of the visible grid, covering text and styles.
+///
+/// Returned as a string since u64 doesn't round-trip through JS numbers.
+#[command]
+pub async fn get_screen_hash<R: Runtime>(
+    _app: AppHandle<R>,
+    state: State<'_, TerminalState>,
+    session_id: String,
+) -> Result<String> {
+    Ok(format!("{:016x}", state.manager.screen_hash(&session_id)?))
+}
+
+/// Get the visible screen as newline-joined, right-trimmed text.
+#[command]
+pub async fn get_screen_text<R: Runtime>(
+    _app: AppHandle<R>,
+    state: State<'_, TerminalState>,
+    session_id: String,
+) -> Result<String> {
+    state.manager.screen_text(&session_id)
+}
+
+/// Get a single cell of the visible screen, for hit-testing.
+#[command]
+pub async fn get_cell<R: Runtime>(
+    _app: AppHandle<R>,
+    state: State<'_, TerminalState>,
+    session_id: String,
+    row: u16,
+    col: u16,
+) -> Result<Cell> {
+    state.manager.get_cell(&session_id, row, col)
+}
+
+/// Get the cells from `top`/`left` to `bottom`/`right` (inclusive) of the
+/// visible screen, for block selection previews. Coordinates are clamped
+/// to the grid, and edges are widened rather than split a wide character.
+#[command]
+pub async fn get_region<R: Runtime>(
+    _app: AppHandle<R>,
+    state: State<'_, TerminalState>,
+    session_id: String,
+    top: u16,
+    left: u16,
+    bottom: u16,
+    right: u16,
+) -> Result<Vec<Row>> {
+    state.manager.region(&session_id, top, left, bottom, right)
+}
+
+/// List the inline images in a session's buffer, without their data.
+#[command]
+pub async fn get_images<R: Runtime>(
+    _app: AppHandle<R>,
+    state: State<'_, TerminalState>,
+    session_id: String,
+) -> Result<Vec<ImagePlacement>> {
+    state.manager.images(&session_id)
+}
+
+/// Get an inline image's base64 data; `None` once it has been evicted.
+#[command]
+pub async fn get_image_data<R: Runtime>(
+    _app: AppHandle<R>,
+    state: State<'_, TerminalState>,
+    session_id: String,
+    image_id: u32,
+) -> Result<Option<String>> {
+    state.manager.image_data(&session_id, image_id)
+}
+
+/// Export a range of buffer lines (0 = oldest scrollback line) as ANSI-colored text.
+///
+/// Lines end in `\r\n` for replaying into a terminal; `normalize_newlines`
+/// gives plain `\n` endings for log files and editors.
+#[command]
+pub async fn export_ansi_range<R: Runtime>(
+    _app: AppHandle<R>,
+    state: State<'_, TerminalState>,
+    session_id: String,
+    start_line: usize,
+    count: usize,
+    normalize_newlines: Option<bool>,
+) -> Result<String> {
+    state.manager.export_ansi_range(
+        &session_id,
+        start_line,
+        count,
+        normalize_newlines.unwrap_or(false),
+    )
+}
+
+/// Write a session's whole buffer to the file at `path`, as plain text
+/// (default) or ANSI-colored text, without building it in memory. Runs on
+/// a worker thread and resolves to the number of lines written.
+///
+/// Not in the default permission set, since `path` can name any file.
+#[command]
+pub async fn export_to_file<R: Runtime>(
+    _app: AppHandle<R>,
+    state: State<'_, TerminalState>,
+    session_id: String,
+    path: String,
+    format: Option<ExportFormat>,
+    preserve_wrapping: Option<bool>,
+) -> Result<usize> {
+    let manager = state.manager.clone();
+    tokio::task::spawn_blocking(move || {
+        manager.export_to_file(
+            &session_id,
+            std::path::Path::new(&path),
+            format.unwrap_or_default(),
+            preserve_wrapping.unwrap_or(false),
+        )
+    })
+    .await
+    .map_err(|e| Error::TerminalError(format!("export failed: {e}")))?
+}
+
+/// Export a session's whole buffer as plain text, rejoining soft-wrapped
+/// rows unless `preserve_wrapping` is set.
+#[command]
+pub async fn export_text<R: Runtime>(
+    _app: AppHandle<R>,
+    state: State<'_, TerminalState>,
+    session_id: String,
+    preserve_wrapping: Option<bool>,
+) -> Result<String> {
+    state
+        .manager
+        .export_text(&session_id, preserve_wrapping.unwrap_or(false))
+}
+
 /// Process pending output for a session and get updates.
 #[command]
 pub async fn poll_session<R: Runtime>(
@@ -113,6 +545,30 @@ pub async fn poll_session<R: Runtime>(
     state.manager.process(&session_id)
 }
 
+/// Check whether a session has output waiting, without processing it, so
+/// a poller can skip `poll_session` when nothing arrived.
+#[command]
+pub async fn has_pending_output<R: Runtime>(
+    _app: AppHandle<R>,
+    state: State<'_, TerminalState>,
+    session_id: String,
+) -> Result<bool> {
+    state.manager.has_pending_output(&session_id)
+}
+
+/// Re-emit the last screen update of a session and return it, to recover
+/// from a dropped event without fetching the whole screen. A frame that was
+/// sent as a full refresh is re-emitted as one. Returns `None` before the
+/// first update.
+#[command]
+pub async fn replay_last_update<R: Runtime>(
+    _app: AppHandle<R>,
+    state: State<'_, TerminalState>,
+    session_id: String,
+) -> Result<Option<ScreenUpdate>> {
+    state.manager.replay_last_update(&session_id)
+}
+
 /// Get the theme for a session.
 #[command]
 pub async fn get_theme<R: Runtime>(
@@ -123,6 +579,17 @@ pub async fn get_theme<R: Runtime>(
     state.manager.get_theme(&session_id)
 }
 
+/// Get a session's 256-color palette resolved to RGB, so renderers can
+/// look colors up by index.
+#[command]
+pub async fn get_palette<R: Runtime>(
+    _app: AppHandle<R>,
+    state: State<'_, TerminalState>,
+    session_id: String,
+) -> Result<Vec<Color>> {
+    state.manager.palette(&session_id)
+}
+
 /// Set the theme for a session.
 #[command]
 pub async fn set_theme<R: Runtime>(
@@ -134,6 +601,392 @@ pub async fn set_theme<R: Runtime>(
     state.manager.set_theme(&session_id, &theme_name)
 }
 
+/// Override a session's cursor color independently of its theme; `None`
+/// restores the theme's cursor color.
+#[command]
+pub async fn set_cursor_color<R: Runtime>(
+    _app: AppHandle<R>,
+    state: State<'_, TerminalState>,
+    session_id: String,
+    color: Option<Color>,
+) -> Result<()> {
+    state.manager.set_cursor_color(&session_id, color)
+}
+
+/// Render indexed color `index` (e.g. 1 for red) as `color` on top of the
+/// theme, for accessibility remapping; `None` removes the override.
+#[command]
+pub async fn set_color_override<R: Runtime>(
+    _app: AppHandle<R>,
+    state: State<'_, TerminalState>,
+    session_id: String,
+    index: u8,
+    color: Option<Color>,
+) -> Result<()> {
+    state.manager.set_color_override(&session_id, index, color)
+}
+
+/// Transition a session to a theme over `duration_ms`, emitting `steps`
+/// intermediate `ThemeChange` events so the frontend can crossfade.
+#[command]
+pub async fn set_theme_animated<R: Runtime>(
+    _app: AppHandle<R>,
+    state: State<'_, TerminalState>,
+    session_id: String,
+    theme_name: String,
+    duration_ms: u64,
+    steps: u32,
+) -> Result<()> {
+    let target = Theme::by_name(&theme_name)
+        .ok_or_else(|| Error::InvalidConfig(format!("Unknown theme: {}", theme_name)))?;
+    let from = state.manager.get_theme(&session_id)?;
+
+    let steps = steps.max(1);
+    let delay = Duration::from_millis(duration_ms / steps as u64);
+    for step in 1..=steps {
+        let t = step as f32 / steps as f32;
+        state.manager.apply_theme(&session_id, from.lerp(&target, t))?;
+        if step < steps {
+            tokio::time::sleep(delay).await;
+        }
+    }
+
+    Ok(())
+}
+
+/// Copy the text between two screen positions to the clipboard.
+///
+/// Emits a `ClipboardRequest` just like a program's OSC 52 write, so the
+/// frontend handles both through one path. Returns the copied text.
+#[command]
+pub async fn copy_selection_osc52<R: Runtime>(
+    _app: AppHandle<R>,
+    state: State<'_, TerminalState>,
+    session_id: String,
+    start: CursorPosition,
+    end: CursorPosition,
+    rectangular: Option<bool>,
+) -> Result<String> {
+    state
+        .manager
+        .copy_selection(&session_id, start, end, rectangular.unwrap_or(false))
+}
+
+/// Default per-line timeout for `run_script`.
+const SCRIPT_LINE_TIMEOUT_MS: u64 = 30_000;
+
+/// Submit lines to the shell one at a time, waiting for each to finish.
+///
+/// Completion is detected from OSC 133 command-end marks when the shell
+/// emits them, falling back to output going quiet, in which case the
+/// line's `success` is unknown. Stops at the first line that fails. A line
+/// still running at its deadline fails with `CommandTimeout`, carrying its
+/// output so far, after `on_timeout` decides what happens to it.
+#[command]
+pub async fn run_script<R: Runtime>(
+    _app: AppHandle<R>,
+    state: State<'_, TerminalState>,
+    session_id: String,
+    lines: Vec<String>,
+    timeout_ms: Option<u64>,
+    on_timeout: Option<TimeoutAction>,
+) -> Result<Vec<ScriptLineResult>> {
+    let timeout = Duration::from_millis(timeout_ms.unwrap_or(SCRIPT_LINE_TIMEOUT_MS));
+    state
+        .manager
+        .run_script(&session_id, lines, timeout, on_timeout.unwrap_or_default())
+        .await
+}
+
+/// Default timeout for `expect`.
+const EXPECT_TIMEOUT_MS: u64 = 10_000;
+/// Output included in an `ExpectTimeout` error.
+const EXPECT_TAIL_BYTES: usize = 512;
+
+/// Type a line into a session and submit it, for use with `expect`.
+#[command]
+pub async fn send_line<R: Runtime>(
+    _app: AppHandle<R>,
+    state: State<'_, TerminalState>,
+    session_id: String,
+    line: String,
+) -> Result<()> {
+    state.manager.submit_line(&session_id, &line)
+}
+
+/// Wait for output matching the regex `pattern`, e.g. a password or
+/// confirmation prompt.
+///
+/// Output is searched with escape sequences stripped, starting after the
+/// previous match, so output that arrives between `send_line` and `expect`
+/// isn't missed. Fails with `ExpectTimeout`, carrying the unmatched output,
+/// if nothing matches in time.
+#[command]
+pub async fn expect<R: Runtime>(
+    _app: AppHandle<R>,
+    state: State<'_, TerminalState>,
+    session_id: String,
+    pattern: String,
+    timeout_ms: Option<u64>,
+) -> Result<MatchRange> {
+    let pattern =
+        Regex::new(&pattern).map_err(|e| Error::InvalidConfig(format!("invalid pattern: {e}")))?;
+    let timeout = Duration::from_millis(timeout_ms.unwrap_or(EXPECT_TIMEOUT_MS));
+    let deadline = tokio::time::Instant::now() + timeout;
+
+    loop {
+        if let Some(found) = state.manager.expect(&session_id, &pattern)? {
+            return Ok(found);
+        }
+        if tokio::time::Instant::now() >= deadline {
+            let tail = state.manager.unmatched_output(&session_id, EXPECT_TAIL_BYTES)?;
+            return Err(Error::ExpectTimeout(tail));
+        }
+        tokio::time::sleep(SCRIPT_POLL_INTERVAL).await;
+    }
+}
+
+/// Get the text of a command run under shell integration (OSC 133).
+///
+/// `command_row` is the row of the command's `CommandStart` mark; the most
+/// recent command is used when it's omitted. Only the output is returned
+/// unless `include_command` or `include_trailing_prompt` is set. Returns
+/// `None` when no matching command was marked.
+#[command]
+pub async fn get_command_output<R: Runtime>(
+    _app: AppHandle<R>,
+    state: State<'_, TerminalState>,
+    session_id: String,
+    command_row: Option<u32>,
+    include_command: Option<bool>,
+    include_trailing_prompt: Option<bool>,
+) -> Result<Option<String>> {
+    state.manager.command_output(
+        &session_id,
+        command_row,
+        include_command.unwrap_or(false),
+        include_trailing_prompt.unwrap_or(false),
+    )
+}
+
+/// Mute categories of events for a session, e.g. `screen_update` for a
+/// backgrounded tab. Lifecycle events such as `ProcessExit` are always
+/// delivered. An empty `mask` unmutes everything; fetch the screen again
+/// after unmuting `screen_update`.
+#[command]
+pub async fn set_event_filter<R: Runtime>(
+    _app: AppHandle<R>,
+    state: State<'_, TerminalState>,
+    session_id: String,
+    mask: Vec<EventCategory>,
+) -> Result<()> {
+    state.manager.get_info(&session_id)?;
+    state.event_filters.set(&session_id, mask);
+    Ok(())
+}
+
+/// Set how the bell is surfaced for a session.
+#[command]
+pub async fn set_bell_mode<R: Runtime>(
+    _app: AppHandle<R>,
+    state: State<'_, TerminalState>,
+    session_id: String,
+    mode: BellMode,
+) -> Result<()> {
+    state.manager.set_bell_mode(&session_id, mode)
+}
+
+/// Set how much a session logs: `warn` (the default) reports recoverable
+/// failures, `debug` adds spawn and exit, `trace` every read and update.
+#[command]
+pub async fn set_log_level<R: Runtime>(
+    _app: AppHandle<R>,
+    state: State<'_, TerminalState>,
+    session_id: String,
+    level: LogLevel,
+) -> Result<()> {
+    state.manager.set_log_level(&session_id, level)
+}
+
+/// Soft-reset a session (DECSTR): clear stuck modes such as mouse tracking,
+/// margins and attributes while keeping the screen and scrollback.
+#[command]
+pub async fn soft_reset<R: Runtime>(
+    _app: AppHandle<R>,
+    state: State<'_, TerminalState>,
+    session_id: String,
+) -> Result<()> {
+    state.manager.soft_reset(&session_id)
+}
+
+/// Force line wrapping on or off for a session; `None` follows DECAWM again.
+#[command]
+pub async fn set_wrap_mode<R: Runtime>(
+    _app: AppHandle<R>,
+    state: State<'_, TerminalState>,
+    session_id: String,
+    wrap: Option<bool>,
+) -> Result<()> {
+    state.manager.set_wrap_mode(&session_id, wrap)
+}
+
+/// Turn software flow control on or off: with it on, Ctrl-S typed into the
+/// session stops reading output and Ctrl-Q resumes, and neither byte
+/// reaches the program.
+#[command]
+pub async fn set_flow_control<R: Runtime>(
+    _app: AppHandle<R>,
+    state: State<'_, TerminalState>,
+    session_id: String,
+    enabled: bool,
+) -> Result<()> {
+    state.manager.set_flow_control(&session_id, enabled)
+}
+
+/// Get the shell's current working directory, from OSC 7 when the shell
+/// reports it and otherwise from the OS (Linux and macOS). `None` when
+/// neither is available.
+#[command]
+pub async fn get_cwd<R: Runtime>(
+    _app: AppHandle<R>,
+    state: State<'_, TerminalState>,
+    session_id: String,
+) -> Result<Option<String>> {
+    state.manager.current_cwd(&session_id)
+}
+
+/// Describe what a session supports (images, OSC 52, truecolor, ...) and
+/// the modes its program has turned on (mouse reporting, bracketed paste,
+/// alternate screen), so the UI can adapt per session.
+#[command]
+pub async fn get_capabilities<R: Runtime>(
+    _app: AppHandle<R>,
+    state: State<'_, TerminalState>,
+    session_id: String,
+) -> Result<Capabilities> {
+    state.manager.capabilities(&session_id)
+}
+
+/// Whether a session is showing the alternate screen, which has no
+/// scrollback (full-screen programs such as vim or less).
+#[command]
+pub async fn is_alt_screen<R: Runtime>(
+    _app: AppHandle<R>,
+    state: State<'_, TerminalState>,
+    session_id: String,
+) -> Result<bool> {
+    state.manager.is_alt_screen(&session_id)
+}
+
+/// Whether a session's program has turned on insert mode (IRM, `CSI 4 h`),
+/// for frontends that echo or predict input locally.
+#[command]
+pub async fn is_insert_mode<R: Runtime>(
+    _app: AppHandle<R>,
+    state: State<'_, TerminalState>,
+    session_id: String,
+) -> Result<bool> {
+    state.manager.is_insert_mode(&session_id)
+}
+
+/// Get the rows visible through the session's scrollback viewport, which
+/// the backend tracks so scroll position survives frontend remounts.
+#[command]
+pub async fn get_viewport<R: Runtime>(
+    _app: AppHandle<R>,
+    state: State<'_, TerminalState>,
+    session_id: String,
+) -> Result<Viewport> {
+    state.manager.viewport(&session_id)
+}
+
+/// Scroll the viewport `delta_lines` (negative is up, e.g. one page for
+/// PgUp) and return the rows now visible. New output that autoscrolls
+/// snaps it back to the bottom.
+#[command]
+pub async fn scroll_viewport<R: Runtime>(
+    _app: AppHandle<R>,
+    state: State<'_, TerminalState>,
+    session_id: String,
+    delta_lines: i64,
+) -> Result<Viewport> {
+    state.manager.scroll_viewport(&session_id, delta_lines)
+}
+
+/// Scroll the viewport so buffer line `line` (0 = oldest scrollback line)
+/// is at the top, clamped to `[0, scrollback_len]`, and return the rows
+/// now visible.
+#[command]
+pub async fn scroll_to<R: Runtime>(
+    _app: AppHandle<R>,
+    state: State<'_, TerminalState>,
+    session_id: String,
+    line: usize,
+) -> Result<Viewport> {
+    state.manager.scroll_to(&session_id, line)
+}
+
+/// Find `query` in the scrollback and screen, for find-next/find-previous.
+/// Searches forward (the default) from after `from_line`/`from_col`, or
+/// backward from before it; without `from_line` the search starts at the
+/// top or bottom of the buffer. Case-insensitive unless `case_sensitive`.
+#[command]
+#[allow(clippy::too_many_arguments)]
+pub async fn search_scrollback<R: Runtime>(
+    _app: AppHandle<R>,
+    state: State<'_, TerminalState>,
+    session_id: String,
+    query: String,
+    from_line: Option<usize>,
+    from_col: Option<u16>,
+    direction: Option<SearchDirection>,
+    case_sensitive: Option<bool>,
+) -> Result<Option<SearchMatch>> {
+    state.manager.search(
+        &session_id,
+        &query,
+        from_line.map(|line| (line, from_col.unwrap_or(0))),
+        direction.unwrap_or_default(),
+        case_sensitive.unwrap_or(false),
+    )
+}
+
+/// Get the shell integration (OSC 133) marks still in the buffer, oldest
+/// first, for prompt gutters and recent-command lists. Rows index the
+/// buffer as `export_ansi_range` does.
+#[command]
+pub async fn get_marks<R: Runtime>(
+    _app: AppHandle<R>,
+    state: State<'_, TerminalState>,
+    session_id: String,
+) -> Result<Vec<Mark>> {
+    state.manager.retained_marks(&session_id)
+}
+
+/// Get the columns with a tab stop set (zero-based).
+#[command]
+pub async fn get_tab_stops<R: Runtime>(
+    _app: AppHandle<R>,
+    state: State<'_, TerminalState>,
+    session_id: String,
+) -> Result<Vec<u16>> {
+    state.manager.tab_stops(&session_id)
+}
+
+/// Largest grid that fits a `width_px` × `height_px` area with cells of
+/// the given pixel size, so the frontend can size a terminal before
+/// resizing it. Never smaller than 2×1.
+#[command]
+pub async fn fit_cells<R: Runtime>(
+    _app: AppHandle<R>,
+    width_px: u32,
+    height_px: u32,
+    cell_width: f64,
+    cell_height: f64,
+) -> Result<Size> {
+    Ok(crate::types::fit_cells(width_px, height_px, cell_width, cell_height))
+}
+
 /// List available themes.
 #[command]
 pub async fn list_themes<R: Runtime>(_app: AppHandle<R>) -> Result<Vec<String>> {
@@ -148,3 +1001,12 @@ pub async fn get_session_count<R: Runtime>(
 ) -> Result<usize> {
     Ok(state.manager.count())
 }
+
+/// Get how many more sessions can be created (`None` if unlimited).
+#[command]
+pub async fn get_remaining_capacity<R: Runtime>(
+    _app: AppHandle<R>,
+    state: State<'_, TerminalState>,
+) -> Result<Option<usize>> {
+    Ok(state.manager.remaining_capacity())
+}
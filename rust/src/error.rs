@@ -15,9 +15,36 @@ pub enum Error {
     #[error("Session already exists: {0}")]
     SessionAlreadyExists(String),
 
+    #[error("Group not found: {0}")]
+    GroupNotFound(String),
+
+    #[error("Group already exists: {0}")]
+    GroupAlreadyExists(String),
+
+    #[error("Session limit reached ({0} sessions)")]
+    SessionLimitReached(usize),
+
     #[error("PTY error: {0}")]
     PtyError(String),
 
+    #[error("Shell not found: {0}")]
+    ShellNotFound(String),
+
+    #[error("Permission denied: {0}")]
+    PermissionDenied(String),
+
+    #[error("Failed to spawn process: {0}")]
+    SpawnFailed(String),
+
+    #[error("Failed to resize PTY: {0}")]
+    ResizeFailed(String),
+
+    #[error("Failed to write to PTY: {0}")]
+    WriteFailed(String),
+
+    #[error("Process has exited")]
+    WriteAfterExit,
+
     #[error("Terminal error: {0}")]
     TerminalError(String),
 
@@ -32,6 +59,39 @@ pub enum Error {
 
     #[error("Lock poisoned")]
     LockPoisoned,
+
+    #[error("Timed out waiting for a match; last output: {0:?}")]
+    ExpectTimeout(String),
+
+    #[error("Command timed out; last output: {output:?}")]
+    CommandTimeout { output: String },
+}
+
+impl Error {
+    /// Stable machine-readable code for the frontend.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Error::SessionNotFound(_) => "session_not_found",
+            Error::SessionAlreadyExists(_) => "session_already_exists",
+            Error::GroupNotFound(_) => "group_not_found",
+            Error::GroupAlreadyExists(_) => "group_already_exists",
+            Error::SessionLimitReached(_) => "session_limit_reached",
+            Error::PtyError(_) => "pty_error",
+            Error::ShellNotFound(_) => "shell_not_found",
+            Error::PermissionDenied(_) => "permission_denied",
+            Error::SpawnFailed(_) => "spawn_failed",
+            Error::ResizeFailed(_) => "resize_failed",
+            Error::WriteFailed(_) => "write_failed",
+            Error::WriteAfterExit => "write_after_exit",
+            Error::TerminalError(_) => "terminal_error",
+            Error::InvalidConfig(_) => "invalid_config",
+            Error::IoError(_) => "io_error",
+            Error::SessionClosed => "session_closed",
+            Error::LockPoisoned => "lock_poisoned",
+            Error::ExpectTimeout(_) => "expect_timeout",
+            Error::CommandTimeout { .. } => "command_timeout",
+        }
+    }
 }
 
 impl From<std::io::Error> for Error {
@@ -41,13 +101,14 @@ impl From<std::io::Error> for Error {
 }
 
 
-// Make Error serializable for Tauri
+// Make Error serializable for Tauri: the display message with the stable
+// code appended, e.g. `Session not found: abc [session_not_found]`
 impl Serialize for Error {
     fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
     where
         S: serde::Serializer,
     {
-        serializer.serialize_str(&self.to_string())
+        serializer.serialize_str(&format!("{} [{}]", self, self.code()))
     }
 }
 
@@ -56,7 +117,16 @@ impl<'de> Deserialize<'de> for Error {
     where
         D: serde::Deserializer<'de>,
     {
-        let s = String::deserialize(deserializer)?;
-        Ok(Error::TerminalError(s))
+        let mut message = String::deserialize(deserializer)?;
+        // Drop the ` [code]` suffix added by `serialize`
+        let code_start = message
+            .strip_suffix(']')
+            .and_then(|rest| rest.rsplit_once(" ["))
+            .filter(|(_, code)| !code.is_empty() && code.bytes().all(|b| b.is_ascii_lowercase() || b == b'_'))
+            .map(|(rest, _)| rest.len());
+        if let Some(len) = code_start {
+            message.truncate(len);
+        }
+        Ok(Error::TerminalError(message))
     }
 }
@@ -1,7 +1,10 @@
 //! Event types emitted by the terminal plugin.
 
-use crate::types::{Cursor, Mark, ScreenUpdate};
+use crate::theme::Theme;
+use crate::types::{BindingAction, Cursor, ExitReason, Mark, Screen, ScreenUpdate};
+use parking_lot::RwLock;
 use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
 
 /// Events emitted by the terminal plugin.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -27,11 +30,18 @@ pub enum TerminalEvent {
     /// Screen content was updated.
     ScreenUpdate(ScreenUpdate),
 
-    /// Full screen refresh (sent on reconnect).
+    /// Raw PTY output from a session created with `emulate: false`.
+    RawOutput {
+        session_id: String,
+        /// Base64 encoded bytes.
+        data: String,
+    },
+
+    /// Full screen, sent instead of a `ScreenUpdate` when most of the grid
+    /// changed at once.
     ScreenRefresh {
         session_id: String,
-        /// Base64 encoded full screen
-        screen: String,
+        screen: Screen,
     },
 
     /// Terminal bell.
@@ -39,12 +49,23 @@ pub enum TerminalEvent {
         session_id: String,
     },
 
+    /// Visual bell (flash) requested by the session's bell mode.
+    VisualBell {
+        session_id: String,
+    },
+
     /// Window title changed.
     TitleChange {
         session_id: String,
         title: String,
     },
 
+    /// Session theme changed (e.g. via OSC 10/11/12).
+    ThemeChange {
+        session_id: String,
+        theme: Theme,
+    },
+
     /// Current working directory changed.
     DirectoryChange {
         session_id: String,
@@ -57,10 +78,40 @@ pub enum TerminalEvent {
         mark: Mark,
     },
 
+    /// Total PTY output crossed a configured threshold or the hard limit.
+    OutputThreshold {
+        session_id: String,
+        bytes: u64,
+    },
+
+    /// A key binding matched; the key was not sent to the PTY.
+    Binding {
+        session_id: String,
+        action: BindingAction,
+    },
+
+    /// Input written to the PTY, for sessions with `audit_input` enabled.
+    InputAudit {
+        session_id: String,
+        /// Milliseconds since the Unix epoch.
+        timestamp: u64,
+        /// Base64 encoded bytes; empty when redacted.
+        data: String,
+        /// Number of bytes written.
+        len: usize,
+        /// Echo was off (e.g. a password prompt) so the bytes were withheld.
+        redacted: bool,
+    },
+
     /// Process exited.
     ProcessExit {
         session_id: String,
         exit_code: Option<i32>,
+        /// Terminating signal, when the process was killed by one.
+        #[serde(default)]
+        signal: Option<i32>,
+        #[serde(default)]
+        reason: ExitReason,
     },
 
     /// Cursor position changed.
@@ -101,11 +152,17 @@ impl TerminalEvent {
             Self::SessionDestroyed { session_id } => session_id,
             Self::TerminalResized { session_id, .. } => session_id,
             Self::ScreenUpdate(update) => &update.session_id,
+            Self::RawOutput { session_id, .. } => session_id,
             Self::ScreenRefresh { session_id, .. } => session_id,
             Self::Bell { session_id } => session_id,
+            Self::VisualBell { session_id } => session_id,
             Self::TitleChange { session_id, .. } => session_id,
+            Self::ThemeChange { session_id, .. } => session_id,
             Self::DirectoryChange { session_id, .. } => session_id,
             Self::Mark { session_id, .. } => session_id,
+            Self::OutputThreshold { session_id, .. } => session_id,
+            Self::Binding { session_id, .. } => session_id,
+            Self::InputAudit { session_id, .. } => session_id,
             Self::ProcessExit { session_id, .. } => session_id,
             Self::CursorMove { session_id, .. } => session_id,
             Self::SelectionChange { session_id, .. } => session_id,
@@ -114,6 +171,31 @@ impl TerminalEvent {
         }
     }
 
+    /// Category for per-session event filters; `None` for lifecycle
+    /// events, which are always delivered.
+    pub fn category(&self) -> Option<EventCategory> {
+        match self {
+            Self::SessionCreated { .. }
+            | Self::SessionDestroyed { .. }
+            | Self::TerminalResized { .. }
+            | Self::ProcessExit { .. } => None,
+            Self::ScreenUpdate(_) | Self::RawOutput { .. } | Self::ScreenRefresh { .. } => {
+                Some(EventCategory::ScreenUpdate)
+            }
+            Self::CursorMove { .. } => Some(EventCategory::CursorMove),
+            Self::Mark { .. } => Some(EventCategory::Mark),
+            Self::Hyperlink { .. } => Some(EventCategory::Hyperlink),
+            Self::Bell { .. } | Self::VisualBell { .. } | Self::OutputThreshold { .. } => {
+                Some(EventCategory::Activity)
+            }
+            Self::TitleChange { .. } | Self::DirectoryChange { .. } => Some(EventCategory::Title),
+            Self::ThemeChange { .. } => Some(EventCategory::Theme),
+            Self::SelectionChange { .. } => Some(EventCategory::Selection),
+            Self::ClipboardRequest { .. } => Some(EventCategory::Clipboard),
+            Self::Binding { .. } | Self::InputAudit { .. } => Some(EventCategory::Input),
+        }
+    }
+
     /// Get the event name for Tauri event emission.
     pub fn event_name(&self) -> &'static str {
         match self {
@@ -121,11 +203,17 @@ impl TerminalEvent {
             Self::SessionDestroyed { .. } => "terminal://session-destroyed",
             Self::TerminalResized { .. } => "terminal://terminal-resized",
             Self::ScreenUpdate { .. } => "terminal://screen-update",
+            Self::RawOutput { .. } => "terminal://raw-output",
             Self::ScreenRefresh { .. } => "terminal://screen-refresh",
             Self::Bell { .. } => "terminal://bell",
+            Self::VisualBell { .. } => "terminal://visual-bell",
             Self::TitleChange { .. } => "terminal://title-change",
+            Self::ThemeChange { .. } => "terminal://theme-change",
             Self::DirectoryChange { .. } => "terminal://directory-change",
             Self::Mark { .. } => "terminal://mark",
+            Self::OutputThreshold { .. } => "terminal://output-threshold",
+            Self::Binding { .. } => "terminal://binding",
+            Self::InputAudit { .. } => "terminal://input-audit",
             Self::ProcessExit { .. } => "terminal://process-exit",
             Self::CursorMove { .. } => "terminal://cursor-move",
             Self::SelectionChange { .. } => "terminal://selection-change",
@@ -135,6 +223,125 @@ impl TerminalEvent {
     }
 }
 
+/// Groups of events that can be muted per session with `set_event_filter`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EventCategory {
+    /// `ScreenUpdate`, `RawOutput` and `ScreenRefresh`.
+    ScreenUpdate,
+    CursorMove,
+    Mark,
+    Hyperlink,
+    /// `Bell`, `VisualBell` and `OutputThreshold`.
+    Activity,
+    /// `TitleChange` and `DirectoryChange`.
+    Title,
+    Theme,
+    Selection,
+    Clipboard,
+    /// `Binding` and `InputAudit`.
+    Input,
+}
+
+/// Muted event categories per session, checked before events are emitted
+/// to the frontend.
+#[derive(Debug, Default)]
+pub struct EventFilters {
+    muted: RwLock<HashMap<String, HashSet<EventCategory>>>,
+}
+
+impl EventFilters {
+    /// Replace a session's muted categories; an empty set delivers everything.
+    pub fn set(&self, session_id: &str, muted: impl IntoIterator<Item = EventCategory>) {
+        let muted: HashSet<_> = muted.into_iter().collect();
+        let mut filters = self.muted.write();
+        if muted.is_empty() {
+            filters.remove(session_id);
+        } else {
+            filters.insert(session_id.to_string(), muted);
+        }
+    }
+
+    /// Whether `event` should reach the frontend.
+    pub fn allows(&self, event: &TerminalEvent) -> bool {
+        let Some(category) = event.category() else {
+            return true;
+        };
+        self.muted
+            .read()
+            .get(event.session_id())
+            .is_none_or(|muted| !muted.contains(&category))
+    }
+
+    /// Forget a session's filter.
+    pub fn remove(&self, session_id: &str) {
+        self.muted.write().remove(session_id);
+    }
+}
+
+/// How queued events are coalesced when the frontend falls behind.
+///
+/// Only high-frequency events are merged; lifecycle events such as
+/// `SessionCreated` and `ProcessExit` are always delivered in order.
+#[derive(Debug, Clone)]
+pub struct EventPolicy {
+    /// Merge consecutive `ScreenUpdate`s for the same session.
+    pub coalesce_screen_updates: bool,
+    /// Keep only the latest of consecutive `CursorMove`s for the same session.
+    pub coalesce_cursor_moves: bool,
+    /// Maximum number of queued events drained into one batch.
+    pub max_batch: usize,
+}
+
+impl Default for EventPolicy {
+    fn default() -> Self {
+        Self {
+            coalesce_screen_updates: true,
+            coalesce_cursor_moves: true,
+            max_batch: 256,
+        }
+    }
+}
+
+/// Coalesce a batch of queued events according to `policy`.
+///
+/// An event is only merged into the most recent earlier event for the same
+/// session, so per-session ordering is preserved.
+pub fn coalesce_events(batch: Vec<TerminalEvent>, policy: &EventPolicy) -> Vec<TerminalEvent> {
+    let mut out: Vec<TerminalEvent> = Vec::with_capacity(batch.len());
+
+    for event in batch {
+        let event = match out.iter_mut().rev().find(|e| e.session_id() == event.session_id()) {
+            Some(prev) => merge_event(prev, event, policy),
+            None => Some(event),
+        };
+        if let Some(event) = event {
+            out.push(event);
+        }
+    }
+
+    out
+}
+
+/// Merge `event` into `prev` if the policy allows, returning it otherwise.
+fn merge_event(prev: &mut TerminalEvent, event: TerminalEvent, policy: &EventPolicy) -> Option<TerminalEvent> {
+    match (prev, event) {
+        (TerminalEvent::ScreenUpdate(prev), TerminalEvent::ScreenUpdate(update))
+            if policy.coalesce_screen_updates && prev.can_merge(&update) =>
+        {
+            prev.merge(update);
+            None
+        }
+        (TerminalEvent::CursorMove { cursor: prev, .. }, TerminalEvent::CursorMove { cursor, .. })
+            if policy.coalesce_cursor_moves =>
+        {
+            *prev = cursor;
+            None
+        }
+        (_, event) => Some(event),
+    }
+}
+
 /// Event channel for emitting terminal events.
 pub type EventSender = tokio::sync::mpsc::UnboundedSender<TerminalEvent>;
 pub type EventReceiver = tokio::sync::mpsc::UnboundedReceiver<TerminalEvent>;
@@ -0,0 +1,245 @@
+//! Encoding of frontend key events into the bytes a terminal would send.
+
+use crate::types::{KeyEvent, KeyEventType};
+
+/// Kitty keyboard flag: report repeat and release events.
+const KITTY_REPORT_EVENTS: u8 = 2;
+/// Kitty keyboard flag: encode every key, text included, as an escape code.
+const KITTY_REPORT_ALL_KEYS: u8 = 8;
+
+/// Terminal modes that change how keys are encoded.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct KeyModes {
+    /// DECCKM: cursor keys send SS3 instead of CSI.
+    pub application_cursor: bool,
+    /// Kitty keyboard protocol flags pushed by the program (1 disambiguates
+    /// escape codes); 0 is legacy xterm encoding.
+    pub kitty_flags: u8,
+}
+
+/// Encode a key event as the input bytes a terminal would send: kitty
+/// keyboard protocol sequences when the program enabled them, otherwise
+/// xterm-style.
+///
+/// Returns `None` for keys with no terminal encoding (bare modifiers,
+/// Meta/Cmd shortcuts meant for the app) and for releases the program
+/// didn't ask for.
+pub fn encode_key(event: &KeyEvent, modes: KeyModes) -> Option<Vec<u8>> {
+    if modes.kitty_flags != 0 {
+        return encode_kitty_key(event, modes);
+    }
+    if event.event_type == KeyEventType::Release {
+        return None;
+    }
+
+    // xterm modifier parameter: 1 + Shift(1) + Alt(2) + Ctrl(4) + Meta(8)
+    let modifier = 1
+        + event.shift as u8
+        + ((event.alt as u8) << 1)
+        + ((event.ctrl as u8) << 2)
+        + ((event.meta as u8) << 3);
+
+    let simple = |bytes: &[u8]| -> Vec<u8> {
+        let mut out = Vec::with_capacity(bytes.len() + 1);
+        if event.alt {
+            out.push(0x1b);
+        }
+        out.extend_from_slice(bytes);
+        out
+    };
+
+    let bytes = match event.key.as_str() {
+        "Enter" => simple(b"\r"),
+        "Tab" if event.shift => b"\x1b[Z".to_vec(),
+        "Tab" => simple(b"\t"),
+        "Backspace" if event.ctrl => simple(b"\x08"),
+        "Backspace" => simple(b"\x7f"),
+        "Escape" => simple(b"\x1b"),
+        "ArrowUp" => cursor_key(b'A', modifier, modes),
+        "ArrowDown" => cursor_key(b'B', modifier, modes),
+        "ArrowRight" => cursor_key(b'C', modifier, modes),
+        "ArrowLeft" => cursor_key(b'D', modifier, modes),
+        "Home" => cursor_key(b'H', modifier, modes),
+        "End" => cursor_key(b'F', modifier, modes),
+        "Insert" => tilde_key(2, modifier),
+        "Delete" => tilde_key(3, modifier),
+        "PageUp" => tilde_key(5, modifier),
+        "PageDown" => tilde_key(6, modifier),
+        "F1" => ss3_key(b'P', modifier),
+        "F2" => ss3_key(b'Q', modifier),
+        "F3" => ss3_key(b'R', modifier),
+        "F4" => ss3_key(b'S', modifier),
+        "F5" => tilde_key(15, modifier),
+        "F6" => tilde_key(17, modifier),
+        "F7" => tilde_key(18, modifier),
+        "F8" => tilde_key(19, modifier),
+        "F9" => tilde_key(20, modifier),
+        "F10" => tilde_key(21, modifier),
+        "F11" => tilde_key(23, modifier),
+        "F12" => tilde_key(24, modifier),
+        key => {
+            let mut chars = key.chars();
+            let (Some(c), None) = (chars.next(), chars.next()) else {
+                return None;
+            };
+            if event.meta {
+                return None;
+            }
+            if event.ctrl {
+                simple(&[ctrl_byte(c)?])
+            } else {
+                let mut buf = [0u8; 4];
+                simple(c.encode_utf8(&mut buf).as_bytes())
+            }
+        }
+    };
+
+    Some(bytes)
+}
+
+/// Encode a key under the kitty keyboard protocol.
+///
+/// Keys are `CSI code;modifiers:event u`, except for keys with legacy
+/// forms (arrows, Home/End, F1-F12, editing keys), which keep their final
+/// byte. Unmodified text and Enter/Tab/Backspace stay plain unless
+/// `KITTY_REPORT_ALL_KEYS` is set.
+fn encode_kitty_key(event: &KeyEvent, modes: KeyModes) -> Option<Vec<u8>> {
+    let flags = modes.kitty_flags;
+    let report_events = flags & KITTY_REPORT_EVENTS != 0;
+    let report_all = flags & KITTY_REPORT_ALL_KEYS != 0;
+    if event.event_type == KeyEventType::Release && !report_events {
+        return None;
+    }
+
+    // Shift(1) + Alt(2) + Ctrl(4) + Super(8), sent plus one
+    let mods = event.shift as u8
+        + ((event.alt as u8) << 1)
+        + ((event.ctrl as u8) << 2)
+        + ((event.meta as u8) << 3);
+    let event_suffix = match event.event_type {
+        _ if !report_events => "",
+        KeyEventType::Press => "",
+        KeyEventType::Repeat => ":2",
+        KeyEventType::Release => ":3",
+    };
+    let plain = mods == 0 && event_suffix.is_empty();
+    let params = |first: &str| {
+        if plain {
+            first.to_string()
+        } else {
+            format!("{};{}{}", if first.is_empty() { "1" } else { first }, mods + 1, event_suffix)
+        }
+    };
+
+    let letter = match event.key.as_str() {
+        "ArrowUp" => Some(b'A'),
+        "ArrowDown" => Some(b'B'),
+        "ArrowRight" => Some(b'C'),
+        "ArrowLeft" => Some(b'D'),
+        "Home" => Some(b'H'),
+        "End" => Some(b'F'),
+        "F1" => Some(b'P'),
+        "F2" => Some(b'Q'),
+        "F4" => Some(b'S'),
+        _ => None,
+    };
+    if let Some(letter) = letter {
+        if plain {
+            return Some(match letter {
+                b'P' | b'Q' | b'S' => ss3_key(letter, 1),
+                _ => cursor_key(letter, 1, modes),
+            });
+        }
+        return Some(format!("\x1b[{}{}", params(""), letter as char).into_bytes());
+    }
+
+    let tilde = match event.key.as_str() {
+        "Insert" => Some(2),
+        "Delete" => Some(3),
+        "PageUp" => Some(5),
+        "PageDown" => Some(6),
+        "F3" => Some(13),
+        "F5" => Some(15),
+        "F6" => Some(17),
+        "F7" => Some(18),
+        "F8" => Some(19),
+        "F9" => Some(20),
+        "F10" => Some(21),
+        "F11" => Some(23),
+        "F12" => Some(24),
+        _ => None,
+    };
+    if let Some(code) = tilde {
+        return Some(format!("\x1b[{}~", params(&code.to_string())).into_bytes());
+    }
+
+    let (code, text): (u32, Option<&[u8]>) = match event.key.as_str() {
+        "Escape" => (27, None),
+        "Enter" => (13, Some(b"\r")),
+        "Tab" => (9, Some(b"\t")),
+        "Backspace" => (127, Some(b"\x7f")),
+        key => {
+            let mut chars = key.chars();
+            let (Some(c), None) = (chars.next(), chars.next()) else {
+                return None;
+            };
+            (c.to_lowercase().next().unwrap_or(c) as u32, None)
+        }
+    };
+
+    // Keys that produce text send it, and their releases are dropped,
+    // unless every key is reported as an escape code
+    let produces_text = code != 27 && if text.is_some() { mods == 0 } else { mods & !1 == 0 };
+    if produces_text && !report_all {
+        return match event.event_type {
+            KeyEventType::Release => None,
+            _ => Some(text.map_or_else(|| event.key.as_bytes().to_vec(), <[u8]>::to_vec)),
+        };
+    }
+
+    Some(format!("\x1b[{}u", params(&code.to_string())).into_bytes())
+}
+
+/// Cursor and Home/End keys: `CSI 1;m X` with modifiers, else SS3 or CSI by DECCKM.
+fn cursor_key(action: u8, modifier: u8, modes: KeyModes) -> Vec<u8> {
+    if modifier > 1 {
+        format!("\x1b[1;{}{}", modifier, action as char).into_bytes()
+    } else if modes.application_cursor {
+        vec![0x1b, b'O', action]
+    } else {
+        vec![0x1b, b'[', action]
+    }
+}
+
+/// F1-F4: `SS3 X`, or `CSI 1;m X` with modifiers.
+fn ss3_key(action: u8, modifier: u8) -> Vec<u8> {
+    if modifier > 1 {
+        format!("\x1b[1;{}{}", modifier, action as char).into_bytes()
+    } else {
+        vec![0x1b, b'O', action]
+    }
+}
+
+/// Editing and function keys: `CSI n ~`, or `CSI n;m ~` with modifiers.
+fn tilde_key(code: u8, modifier: u8) -> Vec<u8> {
+    if modifier > 1 {
+        format!("\x1b[{};{}~", code, modifier).into_bytes()
+    } else {
+        format!("\x1b[{}~", code).into_bytes()
+    }
+}
+
+/// C0 control byte for Ctrl+`c`, if it has one.
+fn ctrl_byte(c: char) -> Option<u8> {
+    match c {
+        'a'..='z' | 'A'..='Z' => Some(c.to_ascii_lowercase() as u8 & 0x1f),
+        '@' | ' ' | '2' => Some(0x00),
+        '[' | '3' => Some(0x1b),
+        '\\' | '4' => Some(0x1c),
+        ']' | '5' => Some(0x1d),
+        '^' | '6' => Some(0x1e),
+        '_' | '-' | '7' => Some(0x1f),
+        '?' | '8' => Some(0x7f),
+        _ => None,
+    }
+}
@@ -23,21 +23,31 @@
 //! }
 //! ```
 
+#[cfg(feature = "bridge")]
+pub mod bridge;
 mod commands;
 mod error;
 mod events;
+mod keys;
 mod plugin;
 mod pty;
+mod search;
+mod sequences;
 mod session;
 mod terminal;
+#[cfg(feature = "testing")]
+pub mod testing;
 mod theme;
+mod trace;
 mod types;
 
 pub use error::{Error, Result};
 pub use events::*;
-pub use plugin::init;
-pub use session::{Session, SessionConfig, SessionId, SessionInfo, SessionManager};
-pub use terminal::Terminal;
+pub use keys::{encode_key, KeyModes};
+pub use plugin::{init, init_with_config, PluginConfig};
+pub use pty::ExternalPty;
+pub use session::{Session, SessionConfig, SessionDiagnostics, SessionFilter, SessionId, SessionInfo, SessionManager, SessionSnapshot};
+pub use terminal::{ColorRequest, Terminal};
 pub use theme::{Theme, THEMES};
 pub use types::*;
 
@@ -1,17 +1,61 @@
 //! Tauri plugin implementation.
 
+#[cfg(feature = "bridge")]
+use crate::bridge::{Bridge, BridgeConfig};
 use crate::commands::*;
-use crate::events::{event_channel, EventReceiver};
-use crate::session::SessionManager;
+use crate::events::{coalesce_events, event_channel, EventFilters, EventPolicy, EventReceiver, TerminalEvent};
+use crate::session::{SessionManager, DEFAULT_FULL_REFRESH_RATIO, DEFAULT_MAX_CELLS};
 use std::sync::Arc;
 use tauri::{
     plugin::{Builder, TauriPlugin},
     Emitter, Manager, Runtime,
 };
-use tokio::time::{interval, Duration};
+use tokio::time::{timeout, Duration};
+
+/// Plugin-wide configuration for [`init_with_config`].
+#[derive(Debug, Clone)]
+pub struct PluginConfig {
+    /// Coalescing policy for events queued behind a slow frontend.
+    pub event_policy: EventPolicy,
+    /// Maximum number of running sessions; exited sessions kept for
+    /// `exit_retention_ms` don't count. `None` is unlimited.
+    pub max_sessions: Option<usize>,
+    /// Fraction of the grid that must change in one update for a full
+    /// `ScreenRefresh` to be sent instead of cell changes. Values above 1.0
+    /// disable the fallback.
+    pub full_refresh_ratio: f32,
+    /// Largest grid (cols × rows) sessions can be created or resized to;
+    /// larger sizes fail with `invalid_config`. `None` is unlimited.
+    pub max_cells: Option<usize>,
+    /// Name sessions created without an id `<prefix>1`, `<prefix>2`, ...
+    /// instead of random UUIDs, e.g. `Some("term-")`.
+    pub id_prefix: Option<String>,
+    /// Serve sessions over a local WebSocket for thin clients.
+    #[cfg(feature = "bridge")]
+    pub bridge: Option<BridgeConfig>,
+}
+
+impl Default for PluginConfig {
+    fn default() -> Self {
+        Self {
+            event_policy: EventPolicy::default(),
+            max_sessions: None,
+            full_refresh_ratio: DEFAULT_FULL_REFRESH_RATIO,
+            max_cells: Some(DEFAULT_MAX_CELLS),
+            id_prefix: None,
+            #[cfg(feature = "bridge")]
+            bridge: None,
+        }
+    }
+}
 
 /// Initialize the terminal plugin.
 pub fn init<R: Runtime>() -> TauriPlugin<R> {
+    init_with_config(PluginConfig::default())
+}
+
+/// Initialize the terminal plugin with custom configuration.
+pub fn init_with_config<R: Runtime>(config: PluginConfig) -> TauriPlugin<R> {
     Builder::new("terminal")
         .invoke_handler(tauri::generate_handler![
             create_session,
@@ -27,14 +71,104 @@ pub fn init<R: Runtime>() -> TauriPlugin<R> {
             set_theme,
             list_themes,
             get_session_count,
+            set_bell_mode,
+            export_ansi_range,
+            set_theme_animated,
+            get_tab_stops,
+            export_text,
+            session_diagnostics,
+            get_pixel_size,
+            set_pixel_size,
+            get_screen_text,
+            send_key,
+            set_session_env,
+            get_remaining_capacity,
+            get_last_update,
+            sessions_changed_since,
+            get_screen_hash,
+            run_script,
+            copy_selection_osc52,
+            set_wrap_mode,
+            get_cell,
+            get_images,
+            get_image_data,
+            get_palette,
+            set_cursor_color,
+            get_command_output,
+            set_event_filter,
+            get_buffer_stats,
+            get_total_buffer_stats,
+            soft_reset,
+            is_alt_screen,
+            get_cwd,
+            get_metadata,
+            set_metadata,
+            close_session,
+            get_capabilities,
+            send_line,
+            expect,
+            set_color_override,
+            fit_cells,
+            create_group,
+            add_to_group,
+            group_resize,
+            group_broadcast,
+            destroy_group,
+            export_to_file,
+            get_marks,
+            set_flow_control,
+            feed_session,
+            get_viewport,
+            scroll_viewport,
+            scroll_to,
+            get_session_env,
+            get_region,
+            list_sessions_filtered,
+            replay_last_update,
+            has_pending_output,
+            search_scrollback,
+            is_insert_mode,
+            export_spawn_command,
+            export_snapshot,
+            import_snapshot,
+            set_log_level,
         ])
-        .setup(|app, _api| {
+        .setup(move |app, _api| {
             let (event_sender, event_receiver) = event_channel();
-            let manager = Arc::new(SessionManager::new(event_sender));
+            let manager = Arc::new(
+                SessionManager::new(event_sender)
+                    .with_max_sessions(config.max_sessions)
+                    .with_full_refresh_ratio(config.full_refresh_ratio)
+                    .with_max_cells(config.max_cells)
+                    .with_id_prefix(config.id_prefix.clone()),
+            );
+
+            // Tee events through the bridge when it's enabled
+            #[cfg(feature = "bridge")]
+            let event_receiver = match config.bridge {
+                Some(bridge_config) => {
+                    let bridge = Arc::new(Bridge::new(bridge_config));
+                    let (tee_sender, tee_receiver) = event_channel();
+
+                    tauri::async_runtime::spawn(bridge.clone().tee(event_receiver, tee_sender));
+
+                    let bridge_manager = manager.clone();
+                    tauri::async_runtime::spawn(async move {
+                        if let Err(e) = bridge.serve(bridge_manager).await {
+                            log::error!("Terminal bridge stopped: {}", e);
+                        }
+                    });
+
+                    tee_receiver
+                }
+                None => event_receiver,
+            };
 
             // Store state
+            let event_filters = Arc::new(EventFilters::default());
             app.manage(TerminalState {
                 manager: manager.clone(),
+                event_filters: event_filters.clone(),
             });
 
             // Start background tasks
@@ -43,7 +177,7 @@ pub fn init<R: Runtime>() -> TauriPlugin<R> {
 
             // Spawn event forwarding task
             tauri::async_runtime::spawn(async move {
-                forward_events(app_handle, event_receiver).await;
+                forward_events(app_handle, event_receiver, config.event_policy, event_filters).await;
             });
 
             // Spawn output polling task
@@ -58,26 +192,53 @@ pub fn init<R: Runtime>() -> TauriPlugin<R> {
 }
 
 /// Forward terminal events to the frontend via Tauri events.
-async fn forward_events<R: Runtime>(app: tauri::AppHandle<R>, mut receiver: EventReceiver) {
-    while let Some(event) = receiver.recv().await {
-        let event_name = event.event_name();
-        if let Err(e) = app.emit(event_name, &event) {
-            log::error!("Failed to emit event {}: {}", event_name, e);
+///
+/// Events that queued up while emitting are drained as a batch and
+/// coalesced, so a slow frontend sees fewer, larger updates instead of an
+/// ever-growing backlog. Events muted by a session's filter are dropped.
+async fn forward_events<R: Runtime>(
+    app: tauri::AppHandle<R>,
+    mut receiver: EventReceiver,
+    policy: EventPolicy,
+    filters: Arc<EventFilters>,
+) {
+    while let Some(first) = receiver.recv().await {
+        let mut batch = vec![first];
+        while batch.len() < policy.max_batch.max(1) {
+            match receiver.try_recv() {
+                Ok(event) => batch.push(event),
+                Err(_) => break,
+            }
+        }
+
+        for event in coalesce_events(batch, &policy) {
+            if !filters.allows(&event) {
+                continue;
+            }
+            if let TerminalEvent::SessionDestroyed { session_id } = &event {
+                filters.remove(session_id);
+            }
+            let event_name = event.event_name();
+            if let Err(e) = app.emit(event_name, &event) {
+                log::error!("Failed to emit event {}: {}", event_name, e);
+            }
         }
     }
 }
 
 /// Poll for PTY output and emit screen updates.
 async fn poll_output<R: Runtime>(_app: tauri::AppHandle<R>, manager: Arc<SessionManager>) {
-    let mut ticker = interval(Duration::from_millis(16)); // ~60fps
+    let frame = Duration::from_millis(16); // ~60fps
 
     loop {
-        ticker.tick().await;
+        // A child exiting wakes the loop early so its exit is reported at once
+        let _ = timeout(frame, manager.exit_notified()).await;
 
         // Process all sessions
         manager.process_all();
 
         // Clean up dead sessions periodically
         manager.cleanup_dead();
+        manager.reap_idle();
     }
 }
@@ -1,13 +1,31 @@
 //! PTY (pseudo-terminal) management.
 
 use crate::error::{Error, Result};
-use crate::types::Size;
-use portable_pty::{native_pty_system, CommandBuilder, MasterPty, PtySize};
+use crate::trace::{session_log, SessionTrace};
+use crate::types::{PixelSize, Size, SpawnSpec};
+use portable_pty::{native_pty_system, Child, ChildKiller, CommandBuilder, ExitStatus, MasterPty, PtySize};
 use std::io::{Read, Write};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::sync::Arc;
-use tokio::sync::mpsc;
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+use tokio::sync::{mpsc, Notify};
 use parking_lot::Mutex;
 
+/// `TERM` given to the child unless configured otherwise.
+pub(crate) const DEFAULT_TERM: &str = "xterm-256color";
+
+/// How long the threads of a dropped `Pty` are waited for before they are
+/// left to finish on their own.
+const DROP_TIMEOUT: Duration = Duration::from_millis(250);
+
+/// How long spawning waits for the reader thread to start.
+const READER_START_TIMEOUT: Duration = Duration::from_millis(100);
+
+/// How long a killed child has to exit after SIGHUP before it is sent SIGKILL.
+#[cfg(unix)]
+const KILL_GRACE: Duration = Duration::from_millis(250);
+
 /// Configuration for spawning a PTY.
 #[derive(Debug, Clone)]
 pub struct PtyConfig {
@@ -19,29 +37,89 @@ pub struct PtyConfig {
     pub env: Vec<(String, String)>,
     /// Initial size.
     pub size: Size,
+    /// TERM value (defaults to `xterm-256color`).
+    pub term: Option<String>,
+    /// Start the shell as a login shell, with the flags its type expects.
+    pub login: bool,
+    /// Extra arguments for the shell, after any login flags.
+    pub args: Vec<String>,
+    /// Logging for the session the PTY belongs to.
+    pub trace: SessionTrace,
+    /// Notified once the child has exited and its status is recorded.
+    pub exit_notify: Option<Arc<Notify>>,
 }
 
-impl Default for PtyConfig {
-    fn default() -> Self {
+/// Caller-provided transport for attaching a session to an existing PTY
+/// (e.g. an SSH channel or container exec stream).
+pub struct ExternalPty {
+    reader: Box<dyn Read + Send>,
+    writer: Box<dyn Write + Send>,
+    alive: Arc<AtomicBool>,
+}
+
+impl ExternalPty {
+    /// Wrap the read and write halves of an external PTY.
+    pub fn new(reader: Box<dyn Read + Send>, writer: Box<dyn Write + Send>) -> Self {
         Self {
-            cwd: None,
-            shell: None,
-            env: Vec::new(),
-            size: Size::default(),
+            reader,
+            writer,
+            alive: Arc::new(AtomicBool::new(true)),
         }
     }
+
+    /// Tie liveness to a caller-owned flag; clear it to mark the session dead.
+    ///
+    /// The flag is also cleared when the reader hits EOF or an error.
+    pub fn with_liveness(mut self, alive: Arc<AtomicBool>) -> Self {
+        self.alive = alive;
+        self
+    }
 }
 
 /// A running PTY process.
+///
+/// Writes go through a queue drained by a dedicated writer thread. Each
+/// `write` call is delivered whole and in call order, so concurrent
+/// writers can't interleave bytes inside one another's escape sequences.
 pub struct Pty {
-    writer: Arc<Mutex<Box<dyn Write + Send>>>,
-    master: Arc<Mutex<Box<dyn MasterPty + Send>>>,
+    /// Queue to the writer thread; taken on drop to stop it.
+    writer: Option<mpsc::UnboundedSender<Vec<u8>>>,
+    /// Handle to the writer thread; taken on drop.
+    writer_handle: Option<JoinHandle<()>>,
+    /// Last error hit by the writer thread, reported by the next `write`.
+    write_error: Arc<Mutex<Option<std::io::Error>>>,
+    /// Master side of a locally spawned PTY; `None` for external transports.
+    master: Option<Arc<Mutex<Box<dyn MasterPty + Send>>>>,
     /// Channel for reading PTY output.
     output_rx: mpsc::UnboundedReceiver<Vec<u8>>,
-    /// Handle to the reader thread.
-    _reader_handle: std::thread::JoinHandle<()>,
-    /// Whether the PTY is still alive.
-    alive: Arc<std::sync::atomic::AtomicBool>,
+    /// Chunks sent by the reader thread and not yet taken by `try_read`.
+    pending: Arc<AtomicUsize>,
+    /// Handle to the reader thread; taken on drop.
+    reader_handle: Option<JoinHandle<()>>,
+    /// Whether the PTY is still open: cleared on reader EOF or error, on
+    /// `kill`, or by the owner of an external transport.
+    alive: Arc<AtomicBool>,
+    /// Whether the reader stopped on an error rather than EOF.
+    read_failed: Arc<AtomicBool>,
+    /// Child process ID, when spawned locally.
+    pid: Option<u32>,
+    /// Kills the child, when spawned locally; the waiter thread owns the
+    /// child itself.
+    killer: Option<Mutex<Box<dyn ChildKiller + Send + Sync>>>,
+    /// Exit status, recorded by the waiter thread once the child exits.
+    exit_status: Arc<Mutex<Option<ExitStatus>>>,
+    /// Whether the child can still be signalled: set until the waiter
+    /// reaps it or fails to wait for it. The waiter reaps only while
+    /// holding this lock, so a signal sent under it can't reach another
+    /// process that reused the pid.
+    signallable: Arc<Mutex<bool>>,
+    /// Whether `kill` has signalled the child.
+    killed: AtomicBool,
+    /// Handle to the waiter thread; taken on drop.
+    waiter_handle: Option<JoinHandle<()>>,
+    /// Command, directory and full environment the child was spawned
+    /// with; `None` for external transports.
+    spawn_spec: Option<SpawnSpec>,
 }
 
 impl Pty {
@@ -69,110 +147,647 @@ impl Pty {
         });
 
         let mut cmd = CommandBuilder::new(&shell);
+        if config.login {
+            cmd.args(login_args(&shell));
+        }
+        cmd.args(&config.args);
 
         // Set working directory
         if let Some(ref cwd) = config.cwd {
             cmd.cwd(cwd);
         }
 
+        // Set TERM and advertise truecolor; explicit env entries override both
+        cmd.env("TERM", config.term.as_deref().unwrap_or(DEFAULT_TERM));
+        cmd.env("COLORTERM", "truecolor");
+
         // Set environment
         for (key, value) in &config.env {
             cmd.env(key, value);
         }
 
-        // Set TERM
-        cmd.env("TERM", "xterm-256color");
-
         // Enable shell integration for common shells
         if shell.contains("zsh") {
             // oh-my-zsh compatible prompt markers
             cmd.env("ITERM_SHELL_INTEGRATION_INSTALLED", "Yes");
         }
 
+        let spawn_spec = SpawnSpec {
+            program: shell.clone(),
+            args: cmd
+                .get_argv()
+                .iter()
+                .skip(1)
+                .map(|arg| arg.to_string_lossy().into_owned())
+                .collect(),
+            // Without a cwd the child inherits the app's
+            cwd: config.cwd.clone().or_else(|| {
+                std::env::current_dir()
+                    .ok()
+                    .map(|dir| dir.to_string_lossy().into_owned())
+            }),
+            env: cmd
+                .iter_full_env_as_str()
+                .map(|(key, value)| (key.to_string(), value.to_string()))
+                .collect(),
+        };
+
         // Spawn the child process
-        let _child = pair.slave.spawn_command(cmd)
-            .map_err(|e| Error::PtyError(e.to_string()))?;
+        let child = pair.slave.spawn_command(cmd)
+            .map_err(|e| spawn_error(&shell, e))?;
+        let pid = child.process_id();
 
         // Set up output reading
-        let mut reader = pair.master.try_clone_reader()
+        let reader = pair.master.try_clone_reader()
             .map_err(|e| Error::PtyError(e.to_string()))?;
-        let (output_tx, output_rx) = mpsc::unbounded_channel();
-        let alive = Arc::new(std::sync::atomic::AtomicBool::new(true));
-        let alive_clone = alive.clone();
-
-        let reader_handle = std::thread::spawn(move || {
-            let mut buf = [0u8; 8192];
-            loop {
-                match reader.read(&mut buf) {
-                    Ok(0) => {
-                        // EOF - process exited
-                        alive_clone.store(false, std::sync::atomic::Ordering::SeqCst);
-                        break;
-                    }
-                    Ok(n) => {
-                        if output_tx.send(buf[..n].to_vec()).is_err() {
-                            break;
-                        }
-                    }
-                    Err(e) => {
-                        log::error!("PTY read error: {}", e);
-                        alive_clone.store(false, std::sync::atomic::Ordering::SeqCst);
-                        break;
-                    }
-                }
-            }
-        });
+        let alive = Arc::new(AtomicBool::new(true));
+        let read_failed = Arc::new(AtomicBool::new(false));
+        let pending = Arc::new(AtomicUsize::new(0));
+        let (output_rx, reader_handle) =
+            spawn_reader(reader, alive.clone(), read_failed.clone(), pending.clone(), config.trace.clone());
+        session_log!(config.trace, Debug, "Spawned {} (pid {:?})", shell, pid);
 
         // Get writer before moving master
         let writer = pair.master.take_writer()
             .map_err(|e| Error::PtyError(e.to_string()))?;
 
+        let write_error = Arc::new(Mutex::new(None));
+        let (writer, writer_handle) = spawn_writer(writer, write_error.clone());
+
+        let killer = child.clone_killer();
+        let exit_status = Arc::new(Mutex::new(None));
+        let signallable = Arc::new(Mutex::new(true));
+        let waiter_handle = spawn_waiter(
+            child,
+            exit_status.clone(),
+            signallable.clone(),
+            config.exit_notify,
+            config.trace.clone(),
+        );
+
         Ok(Self {
-            writer: Arc::new(Mutex::new(writer)),
-            master: Arc::new(Mutex::new(pair.master)),
+            writer: Some(writer),
+            writer_handle: Some(writer_handle),
+            write_error,
+            master: Some(Arc::new(Mutex::new(pair.master))),
             output_rx,
-            _reader_handle: reader_handle,
+            pending,
+            reader_handle: Some(reader_handle),
             alive,
+            read_failed,
+            pid,
+            killer: Some(Mutex::new(killer)),
+            exit_status,
+            signallable,
+            killed: AtomicBool::new(false),
+            waiter_handle: Some(waiter_handle),
+            spawn_spec: Some(spawn_spec),
         })
     }
 
-    /// Write data to the PTY.
+    /// How the child was spawned. Its environment is the plugin's, plus
+    /// TERM and the session's overrides. `None` for external transports.
+    pub fn spawn_spec(&self) -> Option<&SpawnSpec> {
+        self.spawn_spec.as_ref()
+    }
+
+    /// Drive a PTY opened elsewhere through its read/write halves.
+    pub fn attach(external: ExternalPty, trace: SessionTrace) -> Self {
+        let ExternalPty { reader, writer, alive } = external;
+        let read_failed = Arc::new(AtomicBool::new(false));
+        let pending = Arc::new(AtomicUsize::new(0));
+        let (output_rx, reader_handle) =
+            spawn_reader(reader, alive.clone(), read_failed.clone(), pending.clone(), trace);
+
+        let write_error = Arc::new(Mutex::new(None));
+        let (writer, writer_handle) = spawn_writer(writer, write_error.clone());
+
+        Self {
+            writer: Some(writer),
+            writer_handle: Some(writer_handle),
+            write_error,
+            master: None,
+            output_rx,
+            pending,
+            reader_handle: Some(reader_handle),
+            alive,
+            read_failed,
+            pid: None,
+            killer: None,
+            exit_status: Arc::new(Mutex::new(None)),
+            signallable: Arc::new(Mutex::new(false)),
+            killed: AtomicBool::new(false),
+            waiter_handle: None,
+            spawn_spec: None,
+        }
+    }
+
+    /// Queue data to be written to the PTY.
+    ///
+    /// The bytes of one call are never split by another call's bytes.
+    /// Failures are asynchronous, so an error from an earlier write is
+    /// returned by the next call. Writing after the process has exited
+    /// fails with `WriteAfterExit`.
     pub fn write(&self, data: &[u8]) -> Result<()> {
-        let mut writer = self.writer.lock();
-        writer.write_all(data).map_err(|e| Error::PtyError(e.to_string()))?;
-        Ok(())
+        if !self.is_alive() {
+            return Err(Error::WriteAfterExit);
+        }
+        if let Some(e) = self.write_error.lock().take() {
+            return Err(if e.kind() == std::io::ErrorKind::BrokenPipe || !self.is_alive() {
+                Error::WriteAfterExit
+            } else {
+                Error::WriteFailed(e.to_string())
+            });
+        }
+
+        let writer = self.writer.as_ref().ok_or(Error::WriteAfterExit)?;
+        writer.send(data.to_vec()).map_err(|_| Error::WriteAfterExit)
     }
 
     /// Resize the PTY.
-    pub fn resize(&self, cols: u16, rows: u16) -> Result<()> {
-        let master = self.master.lock();
+    ///
+    /// External transports are resized by their owner, so this is a no-op for
+    /// them. Resizing after the process has exited fails with `WriteAfterExit`.
+    pub fn resize(&self, cols: u16, rows: u16, pixels: PixelSize) -> Result<()> {
+        if !self.is_alive() {
+            return Err(Error::WriteAfterExit);
+        }
+        let Some(master) = &self.master else {
+            return Ok(());
+        };
+        let master = master.lock();
         master.resize(PtySize {
             rows,
             cols,
-            pixel_width: 0,
-            pixel_height: 0,
-        }).map_err(|e| Error::PtyError(e.to_string()))?;
+            pixel_width: pixels.width,
+            pixel_height: pixels.height,
+        }).map_err(|e| Error::ResizeFailed(e.to_string()))?;
         Ok(())
     }
 
-    /// Check if PTY has output available.
-    pub async fn read(&mut self) -> Option<Vec<u8>> {
-        self.output_rx.recv().await
+    /// Whether the terminal driver is echoing input, from the PTY's termios.
+    ///
+    /// Programs turn echo off while reading passwords. `None` when it can't be
+    /// determined (Windows, external transports).
+    pub fn echo_enabled(&self) -> Option<bool> {
+        #[cfg(unix)]
+        {
+            let master = self.master.as_ref()?.lock();
+            let fd = master.as_raw_fd()?;
+            let mut termios = std::mem::MaybeUninit::<libc::termios>::uninit();
+            // SAFETY: `fd` is the live master fd and `termios` is only read on success
+            if unsafe { libc::tcgetattr(fd, termios.as_mut_ptr()) } != 0 {
+                return None;
+            }
+            let termios = unsafe { termios.assume_init() };
+            Some(termios.c_lflag & libc::ECHO != 0)
+        }
+        #[cfg(not(unix))]
+        {
+            None
+        }
+    }
+
+    /// Whether a job other than the shell holds the terminal's foreground,
+    /// from the PTY's foreground process group.
+    ///
+    /// Only shells with job control move commands into their own group.
+    /// `None` when it can't be determined (Windows, external transports).
+    pub fn foreground_job_running(&self) -> Option<bool> {
+        #[cfg(unix)]
+        {
+            let leader = self.master.as_ref()?.lock().process_group_leader()?;
+            Some(leader as u32 != self.pid?)
+        }
+        #[cfg(not(unix))]
+        {
+            None
+        }
     }
 
     /// Try to read without blocking.
     pub fn try_read(&mut self) -> Option<Vec<u8>> {
-        self.output_rx.try_recv().ok()
+        let data = self.output_rx.try_recv().ok()?;
+        self.pending.fetch_sub(1, Ordering::SeqCst);
+        Some(data)
     }
 
-    /// Check if the PTY process is still alive.
+    /// Whether output is waiting to be read, without consuming it.
+    pub fn has_pending_output(&self) -> bool {
+        self.pending.load(Ordering::SeqCst) > 0
+    }
+
+    /// Check if the PTY process is still alive: the PTY is open and the
+    /// child hasn't exited. A background process can hold the PTY open
+    /// after the child exits.
     pub fn is_alive(&self) -> bool {
-        self.alive.load(std::sync::atomic::Ordering::SeqCst)
+        self.is_reading() && self.exit_status.lock().is_none()
+    }
+
+    /// Whether the reader is still receiving output (no EOF yet).
+    pub fn is_reading(&self) -> bool {
+        self.alive.load(Ordering::SeqCst)
+    }
+
+    /// Child process ID, if spawned locally.
+    pub fn pid(&self) -> Option<u32> {
+        self.pid
+    }
+
+    /// Whether the reader stopped on a read error rather than EOF.
+    ///
+    /// On Linux a closed PTY reads as EIO, so this is also set on normal exits.
+    pub fn read_failed(&self) -> bool {
+        self.read_failed.load(Ordering::SeqCst)
+    }
+
+    /// Whether there is a local child to reap (false for external transports).
+    pub fn has_child(&self) -> bool {
+        self.killer.is_some()
+    }
+
+    /// The child's exit status, once the waiter thread has reaped it.
+    pub fn exit_status(&self) -> Option<ExitStatus> {
+        self.exit_status.lock().clone()
     }
 
     /// Kill the PTY process.
     pub fn kill(&self) {
-        self.alive.store(false, std::sync::atomic::Ordering::SeqCst);
+        self.alive.store(false, Ordering::SeqCst);
+        if self.killed.swap(true, Ordering::SeqCst) {
+            return;
+        }
+        // The killer only sends SIGHUP, which the child may ignore
+        if let Some(killer) = &self.killer {
+            // Once reaped, the pid may already belong to another process
+            let signallable = self.signallable.lock();
+            if !*signallable {
+                return;
+            }
+            let _ = killer.lock().kill();
+            drop(signallable);
+            #[cfg(unix)]
+            if let Some(pid) = self.pid {
+                spawn_kill_escalation(pid, self.exit_status.clone(), self.signallable.clone());
+            }
+        }
         // The reader thread will exit when it detects the process is gone
     }
 }
+
+impl Drop for Pty {
+    fn drop(&mut self) {
+        self.kill();
+        self.master = None;
+        // Closing the queue stops the writer once it has flushed
+        self.writer = None;
+
+        // The waiter returns once the killed child is reaped, and the reader
+        // once every process holding the PTY has exited, which a background
+        // process can put off indefinitely. Wait for them on another thread
+        // so dropping never blocks the caller, usually holding the session lock.
+        let handles = [
+            (self.waiter_handle.take(), "waiter"),
+            (self.writer_handle.take(), "writer"),
+            (self.reader_handle.take(), "reader"),
+        ];
+        if handles.iter().all(|(handle, _)| handle.is_none()) {
+            return;
+        }
+        std::thread::spawn(move || {
+            let deadline = Instant::now() + DROP_TIMEOUT;
+            for (handle, name) in handles {
+                join_until(handle, deadline, name);
+            }
+        });
+    }
+}
+
+/// Join a thread if it finishes before `deadline`, otherwise let it detach
+/// (e.g. blocked on an external transport).
+fn join_until(handle: Option<JoinHandle<()>>, deadline: Instant, name: &str) {
+    let Some(handle) = handle else {
+        return;
+    };
+    while !handle.is_finished() && Instant::now() < deadline {
+        std::thread::sleep(Duration::from_millis(5));
+    }
+    if handle.is_finished() {
+        let _ = handle.join();
+    } else {
+        log::debug!("PTY {} thread still running after drop", name);
+    }
+}
+
+/// Spawn the thread that drains the write queue into the PTY, recording the
+/// first error for the next `write` to report.
+fn spawn_writer(
+    mut writer: Box<dyn Write + Send>,
+    write_error: Arc<Mutex<Option<std::io::Error>>>,
+) -> (mpsc::UnboundedSender<Vec<u8>>, JoinHandle<()>) {
+    let (tx, mut rx) = mpsc::unbounded_channel::<Vec<u8>>();
+
+    let handle = std::thread::spawn(move || {
+        while let Some(data) = rx.blocking_recv() {
+            if let Err(e) = writer.write_all(&data).and_then(|_| writer.flush()) {
+                log::warn!("PTY write error: {}", e);
+                let mut slot = write_error.lock();
+                if slot.is_none() {
+                    *slot = Some(e);
+                }
+            }
+        }
+    });
+
+    (tx, handle)
+}
+
+/// Flags that make `shell` an interactive login shell, chosen by its name.
+fn login_args(shell: &str) -> &'static [&'static str] {
+    let name = std::path::Path::new(shell)
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_ascii_lowercase())
+        .unwrap_or_default();
+    match name.as_str() {
+        "bash" | "zsh" | "fish" | "ksh" | "mksh" | "nu" => &["-l", "-i"],
+        // csh only accepts -l on its own
+        "sh" | "dash" | "tcsh" | "csh" => &["-l"],
+        "pwsh" if cfg!(unix) => &["-Login"],
+        _ => &[],
+    }
+}
+
+/// Working directory of process `pid`, read from the OS.
+///
+/// Supported on Linux (`/proc/<pid>/cwd`) and macOS (`proc_pidinfo`);
+/// `None` on other platforms or when the process can't be inspected.
+pub(crate) fn process_cwd(pid: u32) -> Option<String> {
+    #[cfg(target_os = "linux")]
+    {
+        std::fs::read_link(format!("/proc/{}/cwd", pid))
+            .ok()
+            .map(|path| path.to_string_lossy().into_owned())
+    }
+    #[cfg(target_os = "macos")]
+    {
+        let mut info = std::mem::MaybeUninit::<libc::proc_vnodepathinfo>::zeroed();
+        let size = std::mem::size_of::<libc::proc_vnodepathinfo>() as libc::c_int;
+        // SAFETY: `info` is a writable buffer of `size` bytes
+        let written = unsafe {
+            libc::proc_pidinfo(
+                pid as libc::c_int,
+                libc::PROC_PIDVNODEPATHINFO,
+                0,
+                info.as_mut_ptr().cast(),
+                size,
+            )
+        };
+        if written != size {
+            return None;
+        }
+        // SAFETY: fully written above; the kernel NUL-terminates the path
+        let info = unsafe { info.assume_init() };
+        let path = unsafe { std::ffi::CStr::from_ptr(info.pvi_cdir.vip_path.as_ptr().cast()) };
+        Some(path.to_string_lossy().into_owned())
+    }
+    #[cfg(not(any(target_os = "linux", target_os = "macos")))]
+    {
+        let _ = pid;
+        None
+    }
+}
+
+/// Description of the signal that terminated the child, if any.
+///
+/// portable-pty 0.8 keeps the signal private and only surfaces it through
+/// `Display` (`"Terminated by <signal>"`).
+pub(crate) fn exit_signal(status: &ExitStatus) -> Option<String> {
+    if status.success() {
+        return None;
+    }
+    status
+        .to_string()
+        .strip_prefix("Terminated by ")
+        .map(str::to_string)
+}
+
+/// Signal number for a signal description as reported by [`exit_signal`]:
+/// the `strsignal` text, e.g. `"Terminated"`, which on some platforms ends
+/// in the number itself, e.g. `"Killed: 9"`.
+pub(crate) fn signal_number(name: &str) -> Option<i32> {
+    let trailing = name
+        .rsplit_once(':')
+        .map(|(_, number)| number)
+        .or_else(|| name.strip_prefix("Unknown signal "));
+    match trailing.and_then(|number| number.trim().parse().ok()) {
+        Some(number) => Some(number),
+        None => named_signal(name),
+    }
+}
+
+#[cfg(unix)]
+fn named_signal(name: &str) -> Option<i32> {
+    let number = match name.trim_start_matches("SIG").to_ascii_lowercase().as_str() {
+        "hangup" | "hup" => libc::SIGHUP,
+        "interrupt" | "int" => libc::SIGINT,
+        "quit" => libc::SIGQUIT,
+        "illegal instruction" | "ill" => libc::SIGILL,
+        "trace/breakpoint trap" | "trap" => libc::SIGTRAP,
+        "aborted" | "abort" | "abrt" => libc::SIGABRT,
+        "bus error" | "bus" => libc::SIGBUS,
+        "floating point exception" | "fpe" => libc::SIGFPE,
+        "killed" | "kill" => libc::SIGKILL,
+        "user defined signal 1" | "usr1" => libc::SIGUSR1,
+        "segmentation fault" | "segv" => libc::SIGSEGV,
+        "user defined signal 2" | "usr2" => libc::SIGUSR2,
+        "broken pipe" | "pipe" => libc::SIGPIPE,
+        "alarm clock" | "alrm" => libc::SIGALRM,
+        "terminated" | "term" => libc::SIGTERM,
+        "cpu time limit exceeded" | "xcpu" => libc::SIGXCPU,
+        "file size limit exceeded" | "xfsz" => libc::SIGXFSZ,
+        "virtual timer expired" | "vtalrm" => libc::SIGVTALRM,
+        "profiling timer expired" | "prof" => libc::SIGPROF,
+        "i/o possible" | "io" => libc::SIGIO,
+        "bad system call" | "sys" => libc::SIGSYS,
+        _ => return None,
+    };
+    Some(number)
+}
+
+#[cfg(not(unix))]
+fn named_signal(_name: &str) -> Option<i32> {
+    None
+}
+
+/// Classify a spawn failure so the frontend can tell a missing shell from
+/// a permissions problem.
+fn spawn_error(shell: &str, e: anyhow::Error) -> Error {
+    match e.downcast_ref::<std::io::Error>().map(|io| io.kind()) {
+        Some(std::io::ErrorKind::NotFound) => Error::ShellNotFound(shell.to_string()),
+        Some(std::io::ErrorKind::PermissionDenied) => Error::PermissionDenied(shell.to_string()),
+        _ => Error::SpawnFailed(e.to_string()),
+    }
+}
+
+/// Spawn the thread that blocks until the child exits and records its
+/// status, so the exit is seen even while a background process it started
+/// keeps the PTY open.
+///
+/// The child is reaped with `signallable` held and cleared, so nothing
+/// signals its pid once it could be reused. If waiting fails, the child
+/// is never signalled again.
+fn spawn_waiter(
+    mut child: Box<dyn Child + Send + Sync>,
+    exit_status: Arc<Mutex<Option<ExitStatus>>>,
+    signallable: Arc<Mutex<bool>>,
+    exit_notify: Option<Arc<Notify>>,
+    trace: SessionTrace,
+) -> JoinHandle<()> {
+    std::thread::spawn(move || {
+        let _entered = trace.enter();
+        #[cfg(unix)]
+        if let Some(pid) = child.process_id() {
+            if let Err(e) = wait_exited(pid) {
+                session_log!(trace, Error, "Failed to wait for child: {}", e);
+                *signallable.lock() = false;
+            }
+        }
+        let waited = {
+            let mut signallable = signallable.lock();
+            *signallable = false;
+            child.wait()
+        };
+        match waited {
+            Ok(status) => {
+                session_log!(trace, Debug, "Child exited with {:?}", status);
+                *exit_status.lock() = Some(status);
+                if let Some(notify) = exit_notify {
+                    notify.notify_one();
+                }
+            }
+            Err(e) => session_log!(trace, Error, "Failed to wait for child: {}", e),
+        }
+    })
+}
+
+/// Block until child `pid` has exited, leaving it unreaped so its pid
+/// can't be reused yet.
+#[cfg(unix)]
+fn wait_exited(pid: u32) -> std::io::Result<()> {
+    loop {
+        // SAFETY: `siginfo_t` is plain data, valid when zeroed
+        let mut info = unsafe { std::mem::zeroed::<libc::siginfo_t>() };
+        // SAFETY: `info` is a writable `siginfo_t`; WNOWAIT leaves the
+        // child to be reaped by `Child::wait`
+        let result = unsafe {
+            libc::waitid(libc::P_PID, pid as libc::id_t, &mut info, libc::WEXITED | libc::WNOWAIT)
+        };
+        if result == 0 {
+            return Ok(());
+        }
+        let error = std::io::Error::last_os_error();
+        if error.kind() != std::io::ErrorKind::Interrupted {
+            return Err(error);
+        }
+    }
+}
+
+/// Send SIGKILL to a child that hasn't exited `KILL_GRACE` after SIGHUP.
+#[cfg(unix)]
+fn spawn_kill_escalation(
+    pid: u32,
+    exit_status: Arc<Mutex<Option<ExitStatus>>>,
+    signallable: Arc<Mutex<bool>>,
+) {
+    std::thread::spawn(move || {
+        let deadline = Instant::now() + KILL_GRACE;
+        while Instant::now() < deadline {
+            if exit_status.lock().is_some() {
+                return;
+            }
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        let signallable = signallable.lock();
+        if *signallable {
+            // SAFETY: `kill` has no memory-safety requirements. The waiter
+            // reaps the child only while holding `signallable` and after
+            // clearing it, so `pid` is still our child (at worst a zombie),
+            // never a process that reused the pid.
+            unsafe {
+                libc::kill(pid as libc::pid_t, libc::SIGKILL);
+            }
+        }
+    });
+}
+
+/// Spawn the thread that forwards PTY output into a channel, clearing
+/// `alive` on EOF or read error and setting `read_failed` on error.
+fn spawn_reader(
+    mut reader: Box<dyn Read + Send>,
+    alive: Arc<AtomicBool>,
+    read_failed: Arc<AtomicBool>,
+    pending: Arc<AtomicUsize>,
+    trace: SessionTrace,
+) -> (mpsc::UnboundedReceiver<Vec<u8>>, std::thread::JoinHandle<()>) {
+    let (output_tx, output_rx) = mpsc::unbounded_channel();
+    let (ready_tx, ready_rx) = std::sync::mpsc::channel();
+
+    let handle = std::thread::spawn(move || {
+        let _entered = trace.enter();
+        let _ = ready_tx.send(());
+        let mut buf = [0u8; 8192];
+        loop {
+            match reader.read(&mut buf) {
+                Ok(0) => {
+                    // EOF - every process holding the PTY has exited
+                    session_log!(trace, Debug, "PTY closed");
+                    alive.store(false, Ordering::SeqCst);
+                    break;
+                }
+                Ok(n) => {
+                    session_log!(trace, Trace, "Read {} bytes", n);
+                    // Count before sending so `try_read` never sees the chunk first
+                    pending.fetch_add(1, Ordering::SeqCst);
+                    if output_tx.send(buf[..n].to_vec()).is_err() {
+                        break;
+                    }
+                }
+                Err(e) => {
+                    session_log!(trace, Error, "PTY read error: {}", e);
+                    read_failed.store(true, Ordering::SeqCst);
+                    alive.store(false, Ordering::SeqCst);
+                    break;
+                }
+            }
+        }
+    });
+
+    // Make sure the reader is running before the first poll, so a shell's
+    // initial prompt isn't left sitting in the PTY for several frames
+    let _ = ready_rx.recv_timeout(READER_START_TIMEOUT);
+
+    (output_rx, handle)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::signal_number;
+
+    #[test]
+    fn signal_number_reads_a_trailing_number() {
+        assert_eq!(signal_number("Terminated: 15"), Some(15));
+        assert_eq!(signal_number("Killed: 9"), Some(9));
+        assert_eq!(signal_number("Unknown signal 40"), Some(40));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn signal_number_reads_a_name() {
+        assert_eq!(signal_number("Terminated"), Some(libc::SIGTERM));
+        assert_eq!(signal_number("Hangup"), Some(libc::SIGHUP));
+        assert_eq!(signal_number("User defined signal 1"), Some(libc::SIGUSR1));
+        assert_eq!(signal_number("User defined signal 2"), Some(libc::SIGUSR2));
+        assert_eq!(signal_number("SIGSEGV"), Some(libc::SIGSEGV));
+        assert_eq!(signal_number("Real-time signal 3"), None);
+    }
+}
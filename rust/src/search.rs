@@ -0,0 +1,112 @@
+//! Plain-text search over the terminal buffer.
+//!
+//! Scrollback lines don't change once they've scrolled off the screen, so
+//! when indexing is enabled their text and a case-folded copy are kept here
+//! instead of being read back out of vt100 on every search.
+
+/// Text of one buffer line and its case-folded copy.
+struct IndexedLine {
+    text: String,
+    folded: String,
+}
+
+/// Cached text of the scrollback, oldest line first.
+#[derive(Default)]
+pub(crate) struct SearchIndex {
+    lines: Vec<IndexedLine>,
+    /// The scrollback may have shifted or been cleared since lines were read.
+    stale: bool,
+}
+
+impl SearchIndex {
+    /// Mark the indexed lines as no longer matching the scrollback.
+    pub fn invalidate(&mut self) {
+        self.stale = true;
+    }
+
+    /// Number of lines still valid for a scrollback of `len` lines,
+    /// dropping everything if the index fell out of step.
+    pub fn valid_lines(&mut self, len: usize) -> usize {
+        if self.stale || self.lines.len() > len {
+            self.lines.clear();
+            self.stale = false;
+        }
+        self.lines.len()
+    }
+
+    /// Append the text of the next scrollback line.
+    pub fn push(&mut self, text: String) {
+        let folded = fold(&text);
+        self.lines.push(IndexedLine { text, folded });
+    }
+
+    /// Text of an indexed line, folded for case-insensitive search.
+    pub fn line(&self, line: usize, case_sensitive: bool) -> &str {
+        let line = &self.lines[line];
+        if case_sensitive {
+            &line.text
+        } else {
+            &line.folded
+        }
+    }
+}
+
+/// Lowercase `text` one character at a time, keeping characters whose
+/// lowercase form is longer, so character offsets line up with the original.
+pub(crate) fn fold(text: &str) -> String {
+    text.chars()
+        .map(|c| {
+            let mut lower = c.to_lowercase();
+            match (lower.next(), lower.next()) {
+                (Some(l), None) => l,
+                _ => c,
+            }
+        })
+        .collect()
+}
+
+/// Character offsets of the non-overlapping occurrences of `needle`.
+pub(crate) fn char_matches(haystack: &str, needle: &str) -> Vec<usize> {
+    haystack
+        .match_indices(needle)
+        .map(|(i, _)| haystack[..i].chars().count())
+        .collect()
+}
+
+/// Text of a visible row, right-trimmed, with the column each character
+/// starts at followed by the column just past the last one.
+///
+/// Wide characters appear once and empty cells as spaces.
+pub(crate) fn line_text(screen: &vt100::Screen, row: u16, cols: u16) -> (String, Vec<u16>) {
+    let mut text = String::new();
+    let mut columns = Vec::new();
+    let (mut kept_chars, mut end) = (0, 0);
+    for col in 0..cols {
+        let Some(cell) = screen.cell(row, col) else {
+            break;
+        };
+        if cell.is_wide_continuation() {
+            continue;
+        }
+        let contents = cell.contents();
+        if contents.is_empty() {
+            text.push(' ');
+            columns.push(col);
+            continue;
+        }
+        for c in contents.chars() {
+            text.push(c);
+            columns.push(col);
+        }
+        if !contents.trim_end().is_empty() {
+            kept_chars = columns.len();
+            end = col + if cell.is_wide() { 2 } else { 1 };
+        }
+    }
+
+    let kept_len = text.char_indices().nth(kept_chars).map_or(text.len(), |(i, _)| i);
+    text.truncate(kept_len);
+    columns.truncate(kept_chars);
+    columns.push(end);
+    (text, columns)
+}
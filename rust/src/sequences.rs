@@ -0,0 +1,265 @@
+//! Side-channel scanner for escape sequences vt100 doesn't surface.
+//!
+//! vt100 silently consumes queries (DSR, DA), dynamic color OSCs, and
+//! several mode changes. The scanner runs over the same byte stream and
+//! reports those sequences along with their end offset, so `Terminal` can
+//! interleave them with vt100 processing in stream order.
+
+/// Maximum length of a buffered OSC payload.
+const MAX_OSC_LEN: usize = 8 * 1024 * 1024;
+
+/// Maximum length of a buffered CSI parameter string.
+const MAX_CSI_LEN: usize = 256;
+
+/// A recognized escape sequence.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Sequence {
+    /// Operating System Command (`ESC ] ... BEL|ST`).
+    Osc {
+        data: Vec<u8>,
+        /// Whether the sequence was terminated with BEL rather than ST.
+        bel: bool,
+    },
+    /// Control Sequence Introducer (`ESC [ ... final`).
+    Csi(Csi),
+    /// Plain escape sequence (`ESC intermediates final`).
+    Esc { intermediates: Vec<u8>, action: u8 },
+    /// C0 control that needs handling outside vt100 (currently only HT).
+    Control(u8),
+}
+
+/// A parsed CSI sequence.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Csi {
+    /// Private marker (`?`, `>`, `<`, `=`), if any.
+    pub private: Option<u8>,
+    /// Raw parameter bytes (digits, `;` and `:`).
+    pub params: Vec<u8>,
+    /// Intermediate bytes (`0x20..=0x2f`).
+    pub intermediates: Vec<u8>,
+    /// Final byte.
+    pub action: u8,
+}
+
+impl Csi {
+    /// Numeric parameters, with empty parameters reported as 0.
+    pub fn params(&self) -> Vec<u16> {
+        if self.params.is_empty() {
+            return Vec::new();
+        }
+        self.params
+            .split(|b| *b == b';')
+            .map(|p| {
+                let digits = p.split(|b| *b == b':').next().unwrap_or(&[]);
+                std::str::from_utf8(digits)
+                    .ok()
+                    .and_then(|s| s.parse().ok())
+                    .unwrap_or(0)
+            })
+            .collect()
+    }
+
+    /// Parameter at `idx`, or `default` when missing or zero.
+    pub fn param(&self, idx: usize, default: u16) -> u16 {
+        match self.params().get(idx) {
+            Some(&0) | None => default,
+            Some(&v) => v,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum State {
+    Ground,
+    Escape,
+    EscapeIntermediate,
+    Csi,
+    Osc,
+    OscEscape,
+    /// DCS/APC/PM/SOS payloads are skipped.
+    String,
+    StringEscape,
+}
+
+/// Incremental escape sequence scanner.
+///
+/// State persists across `feed` calls so sequences split over PTY reads are
+/// still recognized.
+pub struct Scanner {
+    state: State,
+    csi: Csi,
+    intermediates: Vec<u8>,
+    osc: Vec<u8>,
+}
+
+impl Default for Scanner {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Scanner {
+    /// Create a scanner in the ground state.
+    pub fn new() -> Self {
+        Self {
+            state: State::Ground,
+            csi: Csi::default(),
+            intermediates: Vec::new(),
+            osc: Vec::new(),
+        }
+    }
+
+    /// Scan a chunk, returning each completed sequence with the offset just
+    /// past its final byte.
+    pub fn feed(&mut self, data: &[u8]) -> Vec<(usize, Sequence)> {
+        let mut out = Vec::new();
+
+        for (i, &b) in data.iter().enumerate() {
+            if let Some(seq) = self.advance(b) {
+                out.push((i + 1, seq));
+            }
+        }
+
+        out
+    }
+
+    fn advance(&mut self, b: u8) -> Option<Sequence> {
+        match self.state {
+            State::Ground => match b {
+                0x1b => {
+                    self.state = State::Escape;
+                    None
+                }
+                0x09 => Some(Sequence::Control(b)),
+                _ => None,
+            },
+            State::Escape => {
+                self.intermediates.clear();
+                match b {
+                    b'[' => {
+                        self.csi = Csi::default();
+                        self.state = State::Csi;
+                    }
+                    b']' => {
+                        self.osc.clear();
+                        self.state = State::Osc;
+                    }
+                    b'P' | b'X' | b'^' | b'_' => self.state = State::String,
+                    0x1b => {}
+                    0x20..=0x2f => {
+                        self.intermediates.push(b);
+                        self.state = State::EscapeIntermediate;
+                    }
+                    0x30..=0x7e => {
+                        self.state = State::Ground;
+                        return Some(Sequence::Esc {
+                            intermediates: Vec::new(),
+                            action: b,
+                        });
+                    }
+                    _ => self.state = State::Ground,
+                }
+                None
+            }
+            State::EscapeIntermediate => match b {
+                0x20..=0x2f => {
+                    self.intermediates.push(b);
+                    None
+                }
+                0x30..=0x7e => {
+                    self.state = State::Ground;
+                    Some(Sequence::Esc {
+                        intermediates: std::mem::take(&mut self.intermediates),
+                        action: b,
+                    })
+                }
+                0x1b => {
+                    self.state = State::Escape;
+                    None
+                }
+                _ => {
+                    self.state = State::Ground;
+                    None
+                }
+            },
+            State::Csi => match b {
+                b'<' | b'=' | b'>' | b'?' if self.csi.params.is_empty() && self.csi.private.is_none() => {
+                    self.csi.private = Some(b);
+                    None
+                }
+                0x30..=0x3f => {
+                    if self.csi.params.len() < MAX_CSI_LEN {
+                        self.csi.params.push(b);
+                    }
+                    None
+                }
+                0x20..=0x2f => {
+                    self.csi.intermediates.push(b);
+                    None
+                }
+                0x40..=0x7e => {
+                    self.state = State::Ground;
+                    let mut csi = std::mem::take(&mut self.csi);
+                    csi.action = b;
+                    Some(Sequence::Csi(csi))
+                }
+                0x1b => {
+                    self.state = State::Escape;
+                    None
+                }
+                // C0 controls are executed mid-sequence; nothing to record
+                _ => None,
+            },
+            State::Osc => match b {
+                0x07 => {
+                    self.state = State::Ground;
+                    Some(Sequence::Osc {
+                        data: std::mem::take(&mut self.osc),
+                        bel: true,
+                    })
+                }
+                0x1b => {
+                    self.state = State::OscEscape;
+                    None
+                }
+                _ => {
+                    if self.osc.len() < MAX_OSC_LEN {
+                        self.osc.push(b);
+                    }
+                    None
+                }
+            },
+            State::OscEscape => {
+                if b == b'\\' {
+                    self.state = State::Ground;
+                    Some(Sequence::Osc {
+                        data: std::mem::take(&mut self.osc),
+                        bel: false,
+                    })
+                } else {
+                    // Unterminated OSC; treat this byte as the start of a new escape
+                    self.osc.clear();
+                    self.state = State::Escape;
+                    self.advance(b)
+                }
+            }
+            State::String => {
+                match b {
+                    0x07 => self.state = State::Ground,
+                    0x1b => self.state = State::StringEscape,
+                    _ => {}
+                }
+                None
+            }
+            State::StringEscape => {
+                if b == b'\\' {
+                    self.state = State::Ground;
+                    None
+                } else {
+                    self.state = State::Escape;
+                    self.advance(b)
+                }
+            }
+        }
+    }
+}
@@ -1,17 +1,66 @@
 //! Terminal session management.
 
 use crate::error::{Error, Result};
-use crate::events::{EventSender, TerminalEvent};
-use crate::pty::{Pty, PtyConfig};
-use crate::terminal::Terminal;
+use crate::events::{event_channel, EventReceiver, EventSender, TerminalEvent};
+use crate::keys::encode_key;
+use crate::pty::{exit_signal, process_cwd, signal_number, DEFAULT_TERM, ExternalPty, Pty, PtyConfig};
+use crate::terminal::{ColorRequest, Terminal, SCROLLBACK_LINES};
 use crate::theme::Theme;
-use crate::types::{Cursor, Mark, Screen, ScreenUpdate, Size};
-use parking_lot::RwLock;
+use crate::trace::{session_log, SessionTrace};
+use crate::types::{BellMode, BufferStats, C1Mode, Capabilities, Cell, Color, Cursor, CursorPosition, DeviceReports, DynamicColor, ExitReason, ExportFormat, ImagePlacement, KeyBinding, KeyEvent, KeyEventType, LogLevel, Mark, MatchRange, MarkType, PixelSize, Row, Screen, ScreenUpdate, ScriptLineResult, SearchDirection, SearchMatch, Size, SpawnSpec, TimeoutAction, Viewport};
+use parking_lot::{Mutex, RwLock};
+use regex::Regex;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
 use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::Notify;
 use uuid::Uuid;
 
+/// How long to wait for a child's exit status after its PTY closes before
+/// reporting the exit without a code.
+const EXIT_STATUS_GRACE: Duration = Duration::from_millis(500);
+
+/// Contrast kept between an overridden cursor color and the text under it.
+const CURSOR_TEXT_CONTRAST: f32 = 4.5;
+
+/// Written by `close_session` when no close sequence is configured.
+const DEFAULT_CLOSE_SEQUENCE: &str = "exit\n";
+
+/// How long `close_session` waits for a graceful exit by default.
+const DEFAULT_CLOSE_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// How often `run_script` and `expect` check a session.
+pub(crate) const SCRIPT_POLL_INTERVAL: Duration = Duration::from_millis(20);
+
+/// Quiet period treated as completion when the shell has no OSC 133 marks.
+const SCRIPT_IDLE: Duration = Duration::from_millis(300);
+
+/// Output included in a `CommandTimeout` error.
+const SCRIPT_TAIL_BYTES: usize = 512;
+
+/// Default fraction of the grid changed at once that triggers a full refresh.
+pub(crate) const DEFAULT_FULL_REFRESH_RATIO: f32 = 0.6;
+
+/// Default cap on cols × rows, so a mistaken size can't produce screens
+/// of tens of megabytes of JSON.
+pub(crate) const DEFAULT_MAX_CELLS: usize = 200_000;
+
+/// Buffer lines rendered at a time by `export_to_file`.
+const EXPORT_PAGE_LINES: usize = 500;
+
+/// Rows above the bottom within which the cursor still counts as "at the bottom".
+const AUTOSCROLL_MARGIN: u16 = 1;
+
+/// Logical clock stamped on sessions when they emit a screen update.
+///
+/// Strictly increasing across all sessions, so "changed since" queries
+/// never miss an update that landed in the same millisecond.
+static UPDATE_CLOCK: AtomicU64 = AtomicU64::new(0);
+
 /// Unique session identifier.
 pub type SessionId = String;
 
@@ -24,12 +73,18 @@ pub struct SessionConfig {
     /// Working directory.
     #[serde(default)]
     pub cwd: Option<String>,
+    /// Start in the home directory instead of failing when `cwd` is invalid.
+    #[serde(default)]
+    pub cwd_fallback: bool,
     /// Shell to use.
     #[serde(default)]
     pub shell: Option<String>,
     /// Environment variables.
     #[serde(default)]
     pub env: HashMap<String, String>,
+    /// TERM value (defaults to `xterm-256color`); `env` takes precedence.
+    #[serde(default)]
+    pub term: Option<String>,
     /// Initial terminal size.
     #[serde(default)]
     pub cols: Option<u16>,
@@ -38,6 +93,131 @@ pub struct SessionConfig {
     /// Theme name.
     #[serde(default)]
     pub theme: Option<String>,
+    /// How the bell is surfaced to the frontend.
+    #[serde(default)]
+    pub bell_mode: BellMode,
+    /// Minimum time between bell events; bells rung sooner are dropped so
+    /// a burst of BELs surfaces once.
+    #[serde(default = "default_bell_interval_ms")]
+    pub bell_interval_ms: u64,
+    /// Identity reported in reply to DA/XTVERSION queries.
+    #[serde(default)]
+    pub device_reports: DeviceReports,
+    /// Diff scrolls inside a DECSTBM region as a shift plus the exposed rows.
+    #[serde(default = "default_true")]
+    pub scroll_region_diffing: bool,
+    /// Minimum WCAG contrast ratio between text and background (e.g. 4.5).
+    #[serde(default)]
+    pub a11y_contrast: Option<f32>,
+    /// Shown in place of control characters and noncharacters that reach
+    /// the grid; defaults to U+FFFD. Such cells are flagged `substituted`.
+    #[serde(default)]
+    pub replacement_char: Option<char>,
+    /// RGB values replacing indexed colors (e.g. `1` → orange for red),
+    /// applied on top of the theme.
+    #[serde(default)]
+    pub color_overrides: HashMap<u8, Color>,
+    /// Total PTY output sizes at which an `OutputThreshold` event is emitted.
+    #[serde(default)]
+    pub output_byte_thresholds: Vec<u64>,
+    /// Kill the session once its total PTY output exceeds this many bytes.
+    #[serde(default)]
+    pub output_byte_limit: Option<u64>,
+    /// Shortcuts handled by the app instead of being sent to the PTY.
+    #[serde(default)]
+    pub key_bindings: Vec<KeyBinding>,
+    /// Destroy the session after this long without input or output.
+    #[serde(default)]
+    pub idle_timeout_ms: Option<u64>,
+    /// Remove the session this long after its `ProcessExit`. By default an
+    /// exited session stays, with its final screen, until it is closed or
+    /// destroyed.
+    #[serde(default)]
+    pub exit_retention_ms: Option<u64>,
+    /// Hold output for this long after the session starts and send it as
+    /// one update, so the startup burst (motd, prompt) paints once.
+    #[serde(default)]
+    pub initial_coalesce_ms: Option<u64>,
+    /// Advise the frontend to follow new output to the bottom.
+    #[serde(default = "default_true")]
+    pub scroll_on_output: bool,
+    /// Advise the frontend to jump to the bottom when the user types.
+    #[serde(default = "default_true")]
+    pub scroll_on_input: bool,
+    /// Emit an `InputAudit` event for every write to the PTY.
+    #[serde(default)]
+    pub audit_input: bool,
+    /// Withhold audited bytes while the PTY has echo off (password prompts).
+    #[serde(default)]
+    pub redact_passwords: bool,
+    /// Software flow control: Ctrl-S (XOFF) typed into the session stops
+    /// reading its output and Ctrl-Q (XON) resumes it. Both bytes are
+    /// consumed rather than sent to the program.
+    #[serde(default)]
+    pub flow_control: bool,
+    /// Force line wrapping on or off, ignoring DECAWM.
+    #[serde(default)]
+    pub wrap_override: Option<bool>,
+    /// Draw text printed in the DEC special graphics charset (`ESC ( 0`,
+    /// used for ncurses borders) as Unicode box-drawing characters.
+    #[serde(default = "default_true")]
+    pub line_drawing: bool,
+    /// Interpret bytes 0x80–0x9F as UTF-8 (default) or 8-bit C1 controls.
+    #[serde(default)]
+    pub c1_controls: C1Mode,
+    /// Keep an index of the scrollback's text so repeated searches (find
+    /// as you type) don't re-read the whole buffer. Roughly doubles the
+    /// memory used by the scrollback's text.
+    #[serde(default)]
+    pub search_index: bool,
+    /// Run output through the emulator. When false, output is only relayed
+    /// as `RawOutput` events for a frontend that does its own emulation, and
+    /// screen queries see an empty screen.
+    #[serde(default = "default_true")]
+    pub emulate: bool,
+    /// Template for the title sent in `TitleChange` and screen updates,
+    /// e.g. `"[app] {title} - {cwd}"`. Placeholders: `{title}` (set by the
+    /// program), `{cwd}` (`~` for home), `{shell}` (executable name) and
+    /// `{id}`. The program's title is used as is without a template.
+    #[serde(default)]
+    pub title_template: Option<String>,
+    /// Arbitrary app data kept with the session (tab group, project id, ...).
+    #[serde(default)]
+    pub metadata: HashMap<String, String>,
+    /// Start the shell as an interactive login shell (`-l -i` for bash and
+    /// zsh, `-l` for sh and csh, `-Login` for pwsh) so profile files set up
+    /// PATH and aliases.
+    #[serde(default)]
+    pub login_shell: bool,
+    /// Extra arguments for the shell, after any login flags.
+    #[serde(default)]
+    pub shell_args: Vec<String>,
+    /// Written by `close_session` to ask the program to exit; defaults to
+    /// `exit\n` (use `"\u{4}"` for EOF).
+    #[serde(default)]
+    pub close_sequence: Option<String>,
+    /// How long `close_session` waits for the program to exit before
+    /// killing it; defaults to 3 seconds.
+    #[serde(default)]
+    pub close_timeout_ms: Option<u64>,
+    /// How much the session logs; see `set_log_level`.
+    #[serde(default)]
+    pub log_level: LogLevel,
+}
+
+/// Give the config a generated id if it has none and set up the session's
+/// logging under that id.
+fn session_trace(config: &mut SessionConfig) -> SessionTrace {
+    let id = config.id.get_or_insert_with(|| Uuid::new_v4().to_string());
+    SessionTrace::new(id, config.log_level)
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_bell_interval_ms() -> u64 {
+    100
 }
 
 impl Default for SessionConfig {
@@ -45,13 +225,255 @@ impl Default for SessionConfig {
         Self {
             id: None,
             cwd: None,
+            cwd_fallback: false,
             shell: None,
             env: HashMap::new(),
+            term: None,
             cols: Some(80),
             rows: Some(24),
             theme: None,
+            bell_mode: BellMode::default(),
+            bell_interval_ms: default_bell_interval_ms(),
+            device_reports: DeviceReports::default(),
+            scroll_region_diffing: true,
+            a11y_contrast: None,
+            replacement_char: None,
+            color_overrides: HashMap::new(),
+            output_byte_thresholds: Vec::new(),
+            output_byte_limit: None,
+            key_bindings: Vec::new(),
+            idle_timeout_ms: None,
+            exit_retention_ms: None,
+            initial_coalesce_ms: None,
+            scroll_on_output: true,
+            scroll_on_input: true,
+            audit_input: false,
+            redact_passwords: false,
+            flow_control: false,
+            wrap_override: None,
+            line_drawing: true,
+            c1_controls: C1Mode::default(),
+            search_index: false,
+            emulate: true,
+            title_template: None,
+            metadata: HashMap::new(),
+            login_shell: false,
+            shell_args: Vec::new(),
+            close_sequence: None,
+            close_timeout_ms: None,
+            log_level: LogLevel::default(),
+        }
+    }
+}
+
+/// Check `config.cwd` is an existing directory, falling back to the home
+/// directory when `cwd_fallback` is set.
+fn resolve_cwd(config: &SessionConfig) -> Result<Option<String>> {
+    let Some(cwd) = &config.cwd else {
+        return Ok(None);
+    };
+    if std::path::Path::new(cwd).is_dir() {
+        return Ok(Some(cwd.clone()));
+    }
+
+    let home = std::env::var(if cfg!(windows) { "USERPROFILE" } else { "HOME" }).ok();
+    match home {
+        Some(home) if config.cwd_fallback => {
+            log::warn!("Working directory {} is not a directory, using {}", cwd, home);
+            Ok(Some(home))
+        }
+        _ => Err(Error::InvalidConfig(format!(
+            "Working directory does not exist or is not a directory: {}",
+            cwd
+        ))),
+    }
+}
+
+/// Shell command that sets `key` to `value` in the running shell.
+fn export_line(shell: &str, key: &str, value: &str) -> String {
+    if shell.contains("powershell") || shell.contains("pwsh") {
+        format!("$env:{} = '{}'\r", key, value.replace('\'', "''"))
+    } else if shell.contains("fish") {
+        format!("set -gx {} '{}'\r", key, value.replace('\\', "\\\\").replace('\'', "\\'"))
+    } else {
+        format!("export {}='{}'\r", key, value.replace('\'', "'\\''"))
+    }
+}
+
+/// Sessions whose process is still running; exited sessions held for
+/// `exit_retention_ms` don't count toward `max_sessions`.
+fn live_count(sessions: &HashMap<SessionId, Session>) -> usize {
+    sessions.values().filter(|s| s.is_alive()).count()
+}
+
+/// Length of `data` without a trailing UTF-8 character that's cut short.
+fn complete_utf8_len(data: &[u8]) -> usize {
+    for back in 1..=data.len().min(3) {
+        let needed = match data[data.len() - back] {
+            // Continuation byte; keep looking for the lead byte
+            0x80..=0xbf => continue,
+            0xc0..=0xdf => 2,
+            0xe0..=0xef => 3,
+            0xf0..=0xf7 => 4,
+            _ => 1,
+        };
+        return if needed > back { data.len() - back } else { data.len() };
+    }
+    data.len()
+}
+
+/// Turn `\r\n` into `\n` and drop stray carriage returns, for text headed
+/// to files and editors rather than a terminal.
+fn normalize_line_endings(text: &str) -> String {
+    text.replace("\r\n", "\n").replace('\r', "")
+}
+
+/// Replace `{name}` placeholders in `template` with `value(name)`, leaving
+/// unknown ones as written. Substituted text isn't expanded again.
+fn render_template(template: &str, value: impl Fn(&str) -> Option<String>) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(open) = rest.find('{') {
+        out.push_str(&rest[..open]);
+        let after = &rest[open + 1..];
+        match after.find('}').and_then(|close| Some((close, value(&after[..close])?))) {
+            Some((close, text)) => {
+                out.push_str(&text);
+                rest = &after[close + 1..];
+            }
+            None => {
+                out.push('{');
+                rest = after;
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// `path` with the home directory shortened to `~`.
+fn abbreviate_home(path: &str) -> String {
+    let home = std::env::var(if cfg!(windows) { "USERPROFILE" } else { "HOME" }).ok();
+    match home.as_deref().and_then(|home| path.strip_prefix(home)) {
+        Some(rest) if rest.is_empty() || rest.starts_with(std::path::is_separator) => format!("~{rest}"),
+        _ => path.to_string(),
+    }
+}
+
+/// The theme's 256-color palette with `overrides` applied.
+fn resolve_palette(theme: &Theme, overrides: &HashMap<u8, Color>) -> [Color; 256] {
+    let mut palette = theme.palette_256();
+    for (&idx, &color) in overrides {
+        palette[idx as usize] = color;
+    }
+    palette
+}
+
+/// Standard padded base64, as carried by OSC 52.
+fn base64_encode(data: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut out = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let b = [chunk[0], *chunk.get(1).unwrap_or(&0), *chunk.get(2).unwrap_or(&0)];
+        let n = ((b[0] as u32) << 16) | ((b[1] as u32) << 8) | b[2] as u32;
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(ALPHABET[(n >> (18 - 6 * i)) as usize & 63] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+/// Plain-text output kept for `expect`.
+const OUTPUT_TAIL_BYTES: usize = 16 * 1024;
+
+/// Where `OutputTail` is within an escape sequence.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+enum EscapeState {
+    #[default]
+    Ground,
+    Escape,
+    Csi,
+    /// OSC, DCS, APC, PM or SOS payload, ended by BEL or ST.
+    Str,
+    StrEscape,
+}
+
+/// Recent output with escape sequences and control characters other than
+/// newline and tab removed. Offsets count from the start of the session so
+/// they stay valid as old text is dropped.
+#[derive(Debug, Default)]
+struct OutputTail {
+    text: String,
+    /// Offset of the first byte of `text`.
+    start: u64,
+    state: EscapeState,
+    /// Offset the next `expect` searches from: the end of the last match.
+    expect_from: u64,
+}
+
+impl OutputTail {
+    fn push(&mut self, data: &[u8]) {
+        use EscapeState::*;
+        for ch in String::from_utf8_lossy(data).chars() {
+            self.state = match (self.state, ch) {
+                (Ground, '\x1b') => Escape,
+                (Ground, '\n' | '\t') => {
+                    self.text.push(ch);
+                    Ground
+                }
+                (Ground, c) if c.is_control() => Ground,
+                (Ground, c) => {
+                    self.text.push(c);
+                    Ground
+                }
+                (Escape, '[') => Csi,
+                (Escape, ']' | 'P' | '_' | '^' | 'X') => Str,
+                // Intermediate bytes, as in `ESC ( B`
+                (Escape, ' '..='/') => Escape,
+                (Escape, _) => Ground,
+                (Csi, '@'..='~') => Ground,
+                (Csi, _) => Csi,
+                (Str, '\x07') => Ground,
+                (Str, '\x1b') => StrEscape,
+                (Str, _) => Str,
+                (StrEscape, '\\') => Ground,
+                (StrEscape, _) => Str,
+            };
+        }
+
+        if self.text.len() > OUTPUT_TAIL_BYTES {
+            let mut cut = self.text.len() - OUTPUT_TAIL_BYTES;
+            while !self.text.is_char_boundary(cut) {
+                cut += 1;
+            }
+            self.text.drain(..cut);
+            self.start += cut as u64;
         }
     }
+
+    /// Text not yet consumed by a match.
+    fn unmatched(&self) -> &str {
+        let from = self.expect_from.saturating_sub(self.start) as usize;
+        &self.text[from.min(self.text.len())..]
+    }
+
+    /// Find `pattern` in the unmatched text, consuming it up to the match.
+    fn expect(&mut self, pattern: &Regex) -> Option<MatchRange> {
+        let unmatched = self.unmatched();
+        let base = self.start + (self.text.len() - unmatched.len()) as u64;
+        let found = pattern.find(unmatched)?;
+        let range = MatchRange {
+            start: base + found.start() as u64,
+            end: base + found.end() as u64,
+            text: found.as_str().to_string(),
+        };
+        self.expect_from = range.end;
+        Some(range)
+    }
 }
 
 /// Information about a session.
@@ -64,6 +486,58 @@ pub struct SessionInfo {
     pub size: Size,
     pub is_alive: bool,
     pub created_at: u64,
+    /// Logical time of the last screen update (0 if none yet).
+    pub last_update: u64,
+    pub metadata: HashMap<String, String>,
+}
+
+/// Conditions for `list_filtered`; a default filter matches every session.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SessionFilter {
+    /// Only sessions whose process is still running.
+    #[serde(default)]
+    pub alive_only: bool,
+    /// Metadata entries the session must have, all with these exact values.
+    #[serde(default)]
+    pub metadata: HashMap<String, String>,
+    /// Shell executable name (e.g. `zsh`), without its path.
+    #[serde(default)]
+    pub shell: Option<String>,
+}
+
+/// Portable copy of a session, for duplicating it elsewhere or attaching
+/// to bug reports. Importing respawns the shell from `config` in `cwd` and
+/// replays `scrollback`; `env` and `screen` are informational.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionSnapshot {
+    pub config: SessionConfig,
+    /// Full environment the shell was spawned with.
+    pub env: HashMap<String, String>,
+    pub screen: Screen,
+    /// The whole buffer (scrollback and screen) as ANSI text.
+    pub scrollback: String,
+    pub marks: Vec<Mark>,
+    /// The shell's working directory when the snapshot was taken.
+    pub cwd: Option<String>,
+    pub size: Size,
+    pub theme: String,
+}
+
+/// Point-in-time health snapshot of a session, for debugging stuck terminals.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionDiagnostics {
+    pub id: SessionId,
+    pub is_alive: bool,
+    pub pid: Option<u32>,
+    pub bytes_read: u64,
+    pub bytes_written: u64,
+    /// Screen updates emitted so far.
+    pub frames_emitted: u64,
+    /// Milliseconds since the Unix epoch of the last PTY output, if any.
+    pub last_output_at: Option<u64>,
+    pub size: Size,
+    pub alternate_screen: bool,
+    pub mark_count: usize,
 }
 
 /// A terminal session combining PTY and terminal emulator.
@@ -74,46 +548,183 @@ pub struct Session {
     theme: Theme,
     config: SessionConfig,
     event_sender: EventSender,
+    /// Verbosity and span for this session's log messages.
+    trace: SessionTrace,
     created_at: u64,
     marks: Vec<Mark>,
+    /// Total bytes read from the PTY.
+    bytes_read: u64,
+    /// Total bytes written to the PTY; `write` only takes `&self`.
+    bytes_written: AtomicU64,
+    /// Screen updates emitted.
+    frames_emitted: u64,
+    /// Time of the last PTY output, in milliseconds since the Unix epoch.
+    last_output_at: Option<u64>,
+    /// Pixel size reported to the child, independent of the cell grid.
+    pixel_size: PixelSize,
+    /// Trailing bytes of a UTF-8 character cut off by the last read.
+    utf8_carry: Vec<u8>,
+    /// Recent plain-text output, searched by `expect`.
+    output_tail: OutputTail,
+    /// Last working directory reported through OSC 7, with the time (ms
+    /// since the Unix epoch) it was reported.
+    reported_cwd: Option<(String, u64)>,
+    /// Fraction of the grid changed at once above which a full
+    /// `ScreenRefresh` is emitted instead of a `ScreenUpdate`.
+    full_refresh_ratio: f32,
+    /// Cursor color set by the app or OSC 12, overriding the theme's.
+    cursor_color: Option<Color>,
+    /// The theme's resolved 256-color palette, rebuilt when the theme changes.
+    palette: Box<[Color; 256]>,
+    /// When the PTY was first seen closed.
+    dead_since: Option<Instant>,
+    /// When `ProcessExit` was emitted.
+    exited_at: Option<Instant>,
+    /// Last input or output, for the idle timeout; `write` only takes `&self`.
+    last_activity: Mutex<Instant>,
+    /// End of the startup window whose output is merged into one update.
+    coalesce_until: Option<Instant>,
+    /// Why the plugin killed the process, if it did.
+    kill_reason: Option<ExitReason>,
+    /// `UPDATE_CLOCK` value of the last emitted screen update.
+    last_update: u64,
+    /// The last emitted screen update, kept for `replay_last_update`.
+    last_emitted: Option<ScreenUpdate>,
+    /// The screen sent as a `ScreenRefresh` in place of `last_emitted`.
+    last_refresh: Option<Screen>,
+    /// Input was written since the last screen update.
+    input_since_update: AtomicBool,
+    /// Output reading is stopped by XOFF; `write` only takes `&self`.
+    flow_stopped: AtomicBool,
+    /// Exports and snapshot restores in progress; output is held while any
+    /// run so the buffer doesn't move under them.
+    output_holds: AtomicUsize,
+    /// Title last announced in `TitleChange`, after applying the template.
+    title: String,
+    /// When the last bell event was emitted, for `bell_interval_ms`.
+    last_bell: Option<Instant>,
+    /// Buffer line at the top of the scrolled-back viewport; `None` while
+    /// following the bottom.
+    viewport_top: Option<usize>,
 }
 
 impl Session {
     /// Create a new session.
     pub fn new(config: SessionConfig, event_sender: EventSender) -> Result<Self> {
-        let id = config.id.clone().unwrap_or_else(|| Uuid::new_v4().to_string());
+        Self::spawn(config, event_sender, None)
+    }
+
+    /// Create a new session whose child notifies `exit_notify` on exit.
+    pub(crate) fn spawn(
+        mut config: SessionConfig,
+        event_sender: EventSender,
+        exit_notify: Option<Arc<Notify>>,
+    ) -> Result<Self> {
         let cols = config.cols.unwrap_or(80);
         let rows = config.rows.unwrap_or(24);
 
-        let terminal = Terminal::new(cols, rows);
+        // Record the directory actually used so `info` reflects any fallback
+        config.cwd = resolve_cwd(&config)?;
+
+        let trace = session_trace(&mut config);
         let pty = Pty::spawn(PtyConfig {
             cwd: config.cwd.clone(),
             shell: config.shell.clone(),
             env: config.env.iter().map(|(k, v)| (k.clone(), v.clone())).collect(),
             size: Size { cols, rows },
+            term: config.term.clone(),
+            login: config.login_shell,
+            args: config.shell_args.clone(),
+            trace: trace.clone(),
+            exit_notify,
         })?;
 
-        let theme = config
+        Ok(Self::with_pty(config, event_sender, pty, trace))
+    }
+
+    /// Create a session driven by an externally opened PTY instead of a
+    /// spawned shell. The grid size comes from `config.cols`/`config.rows`.
+    pub fn attach(mut config: SessionConfig, event_sender: EventSender, external: ExternalPty) -> Self {
+        let trace = session_trace(&mut config);
+        let pty = Pty::attach(external, trace.clone());
+        Self::with_pty(config, event_sender, pty, trace)
+    }
+
+    fn with_pty(config: SessionConfig, event_sender: EventSender, pty: Pty, trace: SessionTrace) -> Self {
+        // Always set by `session_trace`
+        let id = config.id.clone().unwrap_or_default();
+        let cols = config.cols.unwrap_or(80);
+        let rows = config.rows.unwrap_or(24);
+
+        let mut terminal = Terminal::new(cols, rows);
+        terminal.set_device_reports(config.device_reports.clone());
+        terminal.set_scroll_region_diffing(config.scroll_region_diffing);
+        terminal.set_min_contrast(config.a11y_contrast);
+        terminal.set_color_overrides(config.color_overrides.clone());
+        if let Some(replacement) = config.replacement_char {
+            terminal.set_replacement_char(replacement);
+        }
+        terminal.set_wrap_override(config.wrap_override);
+        terminal.set_c1_mode(config.c1_controls);
+        terminal.set_line_drawing(config.line_drawing);
+        terminal.set_search_index(config.search_index);
+
+        let mut theme = config
             .theme
             .as_ref()
             .and_then(|n| Theme::by_name(n))
             .unwrap_or_default();
+        if let Some(ratio) = config.a11y_contrast {
+            theme = theme.ensure_contrast(ratio);
+        }
 
         let created_at = std::time::SystemTime::now()
             .duration_since(std::time::UNIX_EPOCH)
             .map(|d| d.as_secs())
             .unwrap_or(0);
 
-        Ok(Self {
+        let coalesce_until = config
+            .initial_coalesce_ms
+            .map(|ms| Instant::now() + Duration::from_millis(ms));
+
+        let mut session = Self {
             id,
             terminal,
             pty,
+            utf8_carry: Vec::new(),
+            reported_cwd: None,
+            full_refresh_ratio: DEFAULT_FULL_REFRESH_RATIO,
+            cursor_color: None,
+            palette: Box::new(resolve_palette(&theme, &config.color_overrides)),
             theme,
             config,
             event_sender,
+            trace,
             created_at,
             marks: Vec::new(),
-        })
+            bytes_read: 0,
+            bytes_written: AtomicU64::new(0),
+            frames_emitted: 0,
+            last_output_at: None,
+            pixel_size: PixelSize::default(),
+            output_tail: OutputTail::default(),
+            dead_since: None,
+            exited_at: None,
+            last_activity: Mutex::new(Instant::now()),
+            coalesce_until,
+            kill_reason: None,
+            last_update: 0,
+            last_emitted: None,
+            last_refresh: None,
+            input_since_update: AtomicBool::new(false),
+            flow_stopped: AtomicBool::new(false),
+            output_holds: AtomicUsize::new(0),
+            title: String::new(),
+            last_bell: None,
+            viewport_top: None,
+        };
+        session.title = session.compose_title();
+        session
     }
 
     /// Get session info.
@@ -122,22 +733,195 @@ impl Session {
             id: self.id.clone(),
             cwd: self.config.cwd.clone(),
             shell: self.config.shell.clone(),
-            title: self.terminal.title().to_string(),
+            title: self.title.clone(),
             size: self.terminal.size(),
             is_alive: self.pty.is_alive(),
             created_at: self.created_at,
+            last_update: self.last_update,
+            metadata: self.config.metadata.clone(),
         }
     }
 
+    /// Ask the program to exit by writing the close sequence, returning how
+    /// long to wait for it before killing the session.
+    pub fn request_close(&self) -> Result<Duration> {
+        let sequence = self.config.close_sequence.as_deref().unwrap_or(DEFAULT_CLOSE_SEQUENCE);
+        self.write(sequence.as_bytes())?;
+        Ok(self
+            .config
+            .close_timeout_ms
+            .map_or(DEFAULT_CLOSE_TIMEOUT, Duration::from_millis))
+    }
+
+    /// App metadata attached to the session.
+    pub fn metadata(&self) -> &HashMap<String, String> {
+        &self.config.metadata
+    }
+
+    /// Set a metadata entry, or remove it with `None`.
+    pub fn set_metadata(&mut self, key: String, value: Option<String>) {
+        match value {
+            Some(value) => self.config.metadata.insert(key, value),
+            None => self.config.metadata.remove(&key),
+        };
+    }
+
     /// Write data to the session's PTY.
     pub fn write(&self, data: &[u8]) -> Result<()> {
-        self.pty.write(data)
+        let filtered;
+        let data = if self.config.flow_control {
+            filtered = self.apply_flow_control(data);
+            if filtered.is_empty() {
+                return Ok(());
+            }
+            &filtered[..]
+        } else {
+            data
+        };
+
+        self.pty.write(data)?;
+        self.bytes_written.fetch_add(data.len() as u64, Ordering::Relaxed);
+        *self.last_activity.lock() = Instant::now();
+        self.input_since_update.store(true, Ordering::Relaxed);
+        if self.config.audit_input {
+            self.audit_input(data);
+        }
+        Ok(())
+    }
+
+    /// Act on and remove XOFF/XON bytes in input, returning the rest.
+    fn apply_flow_control(&self, data: &[u8]) -> Vec<u8> {
+        let mut rest = Vec::with_capacity(data.len());
+        for &byte in data {
+            match byte {
+                0x13 => self.flow_stopped.store(true, Ordering::Relaxed),
+                0x11 => self.flow_stopped.store(false, Ordering::Relaxed),
+                _ => rest.push(byte),
+            }
+        }
+        rest
+    }
+
+    /// Turn software flow control on or off. Turning it off resumes output
+    /// stopped by XOFF.
+    pub fn set_flow_control(&mut self, enabled: bool) {
+        self.config.flow_control = enabled;
+        if !enabled {
+            *self.flow_stopped.get_mut() = false;
+        }
+    }
+
+    /// Emit an `InputAudit` event for bytes just written.
+    fn audit_input(&self, data: &[u8]) {
+        let redacted = self.config.redact_passwords && self.pty.echo_enabled() == Some(false);
+        let timestamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or(0);
+
+        let _ = self.event_sender.send(TerminalEvent::InputAudit {
+            session_id: self.id.clone(),
+            timestamp,
+            data: if redacted { String::new() } else { base64_encode(data) },
+            len: data.len(),
+            redacted,
+        });
+    }
+
+    /// Type a line into the shell and submit it, wrapped in bracketed paste
+    /// markers when the application has asked for them.
+    pub fn submit_line(&self, line: &str) -> Result<()> {
+        let mut data = Vec::with_capacity(line.len() + 13);
+        if self.terminal.bracketed_paste() {
+            data.extend_from_slice(b"\x1b[200~");
+            data.extend_from_slice(line.as_bytes());
+            data.extend_from_slice(b"\x1b[201~");
+        } else {
+            data.extend_from_slice(line.as_bytes());
+        }
+        data.push(b'\r');
+        self.write(&data)
+    }
+
+    /// Set an environment variable for future children of this session.
+    ///
+    /// With `apply_now`, also exports it in the running shell by typing an
+    /// `export` line, which only makes sense for spawned shells.
+    pub fn set_env(&mut self, key: &str, value: &str, apply_now: bool) -> Result<()> {
+        let valid_key = key.chars().next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+            && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+        if !valid_key {
+            return Err(Error::InvalidConfig(format!("Invalid environment variable name: {}", key)));
+        }
+        if apply_now && !self.pty.has_child() {
+            return Err(Error::InvalidConfig(
+                "Cannot apply environment changes to a session without a local shell".to_string(),
+            ));
+        }
+
+        self.config.env.insert(key.to_string(), value.to_string());
+
+        if apply_now {
+            let shell = self.config.shell.clone().or_else(|| std::env::var("SHELL").ok());
+            let line = export_line(shell.as_deref().unwrap_or(""), key, value);
+            self.write(line.as_bytes())?;
+        }
+        Ok(())
+    }
+
+    /// Environment the shell was spawned with; empty for attached PTYs.
+    /// Changes from `set_env` aren't included.
+    pub fn spawn_env(&self) -> HashMap<String, String> {
+        self.pty.spawn_spec().map(|spec| spec.env.clone()).unwrap_or_default()
+    }
+
+    /// The program, arguments, directory and environment the shell was
+    /// spawned with, to relaunch it elsewhere; `None` for attached PTYs.
+    pub fn export_spawn_command(&self) -> Option<SpawnSpec> {
+        self.pty.spawn_spec().cloned()
+    }
+
+    /// Send a key press, unless it matches a key binding, in which case a
+    /// `Binding` event is emitted instead.
+    pub fn send_key(&self, event: &KeyEvent) -> Result<()> {
+        if let Some(binding) = self.config.key_bindings.iter().find(|b| b.matches(event)) {
+            // The press already triggered it
+            if event.event_type != KeyEventType::Release {
+                let _ = self.event_sender.send(TerminalEvent::Binding {
+                    session_id: self.id.clone(),
+                    action: binding.action.clone(),
+                });
+            }
+            return Ok(());
+        }
+
+        match encode_key(event, self.terminal.key_modes()) {
+            Some(bytes) => self.write(&bytes),
+            None => Ok(()),
+        }
+    }
+
+    /// Gather counters and state for troubleshooting.
+    pub fn diagnostics(&self) -> SessionDiagnostics {
+        SessionDiagnostics {
+            id: self.id.clone(),
+            is_alive: self.pty.is_alive(),
+            pid: self.pty.pid(),
+            bytes_read: self.bytes_read,
+            bytes_written: self.bytes_written.load(Ordering::Relaxed),
+            frames_emitted: self.frames_emitted,
+            last_output_at: self.last_output_at,
+            size: self.terminal.size(),
+            alternate_screen: self.is_alt_screen(),
+            mark_count: self.marks.len(),
+        }
     }
 
     /// Resize the session.
     pub fn resize(&mut self, cols: u16, rows: u16) -> Result<()> {
+        // Resize the PTY first so a dead session keeps its grid
+        self.pty.resize(cols, rows, self.pixel_size)?;
         self.terminal.resize(cols, rows);
-        self.pty.resize(cols, rows)?;
 
         // Emit resize event to notify frontend
         let _ = self.event_sender.send(TerminalEvent::TerminalResized {
@@ -149,186 +933,1821 @@ impl Session {
         Ok(())
     }
 
+    /// Get the pixel size reported to the child.
+    pub fn pixel_size(&self) -> PixelSize {
+        self.pixel_size
+    }
+
+    /// Set the pixel size reported to the child without changing the grid.
+    pub fn set_pixel_size(&mut self, pixels: PixelSize) -> Result<()> {
+        self.pixel_size = pixels;
+        self.terminal.set_pixel_size(pixels);
+        let size = self.terminal.size();
+        self.pty.resize(size.cols, size.rows, pixels)
+    }
+
     /// Get the full screen state.
     pub fn get_screen(&self) -> Screen {
-        self.terminal.get_screen()
+        let mut screen = self.terminal.get_screen();
+        screen.cursor.color = self.cursor_color;
+        screen.title = self.title.clone();
+        screen
     }
 
-    /// Get cursor state.
-    pub fn get_cursor(&self) -> Cursor {
-        self.terminal.get_cursor()
+    /// Export a range of buffer lines as ANSI-colored text, with `\r\n`
+    /// line endings unless `normalize_newlines` is set.
+    pub fn export_ansi_range(&self, start_line: usize, count: usize, normalize_newlines: bool) -> String {
+        let text = self.terminal.dump_ansi_range(start_line, count);
+        if normalize_newlines {
+            normalize_line_endings(&text)
+        } else {
+            text
+        }
     }
 
-    /// Get the theme.
-    pub fn theme(&self) -> &Theme {
-        &self.theme
+    /// Fingerprint of the visible grid.
+    pub fn screen_hash(&self) -> u64 {
+        self.terminal.screen_hash()
     }
 
-    /// Set the theme.
-    pub fn set_theme(&mut self, theme: Theme) {
-        self.theme = theme;
+    /// Get a single visible cell.
+    pub fn get_cell(&self, row: u16, col: u16) -> Cell {
+        self.terminal.get_cell(row, col)
     }
 
-    /// Check if session is alive.
-    pub fn is_alive(&self) -> bool {
-        self.pty.is_alive()
+    /// A rectangle of visible cells; see `Terminal::region`.
+    pub fn region(&self, top: u16, left: u16, bottom: u16, right: u16) -> Vec<Row> {
+        self.terminal.region(top, left, bottom, right)
     }
 
-    /// Process any available PTY output.
-    /// Returns changes if any processing occurred.
-    pub fn process_output(&mut self) -> Option<ScreenUpdate> {
-        if let Some(data) = self.pty.try_read() {
-            let changes = self.terminal.process(&data);
+    /// Placements of the inline images in the buffer.
+    pub fn images(&self) -> Vec<ImagePlacement> {
+        self.terminal.images()
+    }
 
-            if !changes.is_empty() {
-                let update = ScreenUpdate {
-                    session_id: self.id.clone(),
-                    changes,
-                    cursor: self.terminal.get_cursor(),
-                    title: Some(self.terminal.title().to_string()),
-                };
+    /// Base64 payload of an inline image, if it's still retained.
+    pub fn image_data(&self, id: u32) -> Option<String> {
+        self.terminal.image_data(id)
+    }
 
-                // Emit event
-                let _ = self.event_sender.send(TerminalEvent::ScreenUpdate(update.clone()));
+    /// Approximate memory held by the session's buffers.
+    pub fn buffer_stats(&self) -> BufferStats {
+        self.terminal.buffer_stats()
+    }
 
-                // Check for bell
-                if self.terminal.check_bell() {
-                    let _ = self.event_sender.send(TerminalEvent::Bell {
-                        session_id: self.id.clone(),
-                    });
-                }
+    /// Visible screen as plain text.
+    pub fn screen_text(&self) -> String {
+        self.terminal.screen_text()
+    }
 
-                return Some(update);
-            }
-        }
+    /// Export the buffer as plain text.
+    pub fn export_text(&self, preserve_wrapping: bool) -> String {
+        self.terminal.export_text(preserve_wrapping)
+    }
 
-        None
+    /// Number of lines in the buffer, scrollback included.
+    pub fn buffer_lines(&self) -> usize {
+        self.terminal.buffer_lines()
     }
 
-    /// Get marks.
-    pub fn marks(&self) -> &[Mark] {
-        &self.marks
+    /// Export `count` buffer lines from `start_line` in `format`.
+    pub fn export_page(&self, format: ExportFormat, start_line: usize, count: usize, preserve_wrapping: bool) -> String {
+        match format {
+            ExportFormat::Text => self.terminal.text_range(start_line, count, preserve_wrapping),
+            ExportFormat::Ansi => self.terminal.dump_ansi_range(start_line, count),
+        }
     }
 
-    /// Add a mark.
-    pub fn add_mark(&mut self, mark: Mark) {
-        self.marks.push(mark.clone());
-        let _ = self.event_sender.send(TerminalEvent::Mark {
-            session_id: self.id.clone(),
-            mark,
-        });
+    /// Get cursor state, including any cursor color override.
+    pub fn get_cursor(&self) -> Cursor {
+        Cursor {
+            color: self.cursor_color,
+            ..self.terminal.get_cursor()
+        }
     }
 
-    /// Kill the session.
-    pub fn kill(&self) {
-        self.pty.kill();
+    /// Supported features and the program's current modes.
+    pub fn capabilities(&self) -> Capabilities {
+        let (mouse_mode, mouse_encoding) = self.terminal.mouse_mode();
+        let term = self
+            .config
+            .env
+            .get("TERM")
+            .or(self.config.term.as_ref())
+            .map_or(DEFAULT_TERM, String::as_str);
+        Capabilities {
+            inline_images: true,
+            sixel: false,
+            clipboard_write: true,
+            truecolor: true,
+            shell_integration: self.marks.iter().any(|m| m.mark_type == MarkType::PromptStart),
+            scrollback_lines: SCROLLBACK_LINES,
+            term: term.to_string(),
+            mouse_mode,
+            mouse_encoding,
+            alt_screen: self.is_alt_screen(),
+            bracketed_paste: self.terminal.bracketed_paste(),
+            application_cursor: self.terminal.key_modes().application_cursor,
+            insert_mode: self.is_insert_mode(),
+        }
     }
-}
 
-/// Manages all terminal sessions.
-pub struct SessionManager {
-    sessions: Arc<RwLock<HashMap<SessionId, Session>>>,
-    event_sender: EventSender,
-}
+    /// Whether the alternate screen is active.
+    pub fn is_alt_screen(&self) -> bool {
+        self.terminal.alternate_screen()
+    }
 
-impl SessionManager {
-    /// Create a new session manager.
-    pub fn new(event_sender: EventSender) -> Self {
-        Self {
-            sessions: Arc::new(RwLock::new(HashMap::new())),
-            event_sender,
-        }
+    /// Whether the program has turned on insert mode (IRM).
+    pub fn is_insert_mode(&self) -> bool {
+        self.terminal.insert_mode()
     }
 
-    /// Create a new session.
-    pub fn create(&self, config: SessionConfig) -> Result<SessionId> {
-        let id = config.id.clone().unwrap_or_else(|| Uuid::new_v4().to_string());
+    /// Columns with a tab stop set.
+    pub fn tab_stops(&self) -> Vec<u16> {
+        self.terminal.tab_stops()
+    }
 
-        // Check if session already exists
-        {
-            let sessions = self.sessions.read();
-            if sessions.contains_key(&id) {
-                return Err(Error::SessionAlreadyExists(id));
-            }
+    /// Get the theme, with any cursor color override applied.
+    pub fn theme(&self) -> Theme {
+        let mut theme = self.theme.clone();
+        if let Some(color) = self.cursor_color {
+            theme.cursor = color;
+            theme.cursor_text = theme.cursor_text.ensure_contrast(&color, CURSOR_TEXT_CONTRAST);
         }
+        theme
+    }
 
-        let mut config = config;
-        config.id = Some(id.clone());
-
-        let session = Session::new(config, self.event_sender.clone())?;
+    /// Override the cursor color independently of the theme; `None` goes
+    /// back to the theme's cursor. Emits a `ThemeChange`.
+    pub fn set_cursor_color(&mut self, color: Option<Color>) {
+        self.cursor_color = color;
+        self.emit_theme_change();
+    }
 
-        {
+    /// Set the theme, applying the session's contrast floor if any.
+    pub fn set_theme(&mut self, theme: Theme) {
+        self.theme = match self.config.a11y_contrast {
+            Some(ratio) => theme.ensure_contrast(ratio),
+            None => theme,
+        };
+        *self.palette = resolve_palette(&self.theme, &self.config.color_overrides);
+    }
+
+    /// Replace indexed color `index` with `color` on top of the theme, or
+    /// drop the override when `None`. Emits a `ScreenRefresh`.
+    pub fn set_color_override(&mut self, index: u8, color: Option<Color>) {
+        match color {
+            Some(color) => self.config.color_overrides.insert(index, color),
+            None => self.config.color_overrides.remove(&index),
+        };
+        self.terminal.set_color_overrides(self.config.color_overrides.clone());
+        *self.palette = resolve_palette(&self.theme, &self.config.color_overrides);
+        let _ = self.event_sender.send(TerminalEvent::ScreenRefresh {
+            session_id: self.id.clone(),
+            screen: self.get_screen(),
+        });
+    }
+
+    /// The theme's resolved 256-color palette, with color overrides applied.
+    pub fn palette(&self) -> &[Color; 256] {
+        &self.palette
+    }
+
+    /// Logical time of the last screen update (0 if none yet).
+    pub fn last_update(&self) -> u64 {
+        self.last_update
+    }
+
+    /// Check if session is alive.
+    pub fn is_alive(&self) -> bool {
+        self.pty.is_alive()
+    }
+
+    /// Emit `ProcessExit` once the child has exited, with its exit code when
+    /// it can be reaped. Returns whether the exit has been reported.
+    pub fn poll_exit(&mut self) -> bool {
+        if self.exited_at.is_some() {
+            return true;
+        }
+        if self.pty.is_alive() {
+            return false;
+        }
+
+        // The PTY can close a moment before the child is reapable
+        let status = self.pty.exit_status();
+        let dead_since = *self.dead_since.get_or_insert_with(Instant::now);
+        if status.is_none() && self.pty.has_child() && dead_since.elapsed() < EXIT_STATUS_GRACE {
+            return false;
+        }
+
+        // An export is paging through the buffer; drain once it finishes
+        if *self.output_holds.get_mut() > 0 {
+            return false;
+        }
+
+        // Output written just before exit may still be queued behind the
+        // EOF; show it before the exit is reported and the session reaped.
+        // Nothing more is coming, so a cut-short character goes out as is
+        self.drain(false);
+
+        let signal_name = status.as_ref().and_then(exit_signal);
+        let signal = signal_name.as_deref().and_then(signal_number);
+        let exit_code = match &status {
+            Some(s) if signal_name.is_none() => Some(s.exit_code() as i32),
+            _ => None,
+        };
+        let reason = match (self.kill_reason, &status) {
+            (Some(reason), _) => reason,
+            (None, Some(_)) if signal_name.is_some() => ExitReason::Signal,
+            (None, Some(_)) => ExitReason::Normal,
+            (None, None) if self.pty.read_failed() && self.pty.has_child() => ExitReason::ReaderError,
+            (None, None) => ExitReason::Normal,
+        };
+
+        self.report_exit(exit_code, signal, reason);
+        true
+    }
+
+    /// Emit `ProcessExit` and remember that it was sent.
+    fn report_exit(&mut self, exit_code: Option<i32>, signal: Option<i32>, reason: ExitReason) {
+        let _ = self.event_sender.send(TerminalEvent::ProcessExit {
+            session_id: self.id.clone(),
+            exit_code,
+            signal,
+            reason,
+        });
+        self.exited_at = Some(Instant::now());
+    }
+
+    /// Whether the session exited longer ago than its `exit_retention_ms`.
+    fn retention_expired(&self) -> bool {
+        match (self.exited_at, self.config.exit_retention_ms) {
+            (Some(at), Some(ms)) => at.elapsed() >= Duration::from_millis(ms),
+            _ => false,
+        }
+    }
+
+    /// Whether the session has gone longer than its idle timeout without
+    /// input or output.
+    pub fn is_idle(&self) -> bool {
+        self.config.idle_timeout_ms.is_some_and(|ms| {
+            self.last_activity.lock().elapsed() >= Duration::from_millis(ms)
+        })
+    }
+
+    /// Find `pattern` in output received since the last match, consuming
+    /// the output up to the end of this one. Escape sequences are stripped
+    /// before matching.
+    pub fn expect(&mut self, pattern: &Regex) -> Option<MatchRange> {
+        self.output_tail.expect(pattern)
+    }
+
+    /// Up to `max` bytes of the output not consumed by `expect`.
+    pub fn unmatched_output(&self, max: usize) -> String {
+        let text = self.output_tail.unmatched();
+        let mut from = text.len().saturating_sub(max);
+        while !text.is_char_boundary(from) {
+            from += 1;
+        }
+        text[from..].to_string()
+    }
+
+    /// Time since the last input or output.
+    pub fn idle_for(&self) -> Duration {
+        self.last_activity.lock().elapsed()
+    }
+
+    /// Whether a job other than the shell is in the foreground; `None` if
+    /// unknown.
+    pub fn foreground_job_running(&self) -> Option<bool> {
+        self.pty.foreground_job_running()
+    }
+
+    /// Whether PTY output is waiting to be processed. Doesn't consume it.
+    pub fn has_pending_output(&self) -> bool {
+        self.pty.has_pending_output()
+    }
+
+    /// Process any available PTY output.
+    /// Returns changes if any processing occurred.
+    pub fn process_output(&mut self) -> Option<ScreenUpdate> {
+        // Stopped by XOFF or held for an export: leave output in the PTY
+        // so the program blocks
+        if *self.flow_stopped.get_mut() || *self.output_holds.get_mut() > 0 {
+            return None;
+        }
+        // Let startup output pile up, then process it in one go
+        if let Some(until) = self.coalesce_until {
+            if Instant::now() < until {
+                return None;
+            }
+            self.coalesce_until = None;
+            return self.drain_output();
+        }
+        let mut data = self.pty.try_read()?;
+        if !self.utf8_carry.is_empty() {
+            data.splice(0..0, std::mem::take(&mut self.utf8_carry));
+        }
+
+        // Hold back a character split across reads until the rest arrives
+        self.utf8_carry = data.split_off(complete_utf8_len(&data));
+        if data.is_empty() {
+            return None;
+        }
+        self.ingest(&data)
+    }
+
+    /// Process all queued PTY output as a single update, holding back a
+    /// character cut short at the end as `process_output` does.
+    pub fn drain_output(&mut self) -> Option<ScreenUpdate> {
+        self.drain(true)
+    }
+
+    /// Process all queued PTY output, after any partial character held
+    /// back from the last read, optionally holding back a new one.
+    fn drain(&mut self, hold_partial: bool) -> Option<ScreenUpdate> {
+        let mut data = std::mem::take(&mut self.utf8_carry);
+        while let Some(chunk) = self.pty.try_read() {
+            data.extend_from_slice(&chunk);
+        }
+        if hold_partial {
+            self.utf8_carry = data.split_off(complete_utf8_len(&data));
+        }
+        if data.is_empty() {
+            return None;
+        }
+        self.ingest(&data)
+    }
+
+    /// Account for and process bytes read from the PTY.
+    fn ingest(&mut self, data: &[u8]) -> Option<ScreenUpdate> {
+        let trace = self.trace.clone();
+        let _entered = trace.enter();
+        session_log!(trace, Trace, "Processing {} bytes", data.len());
+        if self.bytes_read == 0 {
+            // The frontend may have mounted before any output; send it everything
+            self.terminal.invalidate();
+        }
+        *self.last_activity.get_mut() = Instant::now();
+        self.last_output_at = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .ok()
+            .map(|d| d.as_millis() as u64);
+        self.count_output(data.len() as u64);
+        self.output_tail.push(data);
+
+        if !self.config.emulate {
+            let _ = self.event_sender.send(TerminalEvent::RawOutput {
+                session_id: self.id.clone(),
+                data: base64_encode(data),
+            });
+            return None;
+        }
+        self.process_bytes(data)
+    }
+
+    /// Total bytes read from the PTY so far.
+    pub fn bytes_read(&self) -> u64 {
+        self.bytes_read
+    }
+
+    /// Add to the output counter, emitting threshold events and enforcing the hard limit.
+    fn count_output(&mut self, len: u64) {
+        let before = self.bytes_read;
+        self.bytes_read = before.saturating_add(len);
+        let after = self.bytes_read;
+
+        let crossed = self
+            .config
+            .output_byte_thresholds
+            .iter()
+            .any(|&t| before < t && t <= after);
+        let over_limit = self
+            .config
+            .output_byte_limit
+            .is_some_and(|limit| before <= limit && limit < after);
+
+        if crossed || over_limit {
+            let _ = self.event_sender.send(TerminalEvent::OutputThreshold {
+                session_id: self.id.clone(),
+                bytes: after,
+            });
+        }
+        if over_limit {
+            session_log!(self.trace, Warn, "Session {} exceeded its output limit ({} bytes), killing", self.id, after);
+            self.kill_with_reason(ExitReason::OutputLimit);
+        }
+    }
+
+    /// Run bytes through the emulator as if they were read from the PTY.
+    pub(crate) fn process_bytes(&mut self, data: &[u8]) -> Option<ScreenUpdate> {
+        let changes = self.terminal.process(data);
+        let scroll = self.terminal.take_scroll_shift();
+        for mark in self.terminal.take_marks() {
+            self.add_mark(mark);
+        }
+        for content in self.terminal.take_clipboard_writes() {
+            self.emit_clipboard(content);
+        }
+        for cwd in self.terminal.take_cwd_reports() {
+            self.report_cwd(cwd);
+        }
+        self.update_title();
+        self.apply_color_requests();
+        self.write_responses();
+
+        // A bare BEL doesn't change the screen but still needs routing
+        if changes.is_empty() && scroll.is_none() {
+            self.emit_bell();
+            return None;
+        }
+
+        let cursor = self.get_cursor();
+        let should_autoscroll = self.should_autoscroll(&cursor);
+        if should_autoscroll {
+            self.viewport_top = None;
+        }
+        let update = ScreenUpdate {
+            session_id: self.id.clone(),
+            changes,
+            cursor,
+            title: Some(self.title.clone()),
+            scroll,
+            should_autoscroll,
+        };
+
+        // Past the threshold the full screen is cheaper than the cell changes
+        let cells = self.terminal.size().cols as f32 * self.terminal.size().rows as f32;
+        self.last_refresh = (update.changes.len() as f32 > cells * self.full_refresh_ratio)
+            .then(|| self.get_screen());
+        let event = match &self.last_refresh {
+            Some(screen) => TerminalEvent::ScreenRefresh {
+                session_id: self.id.clone(),
+                screen: screen.clone(),
+            },
+            None => TerminalEvent::ScreenUpdate(update.clone()),
+        };
+        session_log!(self.trace, Trace, "Emitting {} with {} changes", event.event_name(), update.changes.len());
+        let _ = self.event_sender.send(event);
+        self.frames_emitted += 1;
+        self.last_update = UPDATE_CLOCK.fetch_add(1, Ordering::Relaxed) + 1;
+        self.last_emitted = Some(update.clone());
+
+        self.emit_bell();
+        Some(update)
+    }
+
+    /// Emit the last screen update again, for a frontend that missed it.
+    /// A frame that went out as a `ScreenRefresh` is replayed as one.
+    pub fn replay_last_update(&self) -> Option<ScreenUpdate> {
+        let update = self.last_emitted.clone()?;
+        let event = match &self.last_refresh {
+            Some(screen) => TerminalEvent::ScreenRefresh {
+                session_id: self.id.clone(),
+                screen: screen.clone(),
+            },
+            None => TerminalEvent::ScreenUpdate(update.clone()),
+        };
+        let _ = self.event_sender.send(event);
+        Some(update)
+    }
+
+    /// Write replies to device queries back to the program.
+    fn write_responses(&mut self) {
+        let responses = self.terminal.take_responses();
+        if responses.is_empty() {
+            return;
+        }
+        if let Err(e) = self.pty.write(&responses) {
+            session_log!(self.trace, Warn, "Failed to answer device query for {}: {}", self.id, e);
+        }
+    }
+
+    /// Apply OSC 10/11/12 color sets and answer color queries.
+    fn apply_color_requests(&mut self) {
+        let mut changed = false;
+
+        for request in self.terminal.take_color_requests() {
+            match request {
+                // OSC 12 goes through the same override as `set_cursor_color`
+                ColorRequest::Set(DynamicColor::Cursor, color) => {
+                    self.cursor_color = Some(color);
+                    changed = true;
+                }
+                ColorRequest::Reset(DynamicColor::Cursor) => {
+                    self.cursor_color = None;
+                    changed = true;
+                }
+                ColorRequest::Set(slot, color) => {
+                    self.theme.set_dynamic_color(slot, color);
+                    changed = true;
+                }
+                ColorRequest::Reset(slot) => {
+                    let base = Theme::by_name(&self.theme.name).unwrap_or_default();
+                    self.theme.set_dynamic_color(slot, base.dynamic_color(slot));
+                    changed = true;
+                }
+                ColorRequest::Query { slot, bel } => {
+                    let reply = format!(
+                        "\x1b]{};{}{}",
+                        slot.osc_code(),
+                        self.theme().dynamic_color(slot).to_x11(),
+                        if bel { "\x07" } else { "\x1b\\" }
+                    );
+                    if let Err(e) = self.pty.write(reply.as_bytes()) {
+                        session_log!(self.trace, Warn, "Failed to answer color query for {}: {}", self.id, e);
+                    }
+                }
+            }
+        }
+
+        if changed {
+            self.emit_theme_change();
+        }
+    }
+
+    fn emit_theme_change(&self) {
+        let _ = self.event_sender.send(TerminalEvent::ThemeChange {
+            session_id: self.id.clone(),
+            theme: self.theme(),
+        });
+    }
+
+    /// Copy the text between two screen positions through the same
+    /// `ClipboardRequest` path an OSC 52 write takes. Returns the text.
+    pub fn copy_selection(&self, start: CursorPosition, end: CursorPosition, rectangular: bool) -> String {
+        let text = self.terminal.selection_text(start, end, rectangular);
+        self.emit_clipboard(base64_encode(text.as_bytes()));
+        text
+    }
+
+    /// Record an OSC 7 working directory, emitting `DirectoryChange` when it moved.
+    fn report_cwd(&mut self, cwd: String) {
+        let timestamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or(0);
+        let changed = self.reported_cwd.as_ref().is_none_or(|(prev, _)| *prev != cwd);
+        if changed {
+            let _ = self.event_sender.send(TerminalEvent::DirectoryChange {
+                session_id: self.id.clone(),
+                cwd: cwd.clone(),
+            });
+        }
+        self.reported_cwd = Some((cwd, timestamp));
+    }
+
+    /// The title to show: the program's title passed through the session's
+    /// title template, if it has one.
+    fn compose_title(&self) -> String {
+        let Some(template) = &self.config.title_template else {
+            return self.terminal.title().to_string();
+        };
+        render_template(template, |name| match name {
+            "title" => Some(self.terminal.title().to_string()),
+            "cwd" => Some(
+                self.reported_cwd
+                    .as_ref()
+                    .map(|(cwd, _)| cwd.as_str())
+                    .or(self.config.cwd.as_deref())
+                    .map(abbreviate_home)
+                    .unwrap_or_default(),
+            ),
+            "shell" => self.shell_name(),
+            "id" => Some(self.id.clone()),
+            _ => None,
+        })
+    }
+
+    /// Executable name of the session's shell, e.g. `zsh`.
+    fn shell_name(&self) -> Option<String> {
+        let shell = self.config.shell.clone().or_else(|| std::env::var("SHELL").ok())?;
+        let name = std::path::Path::new(&shell).file_stem()?;
+        Some(name.to_string_lossy().into_owned())
+    }
+
+    /// Whether the session passes every condition of `filter`.
+    pub fn matches(&self, filter: &SessionFilter) -> bool {
+        (!filter.alive_only || self.pty.is_alive())
+            && filter
+                .metadata
+                .iter()
+                .all(|(key, value)| self.config.metadata.get(key) == Some(value))
+            && filter
+                .shell
+                .as_ref()
+                .is_none_or(|shell| self.shell_name().as_ref() == Some(shell))
+    }
+
+    /// Emit `TitleChange` when the composed title differs from the last one.
+    fn update_title(&mut self) {
+        let title = self.compose_title();
+        if title != self.title {
+            self.title = title.clone();
+            let _ = self.event_sender.send(TerminalEvent::TitleChange {
+                session_id: self.id.clone(),
+                title,
+            });
+        }
+    }
+
+    /// The shell's current working directory.
+    ///
+    /// Uses the OSC 7 report unless a command has started since it was
+    /// sent, then asks the OS about the shell process (Linux and macOS
+    /// only), falling back to the last report. `None` when neither source
+    /// is available.
+    pub fn current_cwd(&self) -> Option<String> {
+        if let Some((cwd, reported_at)) = &self.reported_cwd {
+            let command_started = self
+                .marks
+                .iter()
+                .rev()
+                .find(|m| m.mark_type == MarkType::CommandStart)
+                .map(|m| m.timestamp);
+            if command_started.is_none_or(|started| *reported_at >= started) {
+                return Some(cwd.clone());
+            }
+        }
+        self.pty
+            .pid()
+            .and_then(process_cwd)
+            .or_else(|| self.reported_cwd.as_ref().map(|(cwd, _)| cwd.clone()))
+    }
+
+    fn emit_clipboard(&self, content: String) {
+        let _ = self.event_sender.send(TerminalEvent::ClipboardRequest {
+            session_id: self.id.clone(),
+            content,
+        });
+    }
+
+    /// Whether the frontend should snap its viewport to the bottom for the
+    /// update being emitted: the cursor must be within `AUTOSCROLL_MARGIN`
+    /// rows of the bottom and the update must follow output or input the
+    /// policy allows.
+    fn should_autoscroll(&self, cursor: &Cursor) -> bool {
+        let typed = self.input_since_update.swap(false, Ordering::Relaxed);
+        let rows = self.terminal.size().rows;
+        let near_bottom = cursor.position.row + AUTOSCROLL_MARGIN >= rows.saturating_sub(1);
+        near_bottom && (self.config.scroll_on_output || (typed && self.config.scroll_on_input))
+    }
+
+    /// Emit bell events for the last processed chunk according to the bell mode.
+    fn emit_bell(&mut self) {
+        if !self.terminal.check_bell() {
+            return;
+        }
+        let interval = Duration::from_millis(self.config.bell_interval_ms);
+        if self.last_bell.is_some_and(|last| last.elapsed() < interval) {
+            return;
+        }
+        self.last_bell = Some(Instant::now());
+
+        let mode = self.config.bell_mode;
+        if mode.is_audible() {
+            let _ = self.event_sender.send(TerminalEvent::Bell {
+                session_id: self.id.clone(),
+            });
+        }
+        if mode.is_visual() {
+            let _ = self.event_sender.send(TerminalEvent::VisualBell {
+                session_id: self.id.clone(),
+            });
+        }
+    }
+
+    /// Get the bell mode.
+    pub fn bell_mode(&self) -> BellMode {
+        self.config.bell_mode
+    }
+
+    /// Set the fraction of the grid that must change in one update for a
+    /// full `ScreenRefresh` to be sent.
+    pub fn set_full_refresh_ratio(&mut self, ratio: f32) {
+        self.full_refresh_ratio = ratio;
+    }
+
+    /// Set the bell mode.
+    pub fn set_bell_mode(&mut self, mode: BellMode) {
+        self.config.bell_mode = mode;
+    }
+
+    /// Current logging verbosity.
+    pub fn log_level(&self) -> LogLevel {
+        self.trace.level()
+    }
+
+    /// Change how much the session logs, including its reader thread.
+    pub fn set_log_level(&mut self, level: LogLevel) {
+        self.config.log_level = level;
+        self.trace.set_level(level);
+    }
+
+    /// Force wrapping on or off, or follow the program again with `None`.
+    pub fn set_wrap_mode(&mut self, wrap: Option<bool>) {
+        self.config.wrap_override = wrap;
+        self.terminal.set_wrap_override(wrap);
+    }
+
+    /// Soft reset (DECSTR): reset modes, margins and attributes but keep
+    /// the screen content and scrollback.
+    pub fn soft_reset(&mut self) -> Option<ScreenUpdate> {
+        let reset = self.terminal.soft_reset_sequence();
+        self.process_bytes(&reset)
+    }
+
+    /// Rows visible through the scrollback viewport.
+    pub fn viewport(&self) -> Viewport {
+        self.terminal.viewport(self.viewport_top)
+    }
+
+    /// Move the viewport `delta` lines (negative is up, into the
+    /// scrollback), clamped to the buffer.
+    pub fn scroll_viewport(&mut self, delta: i64) -> Viewport {
+        let current = self.terminal.viewport(self.viewport_top);
+        let top = (current.top_line as i64 + delta).max(0) as usize;
+        self.scroll_to(top)
+    }
+
+    /// Put buffer line `line` at the top of the viewport, clamped to the
+    /// scrollback; the viewport follows the bottom again once it gets there.
+    pub fn scroll_to(&mut self, line: usize) -> Viewport {
+        let viewport = self.terminal.viewport(Some(line));
+        self.viewport_top = (viewport.top_line < viewport.scrollback_len).then_some(viewport.top_line as usize);
+        viewport
+    }
+
+    /// Find text in the buffer; see `Terminal::search`.
+    pub fn search(
+        &mut self,
+        query: &str,
+        from: Option<(usize, u16)>,
+        direction: SearchDirection,
+        case_sensitive: bool,
+    ) -> Option<SearchMatch> {
+        self.terminal.search(query, from, direction, case_sensitive)
+    }
+
+    /// Capture the session as a portable snapshot.
+    pub fn snapshot(&self) -> SessionSnapshot {
+        SessionSnapshot {
+            config: self.config.clone(),
+            env: self.spawn_env(),
+            screen: self.get_screen(),
+            scrollback: self.export_ansi_range(0, usize::MAX, false),
+            marks: self.marks.clone(),
+            cwd: self.current_cwd(),
+            size: self.terminal.size(),
+            theme: self.theme.name.clone(),
+        }
+    }
+
+    /// Replay a snapshot's buffer and marks into this session, leaving the
+    /// cursor where the snapshot had it so the new shell's prompt lands there.
+    fn restore(&mut self, snapshot: &SessionSnapshot) {
+        // Replayed lines start at the cursor's line, the top of a new buffer
+        let size = self.terminal.size();
+        let start = self.terminal.buffer_lines() - size.rows as usize
+            + self.get_cursor().position.row as usize;
+        let lines = snapshot.scrollback.matches("\r\n").count();
+
+        // Every exported line ends in CRLF; the last one would scroll the
+        // screen up a line past where the snapshot had it
+        let buffer = snapshot.scrollback.strip_suffix("\r\n").unwrap_or(&snapshot.scrollback);
+        let cursor = snapshot.screen.cursor.position;
+        let replay = format!("{}\x1b[{};{}H", buffer, cursor.row + 1, cursor.col + 1);
+        self.process_bytes(replay.as_bytes());
+
+        self.marks = snapshot
+            .marks
+            .iter()
+            .filter(|mark| (mark.row as usize) < lines)
+            .map(|mark| Mark {
+                row: mark.row + start as u32,
+                ..mark.clone()
+            })
+            .collect();
+    }
+
+    /// Get marks.
+    pub fn marks(&self) -> &[Mark] {
+        &self.marks
+    }
+
+    /// Marks whose rows are still in the buffer, oldest first. Rows index
+    /// the buffer as `export_ansi_range` does, 0 being the oldest retained
+    /// scrollback line.
+    pub fn retained_marks(&self) -> Vec<Mark> {
+        let lines = self.terminal.buffer_lines() as u32;
+        self.marks.iter().filter(|m| m.row < lines).cloned().collect()
+    }
+
+    /// Text of a command run under shell integration, found by the row of
+    /// its `CommandStart` mark, or the most recent command when `None`.
+    ///
+    /// By default only the output is returned. `include_command` starts from
+    /// the prompt the command was typed at, and `include_trailing_prompt`
+    /// runs through the first line of the prompt that followed. A command
+    /// still running yields its output so far.
+    pub fn command_output(
+        &self,
+        command_row: Option<u32>,
+        include_command: bool,
+        include_trailing_prompt: bool,
+    ) -> Option<String> {
+        let index = self.marks.iter().rposition(|m| {
+            m.mark_type == MarkType::CommandStart && command_row.is_none_or(|row| m.row == row)
+        })?;
+        let start = &self.marks[index];
+        let after = &self.marks[index + 1..];
+
+        let first = if include_command {
+            self.marks[..index]
+                .iter()
+                .rev()
+                .find(|m| m.mark_type == MarkType::PromptStart)
+                .map_or(start.row, |m| m.row)
+        } else {
+            start.row
+        };
+
+        let end = after
+            .iter()
+            .take_while(|m| m.mark_type != MarkType::CommandStart)
+            .find(|m| m.mark_type == MarkType::CommandEnd);
+        let count = match end {
+            Some(end) if include_trailing_prompt => {
+                let prompt = after
+                    .iter()
+                    .find(|m| m.mark_type == MarkType::PromptStart && m.row >= end.row)
+                    .map_or(end.row, |m| m.row);
+                (prompt + 1).saturating_sub(first) as usize
+            }
+            Some(end) => end.row.saturating_sub(first) as usize,
+            None => usize::MAX,
+        };
+
+        let text = self.terminal.text_range(first as usize, count, false);
+        Some(text.trim_end_matches('\n').to_string())
+    }
+
+    /// Add a mark.
+    pub fn add_mark(&mut self, mark: Mark) {
+        self.marks.push(mark.clone());
+        let _ = self.event_sender.send(TerminalEvent::Mark {
+            session_id: self.id.clone(),
+            mark,
+        });
+    }
+
+    /// Kill the session.
+    pub fn kill(&self) {
+        self.pty.kill();
+    }
+
+    /// Kill the session, recording why for the `ProcessExit` event.
+    pub fn kill_with_reason(&mut self, reason: ExitReason) {
+        self.kill_reason.get_or_insert(reason);
+        self.pty.kill();
+    }
+}
+
+/// Manages all terminal sessions.
+pub struct SessionManager {
+    sessions: Arc<RwLock<HashMap<SessionId, Session>>>,
+    event_sender: EventSender,
+    /// Maximum number of concurrent sessions; `None` is unlimited.
+    max_sessions: Option<usize>,
+    /// Full refresh threshold applied to new sessions.
+    full_refresh_ratio: f32,
+    /// Largest grid (cols × rows) a session can be created or resized to;
+    /// `None` is unlimited.
+    max_cells: Option<usize>,
+    /// Named sets of sessions operated on together, e.g. the panes of a tab.
+    groups: RwLock<HashMap<String, Vec<SessionId>>>,
+    /// Prefix of sequential ids for sessions created without one; random
+    /// UUIDs are used when `None`.
+    id_prefix: Option<String>,
+    /// Next number in the id sequence.
+    next_id: AtomicU64,
+    /// Notified when a session's child exits, so its exit is reported
+    /// without waiting for the next poll.
+    exit_notify: Arc<Notify>,
+}
+
+impl SessionManager {
+    /// Create a new session manager.
+    pub fn new(event_sender: EventSender) -> Self {
+        Self {
+            sessions: Arc::new(RwLock::new(HashMap::new())),
+            event_sender,
+            max_sessions: None,
+            full_refresh_ratio: DEFAULT_FULL_REFRESH_RATIO,
+            max_cells: Some(DEFAULT_MAX_CELLS),
+            groups: RwLock::new(HashMap::new()),
+            id_prefix: None,
+            next_id: AtomicU64::new(1),
+            exit_notify: Arc::new(Notify::new()),
+        }
+    }
+
+    /// Set the fraction of the grid that must change in one update for a
+    /// full `ScreenRefresh` to be sent instead of cell changes.
+    pub fn with_full_refresh_ratio(mut self, ratio: f32) -> Self {
+        self.full_refresh_ratio = ratio;
+        self
+    }
+
+    /// Give sessions created without an id sequential ids (`term-1`,
+    /// `term-2`, ...) instead of random UUIDs.
+    pub fn with_id_prefix(mut self, prefix: Option<String>) -> Self {
+        self.id_prefix = prefix;
+        self
+    }
+
+    /// Limit the number of concurrent sessions.
+    pub fn with_max_sessions(mut self, max_sessions: Option<usize>) -> Self {
+        self.max_sessions = max_sessions;
+        self
+    }
+
+    /// Limit the grid size (cols × rows) of sessions; `None` is unlimited.
+    pub fn with_max_cells(mut self, max_cells: Option<usize>) -> Self {
+        self.max_cells = max_cells;
+        self
+    }
+
+    /// How many more sessions can be created; `None` if unlimited.
+    pub fn remaining_capacity(&self) -> Option<usize> {
+        let max = self.max_sessions?;
+        Some(max.saturating_sub(live_count(&self.sessions.read())))
+    }
+
+    /// Create a manager that isn't attached to Tauri, returning the
+    /// receiver for its events.
+    pub fn new_headless() -> (Self, EventReceiver) {
+        let (event_sender, event_receiver) = event_channel();
+        (Self::new(event_sender), event_receiver)
+    }
+
+    /// Create a new session.
+    pub fn create(&self, config: SessionConfig) -> Result<SessionId> {
+        self.register(config, |config, event_sender| {
+            Session::spawn(config, event_sender, Some(self.exit_notify.clone()))
+        })
+    }
+
+    /// Wait until a session's child may have exited. A permit is stored
+    /// when nobody is waiting, so an exit between waits isn't missed.
+    pub async fn exit_notified(&self) {
+        self.exit_notify.notified().await;
+    }
+
+    /// Register a session driven by an externally opened PTY.
+    pub fn attach(&self, config: SessionConfig, external: ExternalPty) -> Result<SessionId> {
+        self.register(config, |config, event_sender| {
+            Ok(Session::attach(config, event_sender, external))
+        })
+    }
+
+    fn register<F>(&self, config: SessionConfig, build: F) -> Result<SessionId>
+    where
+        F: FnOnce(SessionConfig, EventSender) -> Result<Session>,
+    {
+        self.check_grid_size(config.cols.unwrap_or(80), config.rows.unwrap_or(24))?;
+
+        // Check if session already exists
+        let id = {
+            let sessions = self.sessions.read();
+            let id = match config.id.clone() {
+                Some(id) => id,
+                None => self.generate_id(&sessions),
+            };
+            if sessions.contains_key(&id) {
+                return Err(Error::SessionAlreadyExists(id));
+            }
+            self.check_capacity(&sessions)?;
+            id
+        };
+
+        let mut config = config;
+        config.id = Some(id.clone());
+
+        let mut session = build(config, self.event_sender.clone())?;
+        session.set_full_refresh_ratio(self.full_refresh_ratio);
+
+        {
+            let mut sessions = self.sessions.write();
+            // Another create may have taken the id or filled the last slot
+            // while spawning; the new session is dropped after the lock
+            if sessions.contains_key(&id) {
+                return Err(Error::SessionAlreadyExists(id));
+            }
+            self.check_capacity(&sessions)?;
+            sessions.insert(id.clone(), session);
+        }
+
+        // Emit event
+        let _ = self.event_sender.send(TerminalEvent::SessionCreated {
+            session_id: id.clone(),
+        });
+
+        Ok(id)
+    }
+
+    /// Snapshot a session; see `SessionSnapshot`.
+    pub fn export_snapshot(&self, id: &str) -> Result<SessionSnapshot> {
+        let sessions = self.sessions.read();
+        let session = sessions
+            .get(id)
+            .ok_or_else(|| Error::SessionNotFound(id.to_string()))?;
+        Ok(session.snapshot())
+    }
+
+    /// Create a session from a snapshot, respawning its shell with the
+    /// snapshot's config, size, theme and working directory (or the home
+    /// directory if that doesn't exist here) and replaying its buffer.
+    /// The snapshot's id is only reused when `id` names it.
+    pub fn import_snapshot(&self, snapshot: SessionSnapshot, id: Option<String>) -> Result<SessionId> {
+        let mut config = snapshot.config.clone();
+        config.id = id;
+        config.cwd = snapshot.cwd.clone().or(config.cwd);
+        config.cwd_fallback = true;
+        config.cols = Some(snapshot.size.cols);
+        config.rows = Some(snapshot.size.rows);
+        config.theme = Some(snapshot.theme.clone());
+
+        let id = self.register(config, |config, event_sender| {
+            let session = Session::spawn(config, event_sender, Some(self.exit_notify.clone()))?;
+            // Keep the new shell's output back until the buffer is replayed
+            session.output_holds.fetch_add(1, Ordering::Relaxed);
+            Ok(session)
+        })?;
+
+        // Restore after `SessionCreated`, so the frontend sees the replay
+        let mut sessions = self.sessions.write();
+        if let Some(session) = sessions.get_mut(&id) {
+            session.restore(&snapshot);
+            *session.output_holds.get_mut() -= 1;
+        }
+        Ok(id)
+    }
+
+    /// Id for a session created without one: the next free id in the
+    /// prefix sequence, or a random UUID.
+    fn generate_id(&self, sessions: &HashMap<SessionId, Session>) -> SessionId {
+        let Some(prefix) = &self.id_prefix else {
+            return Uuid::new_v4().to_string();
+        };
+        loop {
+            // Skip numbers taken by ids the caller chose
+            let id = format!("{}{}", prefix, self.next_id.fetch_add(1, Ordering::Relaxed));
+            if !sessions.contains_key(&id) {
+                return id;
+            }
+        }
+    }
+
+    fn check_capacity(&self, sessions: &HashMap<SessionId, Session>) -> Result<()> {
+        match self.max_sessions {
+            Some(max) if live_count(sessions) >= max => Err(Error::SessionLimitReached(max)),
+            _ => Ok(()),
+        }
+    }
+
+    fn check_grid_size(&self, cols: u16, rows: u16) -> Result<()> {
+        match self.max_cells {
+            Some(max) if cols as usize * rows as usize > max => Err(Error::InvalidConfig(format!(
+                "Terminal size {}x{} exceeds the limit of {} cells",
+                cols, rows, max
+            ))),
+            _ => Ok(()),
+        }
+    }
+
+    /// Kill a session's process, leaving the session until its exit is reported.
+    pub fn kill(&self, id: &str) -> Result<()> {
+        let mut sessions = self.sessions.write();
+        let session = sessions
+            .get_mut(id)
+            .ok_or_else(|| Error::SessionNotFound(id.to_string()))?;
+        session.kill_with_reason(ExitReason::Killed);
+        Ok(())
+    }
+
+    /// Destroy a session.
+    pub fn destroy(&self, id: &str) -> Result<()> {
+        let session = {
             let mut sessions = self.sessions.write();
-            sessions.insert(id.clone(), session);
+            sessions.remove(id)
+        };
+
+        self.remove_from_groups(id);
+
+        match session {
+            Some(mut s) => {
+                s.kill_with_reason(ExitReason::Killed);
+                if s.exited_at.is_none() {
+                    s.report_exit(None, None, ExitReason::Killed);
+                }
+                let _ = self.event_sender.send(TerminalEvent::SessionDestroyed {
+                    session_id: id.to_string(),
+                });
+                Ok(())
+            }
+            None => Err(Error::SessionNotFound(id.to_string())),
+        }
+    }
+
+    /// Drop a removed session from every group.
+    fn remove_from_groups(&self, id: &str) {
+        for members in self.groups.write().values_mut() {
+            members.retain(|member| member != id);
+        }
+    }
+
+    /// Create a group of sessions. Every session must exist.
+    pub fn create_group(&self, name: &str, session_ids: Vec<SessionId>) -> Result<()> {
+        {
+            let sessions = self.sessions.read();
+            if let Some(missing) = session_ids.iter().find(|id| !sessions.contains_key(*id)) {
+                return Err(Error::SessionNotFound(missing.clone()));
+            }
+        }
+
+        let mut groups = self.groups.write();
+        if groups.contains_key(name) {
+            return Err(Error::GroupAlreadyExists(name.to_string()));
+        }
+        let mut members: Vec<SessionId> = Vec::with_capacity(session_ids.len());
+        for id in session_ids {
+            if !members.contains(&id) {
+                members.push(id);
+            }
+        }
+        groups.insert(name.to_string(), members);
+        Ok(())
+    }
+
+    /// Add a session to an existing group.
+    pub fn add_to_group(&self, name: &str, id: &str) -> Result<()> {
+        if !self.sessions.read().contains_key(id) {
+            return Err(Error::SessionNotFound(id.to_string()));
+        }
+        let mut groups = self.groups.write();
+        let members = groups
+            .get_mut(name)
+            .ok_or_else(|| Error::GroupNotFound(name.to_string()))?;
+        if !members.iter().any(|member| member == id) {
+            members.push(id.to_string());
+        }
+        Ok(())
+    }
+
+    /// Sessions in a group, in the order they were added.
+    pub fn group_members(&self, name: &str) -> Result<Vec<SessionId>> {
+        self.groups
+            .read()
+            .get(name)
+            .cloned()
+            .ok_or_else(|| Error::GroupNotFound(name.to_string()))
+    }
+
+    /// Run `op` on every member of a group, returning the first error once
+    /// all have been tried.
+    fn for_each_member(&self, name: &str, mut op: impl FnMut(&str) -> Result<()>) -> Result<()> {
+        let mut first_error = None;
+        for id in self.group_members(name)? {
+            if let Err(e) = op(&id) {
+                first_error.get_or_insert(e);
+            }
+        }
+        first_error.map_or(Ok(()), Err)
+    }
+
+    /// Resize every session in a group.
+    pub fn group_resize(&self, name: &str, cols: u16, rows: u16) -> Result<()> {
+        self.for_each_member(name, |id| self.resize(id, cols, rows))
+    }
+
+    /// Write the same input to every session in a group.
+    pub fn group_broadcast(&self, name: &str, data: &[u8]) -> Result<()> {
+        self.for_each_member(name, |id| self.write(id, data))
+    }
+
+    /// Destroy every session in a group, then the group itself.
+    pub fn destroy_group(&self, name: &str) -> Result<()> {
+        let result = self.for_each_member(name, |id| match self.destroy(id) {
+            // Destroyed concurrently; nothing left to do
+            Err(Error::SessionNotFound(_)) => Ok(()),
+            other => other,
+        });
+        self.groups.write().remove(name);
+        result
+    }
+
+    /// Get session info.
+    pub fn get_info(&self, id: &str) -> Result<SessionInfo> {
+        let sessions = self.sessions.read();
+        sessions
+            .get(id)
+            .map(|s| s.info())
+            .ok_or_else(|| Error::SessionNotFound(id.to_string()))
+    }
+
+    /// Get the logical time of a session's last screen update.
+    pub fn last_update(&self, id: &str) -> Result<u64> {
+        let sessions = self.sessions.read();
+        let session = sessions
+            .get(id)
+            .ok_or_else(|| Error::SessionNotFound(id.to_string()))?;
+        Ok(session.last_update())
+    }
+
+    /// Sessions whose last screen update is later than `since`.
+    pub fn changed_since(&self, since: u64) -> Vec<SessionId> {
+        let sessions = self.sessions.read();
+        sessions
+            .values()
+            .filter(|s| s.last_update() > since)
+            .map(|s| s.id.clone())
+            .collect()
+    }
+
+    /// List all sessions.
+    pub fn list(&self) -> Vec<SessionInfo> {
+        let sessions = self.sessions.read();
+        sessions.values().map(|s| s.info()).collect()
+    }
+
+    /// List the sessions matching a filter.
+    pub fn list_filtered(&self, filter: &SessionFilter) -> Vec<SessionInfo> {
+        let sessions = self.sessions.read();
+        sessions
+            .values()
+            .filter(|s| s.matches(filter))
+            .map(|s| s.info())
+            .collect()
+    }
+
+    /// Write to a session.
+    pub fn write(&self, id: &str, data: &[u8]) -> Result<()> {
+        let sessions = self.sessions.read();
+        let session = sessions
+            .get(id)
+            .ok_or_else(|| Error::SessionNotFound(id.to_string()))?;
+        session.write(data)
+    }
+
+    /// Set an environment variable for a session's future children.
+    pub fn set_env(&self, id: &str, key: &str, value: &str, apply_now: bool) -> Result<()> {
+        let mut sessions = self.sessions.write();
+        let session = sessions
+            .get_mut(id)
+            .ok_or_else(|| Error::SessionNotFound(id.to_string()))?;
+        session.set_env(key, value, apply_now)
+    }
+
+    /// Get the environment a session's shell was spawned with.
+    pub fn spawn_env(&self, id: &str) -> Result<HashMap<String, String>> {
+        let sessions = self.sessions.read();
+        let session = sessions
+            .get(id)
+            .ok_or_else(|| Error::SessionNotFound(id.to_string()))?;
+        Ok(session.spawn_env())
+    }
+
+    /// Get the command a session's shell was spawned with.
+    pub fn export_spawn_command(&self, id: &str) -> Result<Option<SpawnSpec>> {
+        let sessions = self.sessions.read();
+        let session = sessions
+            .get(id)
+            .ok_or_else(|| Error::SessionNotFound(id.to_string()))?;
+        Ok(session.export_spawn_command())
+    }
+
+    /// Send a key press to a session, honoring its key bindings.
+    pub fn send_key(&self, id: &str, event: &KeyEvent) -> Result<()> {
+        let sessions = self.sessions.read();
+        let session = sessions
+            .get(id)
+            .ok_or_else(|| Error::SessionNotFound(id.to_string()))?;
+        session.send_key(event)
+    }
+
+    /// Resize a session.
+    pub fn resize(&self, id: &str, cols: u16, rows: u16) -> Result<()> {
+        self.check_grid_size(cols, rows)?;
+        let mut sessions = self.sessions.write();
+        let session = sessions
+            .get_mut(id)
+            .ok_or_else(|| Error::SessionNotFound(id.to_string()))?;
+        session.resize(cols, rows)
+    }
+
+    /// Get the pixel size reported to a session's child.
+    pub fn pixel_size(&self, id: &str) -> Result<PixelSize> {
+        let sessions = self.sessions.read();
+        let session = sessions
+            .get(id)
+            .ok_or_else(|| Error::SessionNotFound(id.to_string()))?;
+        Ok(session.pixel_size())
+    }
+
+    /// Set the pixel size reported to a session's child.
+    pub fn set_pixel_size(&self, id: &str, pixels: PixelSize) -> Result<()> {
+        let mut sessions = self.sessions.write();
+        let session = sessions
+            .get_mut(id)
+            .ok_or_else(|| Error::SessionNotFound(id.to_string()))?;
+        session.set_pixel_size(pixels)
+    }
+
+    /// Get screen state.
+    pub fn get_screen(&self, id: &str) -> Result<Screen> {
+        let sessions = self.sessions.read();
+        let session = sessions
+            .get(id)
+            .ok_or_else(|| Error::SessionNotFound(id.to_string()))?;
+        Ok(session.get_screen())
+    }
+
+    /// Export a range of a session's buffer lines as ANSI-colored text.
+    pub fn export_ansi_range(
+        &self,
+        id: &str,
+        start_line: usize,
+        count: usize,
+        normalize_newlines: bool,
+    ) -> Result<String> {
+        let sessions = self.sessions.read();
+        let session = sessions
+            .get(id)
+            .ok_or_else(|| Error::SessionNotFound(id.to_string()))?;
+        Ok(session.export_ansi_range(start_line, count, normalize_newlines))
+    }
+
+    /// Get a session's diagnostics snapshot.
+    pub fn diagnostics(&self, id: &str) -> Result<SessionDiagnostics> {
+        let sessions = self.sessions.read();
+        let session = sessions
+            .get(id)
+            .ok_or_else(|| Error::SessionNotFound(id.to_string()))?;
+        Ok(session.diagnostics())
+    }
+
+    /// Get a fingerprint of a session's visible grid.
+    pub fn screen_hash(&self, id: &str) -> Result<u64> {
+        let sessions = self.sessions.read();
+        let session = sessions
+            .get(id)
+            .ok_or_else(|| Error::SessionNotFound(id.to_string()))?;
+        Ok(session.screen_hash())
+    }
+
+    /// Get a session's visible screen as plain text.
+    pub fn screen_text(&self, id: &str) -> Result<String> {
+        let sessions = self.sessions.read();
+        let session = sessions
+            .get(id)
+            .ok_or_else(|| Error::SessionNotFound(id.to_string()))?;
+        Ok(session.screen_text())
+    }
+
+    /// Export a session's buffer as plain text.
+    pub fn export_text(&self, id: &str, preserve_wrapping: bool) -> Result<String> {
+        let sessions = self.sessions.read();
+        let session = sessions
+            .get(id)
+            .ok_or_else(|| Error::SessionNotFound(id.to_string()))?;
+        Ok(session.export_text(preserve_wrapping))
+    }
+
+    /// Write a session's whole buffer to `path` a page at a time, so huge
+    /// scrollback isn't rendered into one string and the session lock is
+    /// released between pages. Returns the number of lines written.
+    ///
+    /// This blocks; run it off the async runtime. The session's output is
+    /// held until the export finishes, so lines can't scroll out of a full
+    /// scrollback and shift between pages.
+    pub fn export_to_file(
+        &self,
+        id: &str,
+        path: &Path,
+        format: ExportFormat,
+        preserve_wrapping: bool,
+    ) -> Result<usize> {
+        let session_page = |start_line: usize, count: usize| -> Result<String> {
+            let sessions = self.sessions.read();
+            let session = sessions
+                .get(id)
+                .ok_or_else(|| Error::SessionNotFound(id.to_string()))?;
+            Ok(session.export_page(format, start_line, count, preserve_wrapping))
+        };
+        let total = {
+            let sessions = self.sessions.read();
+            let session = sessions
+                .get(id)
+                .ok_or_else(|| Error::SessionNotFound(id.to_string()))?;
+            session.output_holds.fetch_add(1, Ordering::Relaxed);
+            session.buffer_lines()
+        };
+
+        let write_pages = || -> Result<()> {
+            let mut file = BufWriter::new(std::fs::File::create(path)?);
+            for start_line in (0..total).step_by(EXPORT_PAGE_LINES) {
+                let count = EXPORT_PAGE_LINES.min(total - start_line);
+                file.write_all(session_page(start_line, count)?.as_bytes())?;
+            }
+            file.flush()?;
+            Ok(())
+        };
+        let result = write_pages();
+
+        if let Some(session) = self.sessions.read().get(id) {
+            // The id may have been reused by a session that holds nothing
+            let _ = session
+                .output_holds
+                .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |n| n.checked_sub(1));
         }
+        result.map(|()| total)
+    }
+
+    /// Get the text of a shell-integrated command; see `Session::command_output`.
+    pub fn command_output(
+        &self,
+        id: &str,
+        command_row: Option<u32>,
+        include_command: bool,
+        include_trailing_prompt: bool,
+    ) -> Result<Option<String>> {
+        let sessions = self.sessions.read();
+        let session = sessions
+            .get(id)
+            .ok_or_else(|| Error::SessionNotFound(id.to_string()))?;
+        Ok(session.command_output(command_row, include_command, include_trailing_prompt))
+    }
+
+    /// Get a session's current working directory; see `Session::current_cwd`.
+    pub fn current_cwd(&self, id: &str) -> Result<Option<String>> {
+        let sessions = self.sessions.read();
+        let session = sessions
+            .get(id)
+            .ok_or_else(|| Error::SessionNotFound(id.to_string()))?;
+        Ok(session.current_cwd())
+    }
+
+    /// Write a session's close sequence, returning its close timeout.
+    pub fn request_close(&self, id: &str) -> Result<Duration> {
+        let sessions = self.sessions.read();
+        let session = sessions
+            .get(id)
+            .ok_or_else(|| Error::SessionNotFound(id.to_string()))?;
+        session.request_close()
+    }
+
+    /// Report a session's exit if it has happened; see `Session::poll_exit`.
+    pub fn poll_exit(&self, id: &str) -> Result<bool> {
+        let mut sessions = self.sessions.write();
+        let session = sessions
+            .get_mut(id)
+            .ok_or_else(|| Error::SessionNotFound(id.to_string()))?;
+        Ok(session.poll_exit())
+    }
+
+    /// Get a session's metadata.
+    pub fn metadata(&self, id: &str) -> Result<HashMap<String, String>> {
+        let sessions = self.sessions.read();
+        let session = sessions
+            .get(id)
+            .ok_or_else(|| Error::SessionNotFound(id.to_string()))?;
+        Ok(session.metadata().clone())
+    }
+
+    /// Set or remove a session metadata entry.
+    pub fn set_metadata(&self, id: &str, key: String, value: Option<String>) -> Result<()> {
+        let mut sessions = self.sessions.write();
+        let session = sessions
+            .get_mut(id)
+            .ok_or_else(|| Error::SessionNotFound(id.to_string()))?;
+        session.set_metadata(key, value);
+        Ok(())
+    }
+
+    /// Get a session's capabilities and current modes.
+    pub fn capabilities(&self, id: &str) -> Result<Capabilities> {
+        let sessions = self.sessions.read();
+        let session = sessions
+            .get(id)
+            .ok_or_else(|| Error::SessionNotFound(id.to_string()))?;
+        Ok(session.capabilities())
+    }
 
-        // Emit event
-        let _ = self.event_sender.send(TerminalEvent::SessionCreated {
-            session_id: id.clone(),
-        });
+    /// Whether a session is showing the alternate screen.
+    pub fn is_alt_screen(&self, id: &str) -> Result<bool> {
+        let sessions = self.sessions.read();
+        let session = sessions
+            .get(id)
+            .ok_or_else(|| Error::SessionNotFound(id.to_string()))?;
+        Ok(session.is_alt_screen())
+    }
 
-        Ok(id)
+    /// Check whether a session's program has turned on insert mode.
+    pub fn is_insert_mode(&self, id: &str) -> Result<bool> {
+        let sessions = self.sessions.read();
+        let session = sessions
+            .get(id)
+            .ok_or_else(|| Error::SessionNotFound(id.to_string()))?;
+        Ok(session.is_insert_mode())
     }
 
-    /// Destroy a session.
-    pub fn destroy(&self, id: &str) -> Result<()> {
-        let session = {
-            let mut sessions = self.sessions.write();
-            sessions.remove(id)
-        };
+    /// Get a session's tab stop columns.
+    pub fn tab_stops(&self, id: &str) -> Result<Vec<u16>> {
+        let sessions = self.sessions.read();
+        let session = sessions
+            .get(id)
+            .ok_or_else(|| Error::SessionNotFound(id.to_string()))?;
+        Ok(session.tab_stops())
+    }
 
-        match session {
-            Some(s) => {
-                s.kill();
-                let _ = self.event_sender.send(TerminalEvent::SessionDestroyed {
-                    session_id: id.to_string(),
+    /// Turn software flow control (XOFF/XON) on or off for a session.
+    pub fn set_flow_control(&self, id: &str, enabled: bool) -> Result<()> {
+        let mut sessions = self.sessions.write();
+        let session = sessions
+            .get_mut(id)
+            .ok_or_else(|| Error::SessionNotFound(id.to_string()))?;
+        session.set_flow_control(enabled);
+        Ok(())
+    }
+
+    /// Get the rows visible through a session's scrollback viewport.
+    pub fn viewport(&self, id: &str) -> Result<Viewport> {
+        let sessions = self.sessions.read();
+        let session = sessions
+            .get(id)
+            .ok_or_else(|| Error::SessionNotFound(id.to_string()))?;
+        Ok(session.viewport())
+    }
+
+    /// Scroll a session's viewport by `delta` lines.
+    pub fn scroll_viewport(&self, id: &str, delta: i64) -> Result<Viewport> {
+        let mut sessions = self.sessions.write();
+        let session = sessions
+            .get_mut(id)
+            .ok_or_else(|| Error::SessionNotFound(id.to_string()))?;
+        Ok(session.scroll_viewport(delta))
+    }
+
+    /// Scroll a session's viewport so buffer line `line` is at the top.
+    pub fn scroll_to(&self, id: &str, line: usize) -> Result<Viewport> {
+        let mut sessions = self.sessions.write();
+        let session = sessions
+            .get_mut(id)
+            .ok_or_else(|| Error::SessionNotFound(id.to_string()))?;
+        Ok(session.scroll_to(line))
+    }
+
+    /// Find the next or previous occurrence of text in a session's buffer.
+    pub fn search(
+        &self,
+        id: &str,
+        query: &str,
+        from: Option<(usize, u16)>,
+        direction: SearchDirection,
+        case_sensitive: bool,
+    ) -> Result<Option<SearchMatch>> {
+        let mut sessions = self.sessions.write();
+        let session = sessions
+            .get_mut(id)
+            .ok_or_else(|| Error::SessionNotFound(id.to_string()))?;
+        Ok(session.search(query, from, direction, case_sensitive))
+    }
+
+    /// Get a session's shell integration marks.
+    pub fn marks(&self, id: &str) -> Result<Vec<Mark>> {
+        let sessions = self.sessions.read();
+        let session = sessions
+            .get(id)
+            .ok_or_else(|| Error::SessionNotFound(id.to_string()))?;
+        Ok(session.marks().to_vec())
+    }
+
+    /// Get a session's marks that are still in its buffer.
+    pub fn retained_marks(&self, id: &str) -> Result<Vec<Mark>> {
+        let sessions = self.sessions.read();
+        let session = sessions
+            .get(id)
+            .ok_or_else(|| Error::SessionNotFound(id.to_string()))?;
+        Ok(session.retained_marks())
+    }
+
+    /// Submit a line of input to a session's shell.
+    pub fn submit_line(&self, id: &str, line: &str) -> Result<()> {
+        let sessions = self.sessions.read();
+        let session = sessions
+            .get(id)
+            .ok_or_else(|| Error::SessionNotFound(id.to_string()))?;
+        session.submit_line(line)
+    }
+
+    /// Submit lines to a session's shell one at a time, waiting up to
+    /// `timeout` for each to finish; see the `run_script` command. Fails
+    /// with `CommandTimeout` when a line is still running at its deadline.
+    ///
+    /// Session output must be processed meanwhile (the plugin's poll loop
+    /// does this), or completion is never seen.
+    pub async fn run_script(
+        &self,
+        id: &str,
+        lines: Vec<String>,
+        timeout: Duration,
+        on_timeout: TimeoutAction,
+    ) -> Result<Vec<ScriptLineResult>> {
+        let mut results = Vec::with_capacity(lines.len());
+        for line in lines {
+            let result = self.run_script_line(id, line, timeout, on_timeout).await?;
+            // A line that went quiet has an unknown result; keep going
+            let failed = result.success == Some(false);
+            results.push(result);
+            if failed {
+                break;
+            }
+        }
+        Ok(results)
+    }
+
+    async fn run_script_line(
+        &self,
+        id: &str,
+        command: String,
+        timeout: Duration,
+        on_timeout: TimeoutAction,
+    ) -> Result<ScriptLineResult> {
+        let marks = self.marks(id)?;
+        let seen = marks.len();
+        let integrated = marks.iter().any(|m| m.mark_type == MarkType::PromptStart);
+
+        self.submit_line(id, &command)?;
+        let deadline = tokio::time::Instant::now() + timeout;
+
+        loop {
+            tokio::time::sleep(SCRIPT_POLL_INTERVAL).await;
+
+            if integrated {
+                let marks = self.marks(id)?;
+                let new = &marks[seen.min(marks.len())..];
+                let end = new.iter().find(|m| m.mark_type == MarkType::CommandEnd);
+                if let Some(end) = end {
+                    return Ok(ScriptLineResult {
+                        output: self.script_line_output(id, new)?,
+                        command,
+                        exit_code: end.exit_code,
+                        success: Some(end.exit_code.unwrap_or(0) == 0),
+                    });
+                }
+            } else if self.idle_for(id)? >= SCRIPT_IDLE
+                // Output also goes quiet while a job like `sleep` runs
+                && self.foreground_job_running(id)? != Some(true)
+            {
+                return Ok(ScriptLineResult {
+                    command,
+                    exit_code: None,
+                    success: None,
+                    output: None,
                 });
-                Ok(())
             }
-            None => Err(Error::SessionNotFound(id.to_string())),
+
+            if tokio::time::Instant::now() >= deadline {
+                // Capture the output before interrupting, so it isn't mixed with `^C`
+                let marks = self.marks(id)?;
+                let output = match self.script_line_output(id, &marks[seen.min(marks.len())..])? {
+                    Some(output) => output,
+                    None => self.unmatched_output(id, SCRIPT_TAIL_BYTES)?,
+                };
+                match on_timeout {
+                    TimeoutAction::Leave => {}
+                    TimeoutAction::Interrupt => self.write(id, b"\x03")?,
+                    TimeoutAction::Kill => self.kill(id)?,
+                }
+                return Err(Error::CommandTimeout { output });
+            }
         }
     }
 
-    /// Get session info.
-    pub fn get_info(&self, id: &str) -> Result<SessionInfo> {
+    /// Output of the command started among `marks`, if its start was marked.
+    fn script_line_output(&self, id: &str, marks: &[Mark]) -> Result<Option<String>> {
+        let Some(start) = marks.iter().find(|m| m.mark_type == MarkType::CommandStart) else {
+            return Ok(None);
+        };
+        self.command_output(id, Some(start.row), false, false)
+    }
+
+    /// Match `pattern` against a session's output since its last match.
+    pub fn expect(&self, id: &str, pattern: &Regex) -> Result<Option<MatchRange>> {
+        let mut sessions = self.sessions.write();
+        let session = sessions
+            .get_mut(id)
+            .ok_or_else(|| Error::SessionNotFound(id.to_string()))?;
+        Ok(session.expect(pattern))
+    }
+
+    /// Up to `max` bytes of a session's output not consumed by `expect`.
+    pub fn unmatched_output(&self, id: &str, max: usize) -> Result<String> {
         let sessions = self.sessions.read();
-        sessions
+        let session = sessions
             .get(id)
-            .map(|s| s.info())
-            .ok_or_else(|| Error::SessionNotFound(id.to_string()))
+            .ok_or_else(|| Error::SessionNotFound(id.to_string()))?;
+        Ok(session.unmatched_output(max))
     }
 
-    /// List all sessions.
-    pub fn list(&self) -> Vec<SessionInfo> {
+    /// Time since a session last saw input or output.
+    pub fn idle_for(&self, id: &str) -> Result<Duration> {
         let sessions = self.sessions.read();
-        sessions.values().map(|s| s.info()).collect()
+        let session = sessions
+            .get(id)
+            .ok_or_else(|| Error::SessionNotFound(id.to_string()))?;
+        Ok(session.idle_for())
     }
 
-    /// Write to a session.
-    pub fn write(&self, id: &str, data: &[u8]) -> Result<()> {
+    /// Whether a job other than the shell is in a session's foreground;
+    /// `None` if unknown.
+    pub fn foreground_job_running(&self, id: &str) -> Result<Option<bool>> {
         let sessions = self.sessions.read();
         let session = sessions
             .get(id)
             .ok_or_else(|| Error::SessionNotFound(id.to_string()))?;
-        session.write(data)
+        Ok(session.foreground_job_running())
     }
 
-    /// Resize a session.
-    pub fn resize(&self, id: &str, cols: u16, rows: u16) -> Result<()> {
-        let mut sessions = self.sessions.write();
+    /// Copy a selection to the clipboard via a `ClipboardRequest` event.
+    pub fn copy_selection(
+        &self,
+        id: &str,
+        start: CursorPosition,
+        end: CursorPosition,
+        rectangular: bool,
+    ) -> Result<String> {
+        let sessions = self.sessions.read();
         let session = sessions
-            .get_mut(id)
+            .get(id)
             .ok_or_else(|| Error::SessionNotFound(id.to_string()))?;
-        session.resize(cols, rows)
+        Ok(session.copy_selection(start, end, rectangular))
     }
 
-    /// Get screen state.
-    pub fn get_screen(&self, id: &str) -> Result<Screen> {
+    /// Get one cell of a session's visible screen. Out-of-range positions
+    /// give an empty cell; only an unknown session is an error.
+    pub fn get_cell(&self, id: &str, row: u16, col: u16) -> Result<Cell> {
         let sessions = self.sessions.read();
         let session = sessions
             .get(id)
             .ok_or_else(|| Error::SessionNotFound(id.to_string()))?;
-        Ok(session.get_screen())
+        Ok(session.get_cell(row, col))
+    }
+
+    /// Get a rectangle of a session's visible cells.
+    pub fn region(&self, id: &str, top: u16, left: u16, bottom: u16, right: u16) -> Result<Vec<Row>> {
+        let sessions = self.sessions.read();
+        let session = sessions
+            .get(id)
+            .ok_or_else(|| Error::SessionNotFound(id.to_string()))?;
+        Ok(session.region(top, left, bottom, right))
+    }
+
+    /// List the inline images in a session's buffer.
+    pub fn images(&self, id: &str) -> Result<Vec<ImagePlacement>> {
+        let sessions = self.sessions.read();
+        let session = sessions
+            .get(id)
+            .ok_or_else(|| Error::SessionNotFound(id.to_string()))?;
+        Ok(session.images())
+    }
+
+    /// Get an inline image's base64 payload; `None` once it has been evicted.
+    pub fn image_data(&self, id: &str, image_id: u32) -> Result<Option<String>> {
+        let sessions = self.sessions.read();
+        let session = sessions
+            .get(id)
+            .ok_or_else(|| Error::SessionNotFound(id.to_string()))?;
+        Ok(session.image_data(image_id))
+    }
+
+    /// Get the approximate memory held by a session's buffers.
+    pub fn buffer_stats(&self, id: &str) -> Result<BufferStats> {
+        let sessions = self.sessions.read();
+        let session = sessions
+            .get(id)
+            .ok_or_else(|| Error::SessionNotFound(id.to_string()))?;
+        Ok(session.buffer_stats())
+    }
+
+    /// Buffer memory summed over all sessions.
+    pub fn total_buffer_stats(&self) -> BufferStats {
+        let mut total = BufferStats::default();
+        for session in self.sessions.read().values() {
+            total += session.buffer_stats();
+        }
+        total
     }
 
     /// Process output for all sessions.
@@ -336,9 +2755,38 @@ impl SessionManager {
         let mut sessions = self.sessions.write();
         for session in sessions.values_mut() {
             session.process_output();
+            session.poll_exit();
         }
     }
 
+    /// Feed bytes into a session's emulator without touching its PTY,
+    /// emitting events as if the program had written them.
+    pub fn feed(&self, id: &str, data: &[u8]) -> Result<Option<ScreenUpdate>> {
+        let mut sessions = self.sessions.write();
+        let session = sessions
+            .get_mut(id)
+            .ok_or_else(|| Error::SessionNotFound(id.to_string()))?;
+        Ok(session.process_bytes(data))
+    }
+
+    /// Re-emit the last screen update of a session.
+    pub fn replay_last_update(&self, id: &str) -> Result<Option<ScreenUpdate>> {
+        let sessions = self.sessions.read();
+        let session = sessions
+            .get(id)
+            .ok_or_else(|| Error::SessionNotFound(id.to_string()))?;
+        Ok(session.replay_last_update())
+    }
+
+    /// Check whether a session has output waiting to be processed.
+    pub fn has_pending_output(&self, id: &str) -> Result<bool> {
+        let sessions = self.sessions.read();
+        let session = sessions
+            .get(id)
+            .ok_or_else(|| Error::SessionNotFound(id.to_string()))?;
+        Ok(session.has_pending_output())
+    }
+
     /// Process output for a specific session.
     pub fn process(&self, id: &str) -> Result<Option<ScreenUpdate>> {
         let mut sessions = self.sessions.write();
@@ -354,7 +2802,36 @@ impl SessionManager {
         let session = sessions
             .get(id)
             .ok_or_else(|| Error::SessionNotFound(id.to_string()))?;
-        Ok(session.theme().clone())
+        Ok(session.theme())
+    }
+
+    /// Override a session's cursor color; `None` restores the theme's.
+    pub fn set_cursor_color(&self, id: &str, color: Option<Color>) -> Result<()> {
+        let mut sessions = self.sessions.write();
+        let session = sessions
+            .get_mut(id)
+            .ok_or_else(|| Error::SessionNotFound(id.to_string()))?;
+        session.set_cursor_color(color);
+        Ok(())
+    }
+
+    /// Override one of a session's indexed colors; `None` removes the override.
+    pub fn set_color_override(&self, id: &str, index: u8, color: Option<Color>) -> Result<()> {
+        let mut sessions = self.sessions.write();
+        let session = sessions
+            .get_mut(id)
+            .ok_or_else(|| Error::SessionNotFound(id.to_string()))?;
+        session.set_color_override(index, color);
+        Ok(())
+    }
+
+    /// Get a session's resolved 256-color palette.
+    pub fn palette(&self, id: &str) -> Result<Vec<Color>> {
+        let sessions = self.sessions.read();
+        let session = sessions
+            .get(id)
+            .ok_or_else(|| Error::SessionNotFound(id.to_string()))?;
+        Ok(session.palette().to_vec())
     }
 
     /// Set theme for a session.
@@ -370,29 +2847,138 @@ impl SessionManager {
         Ok(())
     }
 
+    /// Replace a session's theme and notify the frontend.
+    pub fn apply_theme(&self, id: &str, theme: Theme) -> Result<()> {
+        let mut sessions = self.sessions.write();
+        let session = sessions
+            .get_mut(id)
+            .ok_or_else(|| Error::SessionNotFound(id.to_string()))?;
+        session.set_theme(theme);
+        session.emit_theme_change();
+        Ok(())
+    }
+
+    /// Set the bell mode for a session.
+    pub fn set_bell_mode(&self, id: &str, mode: BellMode) -> Result<()> {
+        let mut sessions = self.sessions.write();
+        let session = sessions
+            .get_mut(id)
+            .ok_or_else(|| Error::SessionNotFound(id.to_string()))?;
+        session.set_bell_mode(mode);
+        Ok(())
+    }
+
+    /// Set how much a session logs.
+    pub fn set_log_level(&self, id: &str, level: LogLevel) -> Result<()> {
+        let mut sessions = self.sessions.write();
+        let session = sessions
+            .get_mut(id)
+            .ok_or_else(|| Error::SessionNotFound(id.to_string()))?;
+        session.set_log_level(level);
+        Ok(())
+    }
+
+    /// Soft-reset a session's terminal modes, keeping its content.
+    pub fn soft_reset(&self, id: &str) -> Result<()> {
+        let mut sessions = self.sessions.write();
+        let session = sessions
+            .get_mut(id)
+            .ok_or_else(|| Error::SessionNotFound(id.to_string()))?;
+        session.soft_reset();
+        Ok(())
+    }
+
+    /// Override line wrapping for a session.
+    pub fn set_wrap_mode(&self, id: &str, wrap: Option<bool>) -> Result<()> {
+        let mut sessions = self.sessions.write();
+        let session = sessions
+            .get_mut(id)
+            .ok_or_else(|| Error::SessionNotFound(id.to_string()))?;
+        session.set_wrap_mode(wrap);
+        Ok(())
+    }
+
     /// Get the number of active sessions.
     pub fn count(&self) -> usize {
         self.sessions.read().len()
     }
 
-    /// Clean up dead sessions.
-    pub fn cleanup_dead(&self) -> Vec<SessionId> {
-        let mut sessions = self.sessions.write();
-        let dead: Vec<SessionId> = sessions
-            .iter()
-            .filter(|(_, s)| !s.is_alive())
-            .map(|(id, _)| id.clone())
-            .collect();
+    /// Kill and remove sessions that have passed their idle timeout,
+    /// reporting `ProcessExit` with reason `IdleTimeout`.
+    pub fn reap_idle(&self) -> Vec<SessionId> {
+        let mut removed = {
+            let mut sessions = self.sessions.write();
+            let idle: Vec<SessionId> = sessions
+                .iter()
+                .filter(|(_, s)| s.is_idle())
+                .map(|(id, _)| id.clone())
+                .collect();
+            idle
+                .into_iter()
+                .filter_map(|id| sessions.remove(&id).map(|s| (id, s)))
+                .collect::<Vec<_>>()
+        };
 
-        for id in &dead {
-            if sessions.remove(id).is_some() {
-                let _ = self.event_sender.send(TerminalEvent::ProcessExit {
-                    session_id: id.clone(),
-                    exit_code: None,
-                });
+        for (id, session) in &mut removed {
+            log::info!("Session {} idle past its timeout, destroying", id);
+            session.kill_with_reason(ExitReason::IdleTimeout);
+            if session.exited_at.is_none() {
+                session.report_exit(None, None, ExitReason::IdleTimeout);
+            }
+        }
+
+        self.release(removed)
+    }
+
+    /// Report removed sessions as destroyed and drop them on a blocking
+    /// thread when a runtime is available, so tearing down their PTYs never
+    /// holds up the caller or the sessions lock.
+    fn release(&self, removed: Vec<(SessionId, Session)>) -> Vec<SessionId> {
+        let mut ids = Vec::with_capacity(removed.len());
+        let mut sessions = Vec::with_capacity(removed.len());
+        for (id, session) in removed {
+            self.remove_from_groups(&id);
+            let _ = self.event_sender.send(TerminalEvent::SessionDestroyed {
+                session_id: id.clone(),
+            });
+            ids.push(id);
+            sessions.push(session);
+        }
+
+        if !sessions.is_empty() {
+            match tokio::runtime::Handle::try_current() {
+                Ok(handle) => {
+                    handle.spawn_blocking(move || drop(sessions));
+                }
+                Err(_) => drop(sessions),
             }
         }
 
-        dead
+        ids
+    }
+
+    /// Report the exit of dead sessions and remove those whose
+    /// `exit_retention_ms` has passed, returning the removed ids.
+    ///
+    /// Output already read is processed and emitted before each
+    /// `ProcessExit`; anything still in flight lands on the retained
+    /// session afterwards. Exited sessions without a retention period stay,
+    /// with their final screen, until closed or destroyed.
+    pub fn cleanup_dead(&self) -> Vec<SessionId> {
+        let removed = {
+            let mut sessions = self.sessions.write();
+            let expired: Vec<SessionId> = sessions
+                .iter_mut()
+                .filter_map(|(id, s)| {
+                    (!s.is_alive() && s.poll_exit() && s.retention_expired()).then(|| id.clone())
+                })
+                .collect();
+            expired
+                .into_iter()
+                .filter_map(|id| sessions.remove(&id).map(|s| (id, s)))
+                .collect()
+        };
+
+        self.release(removed)
     }
 }
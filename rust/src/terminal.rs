@@ -1,50 +1,463 @@
 //! Terminal emulation using vt100.
 
-use crate::types::{Cell, CellAttributes, CellChange, Color, Cursor, CursorPosition, CursorShape, Row, Screen, Size};
+use crate::keys::KeyModes;
+use crate::search::{char_matches, fold, line_text, SearchIndex};
+use crate::sequences::{Csi, Scanner, Sequence};
+use crate::types::{BufferStats, C1Mode, Cell, CellAttributes, CellChange, Color, Cursor, CursorPosition, CursorShape, DeviceReports, DynamicColor, ImagePlacement, LinkSpan, Mark, MarkType, MouseEncoding, MouseMode, PixelSize, Row, Screen, ScrollRegionShift, SearchDirection, SearchMatch, Size, Viewport};
 use parking_lot::Mutex;
-use std::sync::Arc;
+use regex::Regex;
+use std::borrow::Cow;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::ops::Range;
+use std::sync::{Arc, OnceLock};
+
+/// Lines kept in scrollback.
+pub(crate) const SCROLLBACK_LINES: usize = 10_000;
+
+/// Base64 bytes of inline image data kept per terminal before the oldest
+/// images are evicted.
+const MAX_IMAGE_BYTES: usize = 32 * 1024 * 1024;
+
+/// OSC 8 link rows kept per terminal before the oldest are dropped.
+const MAX_LINKS: usize = 10_000;
+
+/// Cell size assumed for sizing images while the pixel size is unknown.
+const DEFAULT_CELL_PIXELS: (u16, u16) = (8, 16);
+
+/// Glyphs for bytes 0x5F–0x7E in the DEC special graphics charset.
+const DEC_GRAPHICS: [char; 32] = [
+    '\u{a0}', '◆', '▒', '␉', '␌', '␍', '␊', '°', '±', '␤', '␋', '┘', '┐', '┌', '└', '┼',
+    '⎺', '⎻', '─', '⎼', '⎽', '├', '┤', '┴', '┬', '│', '≤', '≥', 'π', '≠', '£', '·',
+];
+
+/// A dynamic color operation requested by the running program.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColorRequest {
+    /// OSC 10/11/12 with a color spec.
+    Set(DynamicColor, Color),
+    /// OSC 10/11/12 with `?`; `bel` records the terminator to reply with.
+    Query { slot: DynamicColor, bel: bool },
+    /// OSC 110/111/112.
+    Reset(DynamicColor),
+}
+
+/// Events vt100 reports through callbacks rather than screen state.
+#[derive(Default)]
+struct ParserEvents {
+    /// Audible bells since the last `process` call.
+    bells: usize,
+    /// Last window title set since the last `process` call.
+    title: Option<String>,
+}
+
+impl vt100::Callbacks for ParserEvents {
+    fn audible_bell(&mut self, _: &mut vt100::Screen) {
+        self.bells += 1;
+    }
+
+    fn set_window_title(&mut self, _: &mut vt100::Screen, title: &[u8]) {
+        self.title = Some(String::from_utf8_lossy(title).into_owned());
+    }
+}
+
+type Parser = vt100::Parser<ParserEvents>;
 
 /// A terminal emulator backed by vt100.
 pub struct Terminal {
-    parser: Arc<Mutex<vt100::Parser>>,
+    parser: Arc<Mutex<Parser>>,
     size: Size,
     title: String,
     /// Previous screen state for diffing.
     prev_contents: Arc<Mutex<Option<String>>>,
+    /// Whether the last `process` call rang the bell.
+    bell_rung: bool,
+    /// Scanner for sequences vt100 doesn't surface.
+    scanner: Scanner,
+    /// Pending OSC 10/11/12 requests for the session to apply.
+    color_requests: Vec<ColorRequest>,
+    /// Base64 payloads from OSC 52 clipboard writes.
+    clipboard_writes: Vec<String>,
+    /// Working directories reported through OSC 7.
+    cwd_reports: Vec<String>,
+    /// Replies to device queries, to be written back to the PTY.
+    responses: Vec<u8>,
+    /// Identity reported in device query replies.
+    device_reports: DeviceReports,
+    /// DECSTBM margins (top, bottom), zero-based inclusive; `None` is the full screen.
+    scroll_region: Option<(u16, u16)>,
+    /// Whether scrolls inside the region are diffed as shifts.
+    scroll_region_diffing: bool,
+    /// Previous grid, kept for shift detection when diffing is enabled.
+    prev_grid: Option<Vec<Row>>,
+    /// Shift detected by the last `process` call.
+    scroll_shift: Option<ScrollRegionShift>,
+    /// Minimum WCAG contrast enforced between cell foreground and background.
+    min_contrast: Option<f32>,
+    /// Replacement RGB values for indexed colors.
+    color_overrides: HashMap<u8, Color>,
+    /// Shown in place of cell content that can't be displayed.
+    replacement_char: char,
+    /// Tab stop flag per column, maintained from HTS/TBC.
+    tab_stops: Vec<bool>,
+    /// DECOM: cursor reports are relative to the scroll region.
+    origin_mode: bool,
+    /// Kitty keyboard protocol flag stacks for the main and alternate screens.
+    kitty_flags: [Vec<u8>; 2],
+    /// OSC 133 marks seen since the last `take_marks`.
+    marks: Vec<Mark>,
+    /// Where the last prompt ended (absolute row, column), from OSC 133;B.
+    prompt_end: Option<(u32, u16)>,
+    /// DECAWM as last set by the program.
+    autowrap: bool,
+    /// IRM (`CSI 4 h`): printed characters are meant to shift the rest of
+    /// the line right. Tracked for reporting; vt100 always replaces.
+    insert_mode: bool,
+    /// Forced DECAWM state, ignoring the program's requests.
+    wrap_override: Option<bool>,
+    /// Escape-sequence state carried across chunks while emulating no-wrap.
+    nowrap_state: NoWrapState,
+    /// Window size in pixels, used to convert image sizes to cells.
+    pixel_size: PixelSize,
+    /// Inline images from OSC 1337, oldest first.
+    images: Vec<InlineImage>,
+    /// Id for the next inline image.
+    next_image_id: u32,
+    /// OSC 8 link that has been opened and not yet closed.
+    open_link: Option<OpenLink>,
+    /// Rows of closed OSC 8 links, oldest first.
+    links: Vec<LinkRecord>,
+    /// How bytes 0x80–0x9F are interpreted.
+    c1_mode: C1Mode,
+    /// UTF-8 continuation bytes still expected, carried across chunks.
+    utf8_pending: u8,
+    /// S8C1T: replies use 8-bit C1 controls.
+    eight_bit_replies: bool,
+    /// Translate the DEC special graphics charset to line-drawing glyphs.
+    line_drawing: bool,
+    /// Charset designations and shift state, which vt100 ignores.
+    charsets: Charsets,
+    /// Cached scrollback text for `search`; `None` reads lines on every search.
+    search_index: Option<SearchIndex>,
+}
+
+/// An OSC 8 link being printed, from where the cursor was when it opened.
+struct OpenLink {
+    url: String,
+    id: Option<String>,
+    /// Absolute row (scrollback included).
+    row: u32,
+    col: u16,
+}
+
+/// One row of an OSC 8 link.
+struct LinkRecord {
+    /// Absolute row (scrollback included).
+    row: u32,
+    start_col: u16,
+    end_col: u16,
+    url: String,
+    id: Option<String>,
+    /// Printed on the alternate screen.
+    alternate: bool,
+}
+
+/// An inline image and its base64 payload.
+struct InlineImage {
+    placement: ImagePlacement,
+    data: String,
+}
+
+/// Minimal escape-sequence tracking, enough to tell printable characters
+/// from sequence bytes when emulating DECAWM off.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum NoWrapState {
+    Ground,
+    Escape,
+    Csi,
+    /// OSC/DCS/APC/PM/SOS payload, up to BEL or ST.
+    String,
+}
+
+/// G0/G1 designations and SO/SI shift state, tracked from the byte stream.
+#[derive(Debug, Clone, Copy, Default)]
+struct Charsets {
+    /// Whether G0 and G1 are designated DEC special graphics.
+    graphics: [bool; 2],
+    /// SO: G1 is invoked in place of G0.
+    shifted: bool,
+    state: CharsetState,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+enum CharsetState {
+    #[default]
+    Ground,
+    Escape,
+    /// `ESC (` or `ESC )`, waiting for the charset to designate as G0 or G1.
+    Designate(usize),
+    Csi,
+    /// OSC/DCS/APC/PM/SOS payload, up to BEL or ST.
+    String,
+}
+
+impl Charsets {
+    /// Advance over one byte, returning the glyph to print in its place if
+    /// it's a graphic character from the DEC special graphics set.
+    fn advance(&mut self, b: u8) -> Option<char> {
+        match self.state {
+            CharsetState::Ground => match b {
+                0x1b => self.state = CharsetState::Escape,
+                0x0e => self.shifted = true,
+                0x0f => self.shifted = false,
+                0x5f..=0x7e if self.graphics[self.shifted as usize] => {
+                    return Some(DEC_GRAPHICS[(b - 0x5f) as usize]);
+                }
+                _ => {}
+            },
+            // RIS goes back to ASCII in G0 and G1
+            CharsetState::Escape if b == b'c' => *self = Self::default(),
+            CharsetState::Escape => {
+                self.state = match b {
+                    b'(' => CharsetState::Designate(0),
+                    b')' => CharsetState::Designate(1),
+                    b'[' => CharsetState::Csi,
+                    b']' | b'P' | b'_' | b'^' | b'X' => CharsetState::String,
+                    0x1b | 0x20..=0x2f => CharsetState::Escape,
+                    _ => CharsetState::Ground,
+                };
+            }
+            CharsetState::Designate(slot) => {
+                self.graphics[slot] = b == b'0';
+                self.state = CharsetState::Ground;
+            }
+            CharsetState::Csi => {
+                if (0x40..=0x7e).contains(&b) {
+                    self.state = CharsetState::Ground;
+                }
+            }
+            CharsetState::String => match b {
+                0x07 => self.state = CharsetState::Ground,
+                0x1b => self.state = CharsetState::Escape,
+                _ => {}
+            },
+        }
+        None
+    }
 }
 
 impl Terminal {
     /// Create a new terminal with the given size.
     pub fn new(cols: u16, rows: u16) -> Self {
-        let parser = vt100::Parser::new(rows, cols, 10000); // 10k scrollback
+        let parser = Parser::new_with_callbacks(rows, cols, SCROLLBACK_LINES, ParserEvents::default());
         Self {
             parser: Arc::new(Mutex::new(parser)),
             size: Size { cols, rows },
             title: String::new(),
             prev_contents: Arc::new(Mutex::new(None)),
+            bell_rung: false,
+            scanner: Scanner::new(),
+            color_requests: Vec::new(),
+            clipboard_writes: Vec::new(),
+            cwd_reports: Vec::new(),
+            responses: Vec::new(),
+            device_reports: DeviceReports::default(),
+            scroll_region: None,
+            scroll_region_diffing: true,
+            prev_grid: None,
+            scroll_shift: None,
+            min_contrast: None,
+            color_overrides: HashMap::new(),
+            replacement_char: char::REPLACEMENT_CHARACTER,
+            tab_stops: default_tab_stops(0, cols),
+            origin_mode: false,
+            kitty_flags: [Vec::new(), Vec::new()],
+            marks: Vec::new(),
+            prompt_end: None,
+            autowrap: true,
+            insert_mode: false,
+            wrap_override: None,
+            nowrap_state: NoWrapState::Ground,
+            pixel_size: PixelSize::default(),
+            images: Vec::new(),
+            next_image_id: 1,
+            open_link: None,
+            links: Vec::new(),
+            c1_mode: C1Mode::Utf8,
+            utf8_pending: 0,
+            eight_bit_replies: false,
+            line_drawing: true,
+            charsets: Charsets::default(),
+            search_index: None,
+        }
+    }
+
+    /// Translate text printed in the DEC special graphics charset (`ESC ( 0`)
+    /// to the Unicode line-drawing glyphs it stands for. On by default.
+    pub fn set_line_drawing(&mut self, enabled: bool) {
+        self.line_drawing = enabled;
+        self.charsets = Charsets::default();
+    }
+
+    /// Keep the scrollback's text for `search` instead of reading it back
+    /// from the emulator every time, at the cost of roughly twice its size
+    /// in memory.
+    pub fn set_search_index(&mut self, enabled: bool) {
+        self.search_index = enabled.then(SearchIndex::default);
+    }
+
+    /// Set the window size in pixels, used to size inline images in cells.
+    pub fn set_pixel_size(&mut self, pixels: PixelSize) {
+        self.pixel_size = pixels;
+    }
+
+    /// Force line wrapping on or off regardless of DECAWM; `None` follows
+    /// the program.
+    ///
+    /// vt100 doesn't reflow on resize, so rows truncated while wrapping was
+    /// off stay truncated when the terminal is widened.
+    pub fn set_wrap_override(&mut self, wrap: Option<bool>) {
+        self.wrap_override = wrap;
+    }
+
+    /// Whether text currently wraps at the right margin.
+    pub fn wraps(&self) -> bool {
+        self.wrap_override.unwrap_or(self.autowrap)
+    }
+
+    /// Enforce a minimum foreground/background contrast ratio when rendering cells.
+    pub fn set_min_contrast(&mut self, ratio: Option<f32>) {
+        self.min_contrast = ratio;
+        // Force a full refresh so existing cells pick up the adjustment
+        self.invalidate();
+    }
+
+    /// Character shown in place of undisplayable cell content; U+FFFD by default.
+    pub fn set_replacement_char(&mut self, replacement: char) {
+        self.replacement_char = replacement;
+        self.invalidate();
+    }
+
+    /// Render indexed colors with the given RGB values instead of the defaults.
+    pub fn set_color_overrides(&mut self, overrides: HashMap<u8, Color>) {
+        self.color_overrides = overrides;
+        self.invalidate();
+    }
+
+    /// Forget the previous screen so the next `process` reports every cell.
+    pub fn invalidate(&mut self) {
+        *self.prev_contents.lock() = None;
+        self.prev_grid = None;
+    }
+
+    /// Enable or disable scroll-region shift detection in diffs.
+    pub fn set_scroll_region_diffing(&mut self, enabled: bool) {
+        self.scroll_region_diffing = enabled;
+        if !enabled {
+            self.prev_grid = None;
+        }
+    }
+
+    /// Set the identity reported in device query replies.
+    pub fn set_device_reports(&mut self, reports: DeviceReports) {
+        self.device_reports = reports;
+    }
+
+    /// Set how bytes 0x80–0x9F are interpreted.
+    pub fn set_c1_mode(&mut self, mode: C1Mode) {
+        self.c1_mode = mode;
+        self.utf8_pending = 0;
+        if mode == C1Mode::Utf8 {
+            self.eight_bit_replies = false;
         }
     }
 
     /// Process input data from PTY.
     pub fn process(&mut self, data: &[u8]) -> Vec<CellChange> {
-        let mut parser = self.parser.lock();
-        parser.process(data);
+        let data = self.translate_c1(data);
+        let data = self.translate_charsets(&data);
+        let data = &data[..];
+        let sequences = self.scanner.feed(data);
+        let parser = self.parser.clone();
+        let mut parser = parser.lock();
 
-        let screen = parser.screen();
+        // Feed vt100 up to each side-channel sequence so handlers see the
+        // screen state at the point the sequence was emitted
+        let mut start = 0;
+        for (end, seq) in sequences {
+            if seq == Sequence::Control(0x09) {
+                // vt100 only knows fixed 8-column stops; move the cursor ourselves
+                self.feed(&mut parser, &data[start..end - 1]);
+                let (_, col) = parser.screen().cursor_position();
+                let stop = self.next_tab_stop(col);
+                parser.process(format!("\x1b[{}G", stop + 1).as_bytes());
+                start = end;
+                continue;
+            }
+            self.feed(&mut parser, &data[start..end]);
+            start = end;
+            if let Sequence::Osc { data, .. } = &seq {
+                if data.starts_with(b"133;") {
+                    self.handle_prompt_mark(&mut parser, data);
+                    continue;
+                }
+                if data.starts_with(b"1337;File=") {
+                    self.handle_inline_image(&mut parser, data);
+                    continue;
+                }
+                if data.starts_with(b"8;") {
+                    self.handle_hyperlink(&mut parser, data);
+                    continue;
+                }
+            }
+            self.handle_sequence(parser.screen(), seq);
+        }
+        self.feed(&mut parser, &data[start..]);
+        if let Some(index) = &mut self.search_index {
+            // A full scrollback drops its oldest lines unannounced
+            if buffer_offset(&mut parser) >= SCROLLBACK_LINES {
+                index.invalidate();
+            }
+        }
+        if !self.images.is_empty() {
+            self.evict_images(&mut parser);
+        }
+        if !self.links.is_empty() {
+            self.evict_links(&mut parser);
+        }
 
-        // Update title if changed
-        let title_str = screen.title();
-        if !title_str.is_empty() {
-            self.title = title_str.to_string();
+        let events = std::mem::take(parser.callbacks_mut());
+        self.bell_rung = events.bells > 0;
+        if let Some(title) = events.title.filter(|title| !title.is_empty()) {
+            self.title = title;
         }
 
+        let screen = parser.screen();
+
         // Get current contents for diffing
         let current = screen.contents();
         let mut prev = self.prev_contents.lock();
 
+        self.scroll_shift = None;
         let changes = if prev.as_ref() != Some(&current) {
-            // Content changed, compute full diff
-            self.compute_changes(screen)
+            if self.scroll_region_diffing {
+                // Content changed; send only what a region scroll can't explain
+                let grid = self.grid(screen);
+                let changes = match self.detect_region_shift(&grid) {
+                    Some((shift, shifted)) => {
+                        self.scroll_shift = Some(shift);
+                        diff_rows(&shifted, &grid)
+                    }
+                    None => diff_rows(&[], &grid),
+                };
+                // Only keep the grid around while a region is active
+                self.prev_grid = self.scroll_region.map(|_| grid);
+                changes
+            } else {
+                // Content changed, compute full diff
+                self.compute_changes(screen)
+            }
         } else {
             Vec::new()
         };
@@ -53,6 +466,52 @@ impl Terminal {
         changes
     }
 
+    /// Find a shift of the scroll region that explains the new grid.
+    ///
+    /// Returns the shift and the previous grid with the shift applied; rows
+    /// exposed by the shift are left empty so they always diff as changed.
+    fn detect_region_shift(&self, grid: &[Row]) -> Option<(ScrollRegionShift, Vec<Row>)> {
+        let (top, bottom) = self.scroll_region?;
+        let prev = self.prev_grid.as_ref()?;
+        let (t, b) = (top as usize, bottom as usize);
+        if prev.len() != grid.len() || b >= grid.len() || t >= b {
+            return None;
+        }
+
+        for n in 1..=(b - t) {
+            if grid[t..=b - n] == prev[t + n..=b] {
+                let mut shifted = prev.clone();
+                shifted[t..=b - n].clone_from_slice(&prev[t + n..=b]);
+                for row in &mut shifted[b - n + 1..=b] {
+                    row.clear();
+                }
+                let shift = ScrollRegionShift { top, bottom, lines: n as i16 };
+                return Some((shift, shifted));
+            }
+            if grid[t + n..=b] == prev[t..=b - n] {
+                let mut shifted = prev.clone();
+                shifted[t + n..=b].clone_from_slice(&prev[t..=b - n]);
+                for row in &mut shifted[t..t + n] {
+                    row.clear();
+                }
+                let shift = ScrollRegionShift { top, bottom, lines: -(n as i16) };
+                return Some((shift, shifted));
+            }
+        }
+
+        None
+    }
+
+    fn grid(&self, screen: &vt100::Screen) -> Vec<Row> {
+        (0..self.size.rows)
+            .map(|row| {
+                (0..self.size.cols)
+                    .map(|col| self.cell_at(screen, row, col))
+                    .collect()
+            })
+            .collect()
+    }
+
     fn compute_changes(&self, screen: &vt100::Screen) -> Vec<CellChange> {
         let mut changes = Vec::new();
 
@@ -73,7 +532,8 @@ impl Terminal {
 
     /// Get the full screen state.
     pub fn get_screen(&self) -> Screen {
-        let parser = self.parser.lock();
+        let mut parser = self.parser.lock();
+        let offset = buffer_offset(&mut parser) as u32;
         let screen = parser.screen();
 
         log::debug!("Terminal::get_screen: size={}x{}, parser_size={}x{}",
@@ -93,8 +553,58 @@ impl Terminal {
             cursor: self.get_cursor_from_screen(screen),
             size: self.size,
             scrollback_len: screen.scrollback() as u32,
-            title: screen.title().to_string(),
+            title: self.title.clone(),
+            links: self.screen_links(screen, offset),
+        }
+    }
+
+    /// Links on the visible screen: OSC 8 spans, then URLs found in the
+    /// text that don't overlap them.
+    fn screen_links(&self, screen: &vt100::Screen, offset: u32) -> Vec<LinkSpan> {
+        let alternate = screen.alternate_screen();
+        let rows = offset..offset + self.size.rows as u32;
+        let mut links: Vec<LinkSpan> = self
+            .links
+            .iter()
+            .filter(|link| link.alternate == alternate && rows.contains(&link.row))
+            .map(|link| LinkSpan {
+                row: (link.row - offset) as u16,
+                start_col: link.start_col,
+                end_col: link.end_col,
+                url: link.url.clone(),
+                id: link.id.clone(),
+            })
+            .collect();
+
+        let explicit = links.len();
+        for row in 0..self.size.rows {
+            let (text, columns) = line_text(screen, row, self.size.cols);
+            if !text.contains("://") {
+                continue;
+            }
+            for found in url_pattern().find_iter(&text) {
+                let url = found.as_str().trim_end_matches(['.', ',', ';', ':', '!', '?', ')', ']', '}', '\'', '"']);
+                let first = text[..found.start()].chars().count();
+                let (Some(&start_col), Some(&end_col)) =
+                    (columns.get(first), columns.get(first + url.chars().count()))
+                else {
+                    continue;
+                };
+                let overlaps = links[..explicit]
+                    .iter()
+                    .any(|link| link.row == row && link.start_col < end_col && start_col < link.end_col);
+                if !overlaps {
+                    links.push(LinkSpan {
+                        row,
+                        start_col,
+                        end_col,
+                        url: url.to_string(),
+                        id: None,
+                    });
+                }
+            }
         }
+        links
     }
 
     /// Get scrollback lines.
@@ -103,24 +613,349 @@ impl Terminal {
         let screen = parser.screen();
         let scrollback = screen.scrollback();
 
-        let start = scrollback.saturating_sub(lines as usize);
+        let _start = scrollback.saturating_sub(lines as usize);
         let _end = scrollback;
 
-        // vt100 0.15 doesn't have scrollback_cell, return empty for now
+        // vt100 doesn't have scrollback_cell, return empty for now
         // In production, we'd use screen.rows_formatted() or similar
         Vec::new()
     }
 
+    /// Rows visible with buffer line `top_line` at the top of the viewport,
+    /// clamped to the scrollback; `None` shows the bottom. The parser's own
+    /// viewport is left as it was.
+    pub fn viewport(&self, top_line: Option<usize>) -> Viewport {
+        let mut parser = self.parser.lock();
+        let saved = parser.screen().scrollback();
+        let len = scrollback_len(&mut parser);
+        let top = top_line.unwrap_or(len).min(len);
+        parser.screen_mut().set_scrollback(len - top);
+        let rows = self.grid(parser.screen());
+        parser.screen_mut().set_scrollback(saved);
+
+        Viewport {
+            top_line: top as u32,
+            scrollback_len: len as u32,
+            rows,
+        }
+    }
+
+    /// Export the whole buffer (scrollback and screen) as ANSI-colored text.
+    pub fn dump_ansi(&self) -> String {
+        self.dump_ansi_range(0, usize::MAX)
+    }
+
+    /// Export `count` lines starting at `start_line` as ANSI-colored text.
+    ///
+    /// Lines are indexed over the whole buffer, with 0 the oldest retained
+    /// scrollback line. Each line starts from a reset SGR state, so ranges
+    /// can be concatenated without colors bleeding between them.
+    pub fn dump_ansi_range(&self, start_line: usize, count: usize) -> String {
+        let mut parser = self.parser.lock();
+        let mut out = Vec::new();
+
+        self.visit_lines(&mut parser, start_line, count, |screen, rows| {
+            let formatted = screen.rows_formatted(0, self.size.cols);
+            for row in formatted.skip(rows.start).take(rows.len()) {
+                out.extend_from_slice(b"\x1b[m");
+                out.extend_from_slice(&row);
+                out.extend_from_slice(b"\x1b[m\r\n");
+            }
+        });
+
+        String::from_utf8_lossy(&out).into_owned()
+    }
+
+    /// Fingerprint of the visible grid, covering each cell's text, colors
+    /// and attributes.
+    ///
+    /// Stable for a given build, so it suits change detection and test
+    /// assertions but shouldn't be persisted across versions.
+    pub fn screen_hash(&self) -> u64 {
+        let parser = self.parser.lock();
+        let mut hasher = DefaultHasher::new();
+        self.size.cols.hash(&mut hasher);
+        self.size.rows.hash(&mut hasher);
+        self.grid(parser.screen()).hash(&mut hasher);
+        hasher.finish()
+    }
+
+    /// Visible screen as plain text, one right-trimmed line per row.
+    ///
+    /// Wide characters appear once; their continuation cells are skipped.
+    pub fn screen_text(&self) -> String {
+        let parser = self.parser.lock();
+        let lines: Vec<String> = parser
+            .screen()
+            .rows(0, self.size.cols)
+            .map(|row| row.trim_end().to_string())
+            .collect();
+        lines.join("\n")
+    }
+
+    /// Text between two visible-screen positions, inclusive of `end`.
+    ///
+    /// Linear selections rejoin soft-wrapped rows into one line; rectangular
+    /// selections take the same column span from every row.
+    pub fn selection_text(&self, start: CursorPosition, end: CursorPosition, rectangular: bool) -> String {
+        let (start, end) = if (start.row, start.col) <= (end.row, end.col) {
+            (start, end)
+        } else {
+            (end, start)
+        };
+        let parser = self.parser.lock();
+        let screen = parser.screen();
+
+        if rectangular {
+            let left = start.col.min(end.col);
+            let width = start.col.max(end.col) - left + 1;
+            let lines: Vec<String> = screen
+                .rows(left, width)
+                .skip(start.row as usize)
+                .take((end.row - start.row) as usize + 1)
+                .map(|row| row.trim_end().to_string())
+                .collect();
+            return lines.join("\n");
+        }
+
+        screen.contents_between(start.row, start.col, end.row, end.col.saturating_add(1))
+    }
+
+    /// Export the whole buffer (scrollback and screen) as plain text.
+    ///
+    /// Rows that soft-wrapped are rejoined into their logical line unless
+    /// `preserve_wrapping` is set, so copied commands and long log lines
+    /// don't pick up line breaks the program never wrote.
+    pub fn export_text(&self, preserve_wrapping: bool) -> String {
+        self.text_range(0, usize::MAX, preserve_wrapping)
+    }
+
+    /// Number of lines in the buffer: retained scrollback plus the screen.
+    pub fn buffer_lines(&self) -> usize {
+        buffer_offset(&mut self.parser.lock()) + self.size.rows as usize
+    }
+
+    /// Export `count` buffer lines starting at `start_line` as plain text,
+    /// indexed and wrapped as in `export_text`.
+    pub fn text_range(&self, start_line: usize, count: usize, preserve_wrapping: bool) -> String {
+        let mut parser = self.parser.lock();
+        let mut out = String::new();
+
+        self.visit_lines(&mut parser, start_line, count, |screen, rows| {
+            let texts = screen.rows(0, self.size.cols).skip(rows.start);
+            for (row, text) in rows.zip(texts) {
+                if !preserve_wrapping && screen.row_wrapped(row as u16) {
+                    out.push_str(&text);
+                } else {
+                    out.push_str(text.trim_end());
+                    out.push('\n');
+                }
+            }
+        });
+
+        out
+    }
+
+    /// Find the next occurrence of `query` in the buffer after `from` (a
+    /// line and column), or the previous one before it when searching
+    /// backward. Without `from` the search starts at the top or bottom of
+    /// the buffer.
+    ///
+    /// Plain substring match within each row, so matches don't span
+    /// soft-wrapped rows.
+    pub fn search(
+        &mut self,
+        query: &str,
+        from: Option<(usize, u16)>,
+        direction: SearchDirection,
+        case_sensitive: bool,
+    ) -> Option<SearchMatch> {
+        if query.is_empty() {
+            return None;
+        }
+        let needle = if case_sensitive { query.to_string() } else { fold(query) };
+        let needle_chars = needle.chars().count();
+        let cols = self.size.cols;
+        let parser = self.parser.clone();
+        let mut parser = parser.lock();
+
+        // Catch the index up with lines that scrolled off since the last search
+        let mut index = self.search_index.take();
+        let indexed = match &mut index {
+            Some(index) => {
+                let scrollback = buffer_offset(&mut parser);
+                let valid = index.valid_lines(scrollback);
+                let mut texts = Vec::new();
+                self.visit_lines(&mut parser, valid, scrollback - valid, |screen, rows| {
+                    texts.extend(rows.map(|row| line_text(screen, row as u16, cols).0));
+                });
+                texts.into_iter().for_each(|text| index.push(text));
+                scrollback
+            }
+            None => 0,
+        };
+
+        // Lines past the index are read live
+        let mut live = Vec::new();
+        self.visit_lines(&mut parser, indexed, usize::MAX, |screen, rows| {
+            live.extend(rows.map(|row| {
+                let text = line_text(screen, row as u16, cols).0;
+                if case_sensitive { text } else { fold(&text) }
+            }));
+        });
+        let line_at = |line: usize| match line.checked_sub(indexed) {
+            Some(i) => live[i].as_str(),
+            None => index.as_ref().map_or("", |index| index.line(line, case_sensitive)),
+        };
+
+        let total = indexed + live.len();
+        let start = from.map_or(0, |(line, _)| line);
+        let lines: Box<dyn Iterator<Item = usize>> = match (direction, from) {
+            (SearchDirection::Forward, _) => Box::new(start..total),
+            (SearchDirection::Backward, Some(_)) => Box::new((0..(start + 1).min(total)).rev()),
+            (SearchDirection::Backward, None) => Box::new((0..total).rev()),
+        };
+
+        let mut found = None;
+        for line in lines {
+            let offsets = char_matches(line_at(line), &needle);
+            if offsets.is_empty() {
+                continue;
+            }
+            let mut columns = Vec::new();
+            self.visit_lines(&mut parser, line, 1, |screen, mut rows| {
+                if let Some(row) = rows.next() {
+                    columns = line_text(screen, row as u16, cols).1;
+                }
+            });
+
+            let mut hits = offsets
+                .into_iter()
+                .filter_map(|i| {
+                    Some(SearchMatch {
+                        line: line as u32,
+                        start_col: *columns.get(i)?,
+                        end_col: *columns.get(i + needle_chars)?,
+                    })
+                })
+                .filter(|hit| match from {
+                    Some((from_line, col)) if from_line == line => match direction {
+                        SearchDirection::Forward => hit.start_col > col,
+                        SearchDirection::Backward => hit.start_col < col,
+                    },
+                    _ => true,
+                });
+            found = match direction {
+                SearchDirection::Forward => hits.next(),
+                SearchDirection::Backward => hits.next_back(),
+            };
+            if found.is_some() {
+                break;
+            }
+        }
+
+        self.search_index = index;
+        found
+    }
+
+    /// Walk `count` buffer lines starting at `start_line` a viewport at a
+    /// time, calling `f` with the screen and the visible rows to read.
+    ///
+    /// Lines are indexed over the whole buffer, with 0 the oldest retained
+    /// scrollback line. The viewport offset is restored afterwards.
+    fn visit_lines<F>(&self, parser: &mut Parser, start_line: usize, count: usize, mut f: F)
+    where
+        F: FnMut(&vt100::Screen, Range<usize>),
+    {
+        let saved = parser.screen().scrollback();
+        let scrollback_len = scrollback_len(parser);
+        let rows = self.size.rows as usize;
+        let end = start_line.saturating_add(count).min(scrollback_len + rows);
+
+        let mut line = start_line;
+        while line < end {
+            // Scroll so `line` is in view, then walk the visible rows
+            let offset = scrollback_len.saturating_sub(line);
+            parser.screen_mut().set_scrollback(offset);
+            let first = scrollback_len - offset;
+
+            let from = line - first;
+            let to = (end - first).min(rows);
+            f(parser.screen(), from..to);
+            line = first + rows.max(1);
+        }
+
+        parser.screen_mut().set_scrollback(saved);
+    }
+
     /// Resize the terminal.
     pub fn resize(&mut self, cols: u16, rows: u16) {
         self.size = Size { cols, rows };
         let mut parser = self.parser.lock();
-        parser.set_size(rows, cols);
+        parser.screen_mut().set_size(rows, cols);
 
         // Clear prev_contents cache to force a full refresh after resize
         // This ensures the frontend gets the complete new screen state
         let mut prev = self.prev_contents.lock();
         *prev = None;
+
+        // DECSTBM margins reset on resize
+        self.scroll_region = None;
+        if let Some(index) = &mut self.search_index {
+            index.invalidate();
+        }
+        self.prev_grid = None;
+
+        // Keep existing stops; new columns get the default every-8 stops
+        let old = self.tab_stops.len() as u16;
+        self.tab_stops.truncate(cols as usize);
+        if cols > old {
+            self.tab_stops.extend(default_tab_stops(old, cols));
+        }
+    }
+
+    /// Columns with a tab stop set, in ascending order.
+    pub fn tab_stops(&self) -> Vec<u16> {
+        self.tab_stops
+            .iter()
+            .enumerate()
+            .filter(|(_, set)| **set)
+            .map(|(col, _)| col as u16)
+            .collect()
+    }
+
+    /// Get a single visible cell; out-of-range positions give an empty cell.
+    pub fn get_cell(&self, row: u16, col: u16) -> Cell {
+        let parser = self.parser.lock();
+        self.cell_at(parser.screen(), row, col)
+    }
+
+    /// Cells of the visible screen from `top`/`left` to `bottom`/`right`
+    /// inclusive, clamped to the grid. Edges that would split a wide
+    /// character are widened to include all of it.
+    pub fn region(&self, top: u16, left: u16, bottom: u16, right: u16) -> Vec<Row> {
+        let Size { cols, rows } = self.size;
+        if cols == 0 || rows == 0 {
+            return Vec::new();
+        }
+        let (top, bottom) = (top.min(bottom).min(rows - 1), top.max(bottom).min(rows - 1));
+        let (mut left, mut right) = (left.min(right).min(cols - 1), left.max(right).min(cols - 1));
+
+        let parser = self.parser.lock();
+        let screen = parser.screen();
+        let wide_at = |row: u16, col: u16, check: fn(&vt100::Cell) -> bool| {
+            screen.cell(row, col).is_some_and(check)
+        };
+        if left > 0 && (top..=bottom).any(|row| wide_at(row, left, vt100::Cell::is_wide_continuation)) {
+            left -= 1;
+        }
+        if right + 1 < cols && (top..=bottom).any(|row| wide_at(row, right, vt100::Cell::is_wide)) {
+            right += 1;
+        }
+
+        (top..=bottom)
+            .map(|row| (left..=right).map(|col| self.cell_at(screen, row, col)).collect())
+            .collect()
     }
 
     /// Get cursor state.
@@ -129,6 +964,299 @@ impl Terminal {
         self.get_cursor_from_screen(parser.screen())
     }
 
+    /// Take the scroll-region shift detected by the last `process` call.
+    pub fn take_scroll_shift(&mut self) -> Option<ScrollRegionShift> {
+        self.scroll_shift.take()
+    }
+
+    /// Get the DECSTBM scroll region (top, bottom), if narrower than the screen.
+    pub fn scroll_region(&self) -> Option<(u16, u16)> {
+        self.scroll_region
+    }
+
+    /// Take pending replies to device queries.
+    pub fn take_responses(&mut self) -> Vec<u8> {
+        let responses = std::mem::take(&mut self.responses);
+        if !self.eight_bit_replies {
+            return responses;
+        }
+
+        // ESC Fe becomes the single C1 byte, e.g. ESC [ -> 0x9B
+        let mut out = Vec::with_capacity(responses.len());
+        let mut bytes = responses.iter().copied().peekable();
+        while let Some(b) = bytes.next() {
+            match bytes.peek() {
+                Some(&next) if b == 0x1b && (0x40..=0x5f).contains(&next) => {
+                    out.push(next + 0x40);
+                    bytes.next();
+                }
+                _ => out.push(b),
+            }
+        }
+        out
+    }
+
+    /// Rewrite 8-bit C1 controls as their 7-bit `ESC Fe` form, which is all
+    /// vt100 and the scanner understand. Bytes continuing a UTF-8 character
+    /// are left alone.
+    fn translate_c1<'a>(&mut self, data: &'a [u8]) -> Cow<'a, [u8]> {
+        if self.c1_mode == C1Mode::Utf8 {
+            return Cow::Borrowed(data);
+        }
+
+        let mut out = Vec::with_capacity(data.len());
+        for &b in data {
+            if self.utf8_pending > 0 && (0x80..=0xbf).contains(&b) {
+                self.utf8_pending -= 1;
+                out.push(b);
+                continue;
+            }
+            self.utf8_pending = match b {
+                0xc2..=0xdf => 1,
+                0xe0..=0xef => 2,
+                0xf0..=0xf4 => 3,
+                _ => 0,
+            };
+            if (0x80..=0x9f).contains(&b) {
+                out.extend_from_slice(&[0x1b, b - 0x40]);
+            } else {
+                out.push(b);
+            }
+        }
+        Cow::Owned(out)
+    }
+
+    /// Replace characters printed while the DEC special graphics set is
+    /// active with their line-drawing glyphs; vt100 has no charset support.
+    fn translate_charsets<'a>(&mut self, data: &'a [u8]) -> Cow<'a, [u8]> {
+        if !self.line_drawing {
+            return Cow::Borrowed(data);
+        }
+
+        let mut out: Option<Vec<u8>> = None;
+        for (i, &b) in data.iter().enumerate() {
+            match self.charsets.advance(b) {
+                Some(glyph) => {
+                    let out = out.get_or_insert_with(|| data[..i].to_vec());
+                    out.extend_from_slice(glyph.encode_utf8(&mut [0; 4]).as_bytes());
+                }
+                None => {
+                    if let Some(out) = &mut out {
+                        out.push(b);
+                    }
+                }
+            }
+        }
+        out.map_or(Cow::Borrowed(data), Cow::Owned)
+    }
+
+    /// Feed bytes to vt100, emulating DECAWM off when wrapping is disabled.
+    ///
+    /// vt100 always wraps, so before each printable character at the right
+    /// margin the cursor is pinned back to the last column, making the
+    /// character overwrite it instead of wrapping.
+    fn feed(&mut self, parser: &mut Parser, bytes: &[u8]) {
+        if self.wraps() {
+            self.nowrap_state = NoWrapState::Ground;
+            parser.process(bytes);
+            return;
+        }
+
+        let mut flushed = 0;
+        for (i, &b) in bytes.iter().enumerate() {
+            let printable = match self.nowrap_state {
+                NoWrapState::Ground => match b {
+                    0x1b => {
+                        self.nowrap_state = NoWrapState::Escape;
+                        false
+                    }
+                    // ASCII or a UTF-8 lead byte
+                    0x20..=0x7e | 0xc0..=0xff => true,
+                    _ => false,
+                },
+                NoWrapState::Escape => {
+                    self.nowrap_state = match b {
+                        b'[' => NoWrapState::Csi,
+                        b']' | b'P' | b'_' | b'^' | b'X' => NoWrapState::String,
+                        0x20..=0x2f => NoWrapState::Escape,
+                        _ => NoWrapState::Ground,
+                    };
+                    false
+                }
+                NoWrapState::Csi => {
+                    if (0x40..=0x7e).contains(&b) {
+                        self.nowrap_state = NoWrapState::Ground;
+                    }
+                    false
+                }
+                NoWrapState::String => {
+                    match b {
+                        0x07 => self.nowrap_state = NoWrapState::Ground,
+                        0x1b => self.nowrap_state = NoWrapState::Escape,
+                        _ => {}
+                    }
+                    false
+                }
+            };
+
+            if printable {
+                parser.process(&bytes[flushed..i]);
+                flushed = i;
+                let (_, col) = parser.screen().cursor_position();
+                if col + 1 >= self.size.cols {
+                    parser.process(format!("\x1b[{}G", self.size.cols).as_bytes());
+                }
+            }
+        }
+        parser.process(&bytes[flushed..]);
+    }
+
+    /// Placements of the inline images still in the buffer, oldest first.
+    pub fn images(&self) -> Vec<ImagePlacement> {
+        self.images.iter().map(|image| image.placement).collect()
+    }
+
+    /// Base64 payload of an inline image, if it hasn't been evicted.
+    pub fn image_data(&self, id: u32) -> Option<String> {
+        self.images
+            .iter()
+            .find(|image| image.placement.id == id)
+            .map(|image| image.data.clone())
+    }
+
+    /// Estimate the memory held by the scrollback, screen grids and images.
+    pub fn buffer_stats(&self) -> BufferStats {
+        let scrollback_lines = buffer_offset(&mut self.parser.lock()) as u64;
+        let row_bytes = (self.size.cols as usize * std::mem::size_of::<vt100::Cell>()) as u64;
+        BufferStats {
+            scrollback_lines,
+            scrollback_bytes: scrollback_lines * row_bytes,
+            image_bytes: self.images.iter().map(|image| image.data.len() as u64).sum(),
+            // vt100 keeps the alternate grid allocated alongside the primary
+            grid_bytes: 2 * self.size.rows as u64 * row_bytes,
+        }
+    }
+
+    /// Drop images past the end of the buffer (the scrollback was cleared),
+    /// then the oldest images until the payloads fit in `MAX_IMAGE_BYTES`.
+    ///
+    /// vt100 doesn't report lines falling off a full scrollback, so the
+    /// budget stands in for that: the oldest images are the first to scroll out.
+    fn evict_images(&mut self, parser: &mut Parser) {
+        let end = (buffer_offset(parser) + self.size.rows as usize) as u32;
+        self.images.retain(|image| image.placement.row < end);
+
+        let mut total: usize = self.images.iter().map(|image| image.data.len()).sum();
+        let excess = self
+            .images
+            .iter()
+            .take_while(|image| {
+                let over = total > MAX_IMAGE_BYTES;
+                total -= image.data.len();
+                over
+            })
+            .count();
+        self.images.drain(..excess);
+    }
+
+    /// Drop links past the end of the buffer (the scrollback was cleared),
+    /// then the oldest past `MAX_LINKS`.
+    fn evict_links(&mut self, parser: &mut Parser) {
+        let alternate = parser.screen().alternate_screen();
+        let end = (buffer_offset(parser) + self.size.rows as usize) as u32;
+        self.links.retain(|link| link.alternate != alternate || link.row < end);
+        let excess = self.links.len().saturating_sub(MAX_LINKS);
+        self.links.drain(..excess);
+    }
+
+    /// Take shell integration marks recorded from OSC 133.
+    pub fn take_marks(&mut self) -> Vec<Mark> {
+        std::mem::take(&mut self.marks)
+    }
+
+    /// Take pending OSC 52 clipboard writes (base64 payloads).
+    pub fn take_clipboard_writes(&mut self) -> Vec<String> {
+        std::mem::take(&mut self.clipboard_writes)
+    }
+
+    /// Take pending OSC 7 working directory reports.
+    pub fn take_cwd_reports(&mut self) -> Vec<String> {
+        std::mem::take(&mut self.cwd_reports)
+    }
+
+    /// Take pending OSC 10/11/12 color requests.
+    pub fn take_color_requests(&mut self) -> Vec<ColorRequest> {
+        std::mem::take(&mut self.color_requests)
+    }
+
+    /// Modes that affect key encoding.
+    pub fn key_modes(&self) -> KeyModes {
+        let parser = self.parser.lock();
+        let screen = parser.screen();
+        KeyModes {
+            application_cursor: screen.application_cursor(),
+            kitty_flags: self.kitty_flags[screen.alternate_screen() as usize]
+                .last()
+                .copied()
+                .unwrap_or(0),
+        }
+    }
+
+    /// Sequences with the effect of DECSTR, which vt100 doesn't implement.
+    ///
+    /// Clears origin mode, margins, SGR, the saved cursor, cursor keys and
+    /// keypad modes, mouse reporting, bracketed paste and insert mode, and
+    /// shows the cursor with autowrap on. The cursor stays where it was and
+    /// the screen and scrollback are untouched.
+    pub fn soft_reset_sequence(&self) -> Vec<u8> {
+        let (row, col) = self.parser.lock().screen().cursor_position();
+        format!(
+            concat!(
+                "\x1b[?6l\x1b[r\x1b[m\x1b7",
+                "\x1b[?1l\x1b>\x1b[4l\x1b[?7h\x1b[?25h\x1b[?2004l",
+                "\x1b[?9l\x1b[?1000l\x1b[?1002l\x1b[?1003l\x1b[?1005l\x1b[?1006l",
+                "\x1b[{};{}H"
+            ),
+            row + 1,
+            col + 1
+        )
+        .into_bytes()
+    }
+
+    /// Whether the alternate screen is active.
+    pub fn alternate_screen(&self) -> bool {
+        self.parser.lock().screen().alternate_screen()
+    }
+
+    /// Mouse reporting mode and encoding requested by the program.
+    pub fn mouse_mode(&self) -> (MouseMode, MouseEncoding) {
+        let parser = self.parser.lock();
+        let screen = parser.screen();
+        let mode = match screen.mouse_protocol_mode() {
+            vt100::MouseProtocolMode::None => MouseMode::None,
+            vt100::MouseProtocolMode::Press => MouseMode::Press,
+            vt100::MouseProtocolMode::PressRelease => MouseMode::PressRelease,
+            vt100::MouseProtocolMode::ButtonMotion => MouseMode::ButtonMotion,
+            vt100::MouseProtocolMode::AnyMotion => MouseMode::AnyMotion,
+        };
+        let encoding = match screen.mouse_protocol_encoding() {
+            vt100::MouseProtocolEncoding::Default => MouseEncoding::Default,
+            vt100::MouseProtocolEncoding::Utf8 => MouseEncoding::Utf8,
+            vt100::MouseProtocolEncoding::Sgr => MouseEncoding::Sgr,
+        };
+        (mode, encoding)
+    }
+
+    /// Whether the program has turned on insert mode (IRM, `CSI 4 h`).
+    pub fn insert_mode(&self) -> bool {
+        self.insert_mode
+    }
+
+    /// Whether the application has enabled bracketed paste (DECSET 2004).
+    pub fn bracketed_paste(&self) -> bool {
+        self.parser.lock().screen().bracketed_paste()
+    }
+
     /// Get the current title.
     pub fn title(&self) -> &str {
         &self.title
@@ -139,24 +1267,411 @@ impl Terminal {
         self.size
     }
 
-    /// Check if bell was rung (not directly exposed in vt100 0.15).
+    /// Check if the bell was rung during the last `process` call.
     pub fn check_bell(&self) -> bool {
-        // vt100 0.15 doesn't expose audible_bell() directly
-        // Would need to track escape sequences ourselves
-        false
+        self.bell_rung
     }
 
     // Private helpers
 
+    fn handle_sequence(&mut self, screen: &vt100::Screen, seq: Sequence) {
+        match seq {
+            Sequence::Osc { data, bel } => self.handle_osc(&data, bel),
+            Sequence::Csi(csi) => self.handle_csi(screen, &csi),
+            Sequence::Esc { intermediates, action } if intermediates.is_empty() => match action {
+                // RIS resets the margins and tab stops
+                b'c' => {
+                    self.images.clear();
+                    self.open_link = None;
+                    self.links.clear();
+                    self.scroll_region = None;
+                    self.tab_stops = default_tab_stops(0, self.size.cols);
+                    self.origin_mode = false;
+                    self.autowrap = true;
+                    self.insert_mode = false;
+                    self.eight_bit_replies = false;
+                    self.kitty_flags = [Vec::new(), Vec::new()];
+                    if let Some(index) = &mut self.search_index {
+                        index.invalidate();
+                    }
+                }
+                // HTS
+                b'H' => {
+                    let (_, col) = screen.cursor_position();
+                    if let Some(stop) = self.tab_stops.get_mut(col as usize) {
+                        *stop = true;
+                    }
+                }
+                _ => {}
+            },
+            // S8C1T / S7C1T, only honored when 8-bit controls are enabled
+            Sequence::Esc { intermediates, action } if intermediates == b" " => match action {
+                b'G' => self.eight_bit_replies = self.c1_mode == C1Mode::EightBit,
+                b'F' => self.eight_bit_replies = false,
+                _ => {}
+            },
+            _ => {}
+        }
+    }
+
+    /// One-based cursor position for CPR, relative to the scroll region in origin mode.
+    fn report_position(&self, screen: &vt100::Screen) -> (u16, u16) {
+        let position = self.origin_position(screen);
+        (position.row + 1, position.col + 1)
+    }
+
+    /// Zero-based cursor position, relative to the top margin in origin mode.
+    fn origin_position(&self, screen: &vt100::Screen) -> CursorPosition {
+        let (row, col) = screen.cursor_position();
+        let top = match (self.origin_mode, self.scroll_region) {
+            (true, Some((top, _))) => top,
+            _ => 0,
+        };
+        CursorPosition {
+            row: row.saturating_sub(top),
+            col,
+        }
+    }
+
+    /// Column the cursor moves to on HT from `col`; the last column if no stop follows.
+    fn next_tab_stop(&self, col: u16) -> u16 {
+        let last = self.size.cols.saturating_sub(1);
+        (col + 1..=last)
+            .find(|c| self.tab_stops.get(*c as usize) == Some(&true))
+            .unwrap_or(last)
+    }
+
+    fn handle_csi(&mut self, screen: &vt100::Screen, csi: &Csi) {
+        match (csi.private, csi.intermediates.as_slice(), csi.action) {
+            // DECSTBM
+            (None, [], b'r') => {
+                let rows = self.size.rows;
+                let top = csi.param(0, 1).saturating_sub(1);
+                let bottom = csi.param(1, rows).min(rows).saturating_sub(1);
+                self.scroll_region = if top < bottom && (top > 0 || bottom + 1 < rows) {
+                    Some((top, bottom))
+                } else {
+                    None
+                };
+            }
+            // TBC
+            (None, [], b'g') => match csi.param(0, 0) {
+                0 => {
+                    let (_, col) = screen.cursor_position();
+                    if let Some(stop) = self.tab_stops.get_mut(col as usize) {
+                        *stop = false;
+                    }
+                }
+                3 => self.tab_stops.iter_mut().for_each(|stop| *stop = false),
+                _ => {}
+            },
+            // DSR: operating status
+            (None, [], b'n') if csi.param(0, 0) == 5 => {
+                self.responses.extend_from_slice(b"\x1b[0n");
+            }
+            // DSR: cursor position report
+            (None, [], b'n') if csi.param(0, 0) == 6 => {
+                let (row, col) = self.report_position(screen);
+                let reply = format!("\x1b[{};{}R", row, col);
+                self.responses.extend_from_slice(reply.as_bytes());
+            }
+            // DECXCPR
+            (Some(b'?'), [], b'n') if csi.param(0, 0) == 6 => {
+                let (row, col) = self.report_position(screen);
+                let reply = format!("\x1b[?{};{};1R", row, col);
+                self.responses.extend_from_slice(reply.as_bytes());
+            }
+            // DECSET/DECRST; only origin mode and autowrap are tracked here
+            (Some(b'?'), [], action @ (b'h' | b'l')) => {
+                if csi.params().contains(&6) {
+                    self.origin_mode = action == b'h';
+                }
+                if csi.params().contains(&7) {
+                    self.autowrap = action == b'h';
+                }
+                // Entering the alternate screen clears it
+                if action == b'h' && csi.params().iter().any(|mode| matches!(mode, 47 | 1047 | 1049)) {
+                    self.links.retain(|link| !link.alternate);
+                }
+            }
+            // SM/RM; only IRM is tracked
+            (None, [], action @ (b'h' | b'l')) if csi.params().contains(&4) => {
+                self.insert_mode = action == b'h';
+            }
+            // Kitty keyboard protocol: push, pop, set and query flags
+            (Some(marker @ (b'>' | b'<' | b'=' | b'?')), [], b'u') => {
+                let stack = &mut self.kitty_flags[screen.alternate_screen() as usize];
+                let current = stack.last().copied().unwrap_or(0);
+                match marker {
+                    b'>' => {
+                        if stack.len() >= KITTY_STACK_DEPTH {
+                            stack.remove(0);
+                        }
+                        stack.push(csi.param(0, 0) as u8 & KITTY_FLAGS_MASK);
+                    }
+                    b'<' => {
+                        let count = csi.param(0, 1) as usize;
+                        stack.truncate(stack.len().saturating_sub(count));
+                    }
+                    b'=' => {
+                        let flags = csi.param(0, 0) as u8 & KITTY_FLAGS_MASK;
+                        let flags = match csi.param(1, 1) {
+                            2 => current | flags,
+                            3 => current & !flags,
+                            _ => flags,
+                        };
+                        match stack.last_mut() {
+                            Some(top) => *top = flags,
+                            None => stack.push(flags),
+                        }
+                    }
+                    _ => {
+                        let reply = format!("\x1b[?{}u", current);
+                        self.responses.extend_from_slice(reply.as_bytes());
+                    }
+                }
+            }
+            // Primary DA
+            (None, [], b'c') if csi.param(0, 0) == 0 => {
+                let reply = format!("\x1b[?{}c", self.device_reports.primary_da);
+                self.responses.extend_from_slice(reply.as_bytes());
+            }
+            // Secondary DA
+            (Some(b'>'), [], b'c') if csi.param(0, 0) == 0 => {
+                let reply = format!("\x1b[>{}c", self.device_reports.secondary_da);
+                self.responses.extend_from_slice(reply.as_bytes());
+            }
+            // XTVERSION
+            (Some(b'>'), [], b'q') => {
+                let reply = format!("\x1bP>|{}\x1b\\", self.device_reports.version);
+                self.responses.extend_from_slice(reply.as_bytes());
+            }
+            // DECRQM (DEC private modes)
+            (Some(b'?'), [b'$'], b'p') => {
+                let mode = csi.param(0, 0);
+                // DECOM and DECAWM are tracked here rather than by vt100
+                let set = match mode {
+                    6 => Some(self.origin_mode),
+                    7 => Some(self.wraps()),
+                    _ => dec_mode_set(screen, mode),
+                };
+                let state = match set {
+                    Some(true) => 1,
+                    Some(false) => 2,
+                    None => 0,
+                };
+                let reply = format!("\x1b[?{};{}$y", mode, state);
+                self.responses.extend_from_slice(reply.as_bytes());
+            }
+            // DECRQM (ANSI modes); only IRM is tracked, others are "not recognized"
+            (None, [b'$'], b'p') => {
+                let mode = csi.param(0, 0);
+                let state = match mode {
+                    4 if self.insert_mode => 1,
+                    4 => 2,
+                    _ => 0,
+                };
+                let reply = format!("\x1b[{};{}$y", mode, state);
+                self.responses.extend_from_slice(reply.as_bytes());
+            }
+            _ => {}
+        }
+    }
+
+    /// Record an OSC 133 semantic prompt mark (`A` prompt start, `B` prompt
+    /// end, `C` command start, `D;<exit>` command end).
+    fn handle_prompt_mark(&mut self, parser: &mut Parser, data: &[u8]) {
+        let text = String::from_utf8_lossy(data);
+        let mut parts = text.split(';').skip(1);
+        let kind = parts.next().unwrap_or("");
+
+        let offset = buffer_offset(parser);
+        let (row, col) = parser.screen().cursor_position();
+        let abs_row = offset as u32 + row as u32;
+
+        let (mark_type, command, exit_code) = match kind {
+            "A" => (MarkType::PromptStart, None, None),
+            "B" => {
+                self.prompt_end = Some((abs_row, col));
+                return;
+            }
+            "C" => {
+                // The command is whatever was typed between the prompt and now
+                let command = self.prompt_end.take().and_then(|(start_row, start_col)| {
+                    let start_row = u16::try_from(start_row.checked_sub(offset as u32)?).ok()?;
+                    let text = parser.screen().contents_between(start_row, start_col, row, col);
+                    let text = text.trim();
+                    (!text.is_empty()).then(|| text.to_string())
+                });
+                (MarkType::CommandStart, command, None)
+            }
+            "D" => (MarkType::CommandEnd, None, parts.next().and_then(|c| c.parse().ok())),
+            _ => return,
+        };
+
+        let timestamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or(0);
+
+        self.marks.push(Mark {
+            row: abs_row,
+            timestamp,
+            mark_type,
+            command,
+            exit_code,
+        });
+    }
+
+    /// Record an iTerm2 inline image (`OSC 1337;File=<args>:<base64>`) at
+    /// the cursor and move the cursor below it. Non-inline transfers are
+    /// file downloads and are ignored.
+    fn handle_inline_image(&mut self, parser: &mut Parser, data: &[u8]) {
+        let text = String::from_utf8_lossy(&data[b"1337;File=".len()..]);
+        let Some((args, payload)) = text.split_once(':') else {
+            return;
+        };
+        let args: HashMap<&str, &str> = args.split(';').filter_map(|arg| arg.split_once('=')).collect();
+        if args.get("inline") != Some(&"1") || payload.is_empty() {
+            return;
+        }
+
+        let pixel_size = image_pixel_size(payload.as_bytes());
+        let cell = self.cell_pixel_size();
+        let requested = |key: &str, cells: u16, cell_px: u16| {
+            args.get(key).and_then(|spec| image_extent(spec, cells, cell_px))
+        };
+        let width = requested("width", self.size.cols, cell.0);
+        let height = requested("height", self.size.rows, cell.1);
+        let preserve_aspect = args.get("preserveAspectRatio") != Some(&"0");
+        let (width, height) = fit_image(width, height, pixel_size, cell, preserve_aspect);
+        let width = width.clamp(1, self.size.cols.max(1));
+        let height = height.max(1);
+
+        let (row, col) = parser.screen().cursor_position();
+        let id = self.next_image_id;
+        self.next_image_id = self.next_image_id.wrapping_add(1);
+        self.images.push(InlineImage {
+            placement: ImagePlacement {
+                id,
+                row: buffer_offset(parser) as u32 + row as u32,
+                col,
+                width,
+                height,
+                pixel_size,
+            },
+            data: payload.to_string(),
+        });
+
+        // Reserve the rows the image covers, leaving the cursor below it
+        parser.process(&b"\n".repeat(height as usize));
+        parser.process(b"\r");
+    }
+
+    /// Open or close an OSC 8 hyperlink (`8;params;uri`, an empty uri
+    /// closing it), recording the rows the closed link covered.
+    fn handle_hyperlink(&mut self, parser: &mut Parser, data: &[u8]) {
+        let text = String::from_utf8_lossy(&data[b"8;".len()..]);
+        let Some((params, uri)) = text.split_once(';') else {
+            return;
+        };
+        let (row, col) = parser.screen().cursor_position();
+        let row = buffer_offset(parser) as u32 + row as u32;
+        let alternate = parser.screen().alternate_screen();
+
+        if let Some(open) = self.open_link.take() {
+            for r in open.row..=row {
+                let start_col = if r == open.row { open.col } else { 0 };
+                let end_col = if r == row { col } else { self.size.cols };
+                if start_col < end_col {
+                    self.links.push(LinkRecord {
+                        row: r,
+                        start_col,
+                        end_col,
+                        url: open.url.clone(),
+                        id: open.id.clone(),
+                        alternate,
+                    });
+                }
+            }
+        }
+        if !uri.is_empty() {
+            let id = params
+                .split(':')
+                .find_map(|param| param.strip_prefix("id="))
+                .map(str::to_string);
+            self.open_link = Some(OpenLink {
+                url: uri.to_string(),
+                id,
+                row,
+                col,
+            });
+        }
+    }
+
+    /// Cell size in pixels from the window pixel size, or a default when unknown.
+    fn cell_pixel_size(&self) -> (u16, u16) {
+        let width = self.pixel_size.width / self.size.cols.max(1);
+        let height = self.pixel_size.height / self.size.rows.max(1);
+        if width == 0 || height == 0 {
+            DEFAULT_CELL_PIXELS
+        } else {
+            (width, height)
+        }
+    }
+
+    fn handle_osc(&mut self, data: &[u8], bel: bool) {
+        let text = String::from_utf8_lossy(data);
+        let mut parts = text.split(';');
+        let Some(code) = parts.next().and_then(|p| p.parse::<u16>().ok()) else {
+            return;
+        };
+
+        match code {
+            // OSC 10;fg;bg;cursor sets/queries consecutive slots
+            10..=12 => {
+                for (i, spec) in parts.enumerate() {
+                    let Some(slot) = DynamicColor::from_osc(code + i as u16) else {
+                        break;
+                    };
+                    if spec == "?" {
+                        self.color_requests.push(ColorRequest::Query { slot, bel });
+                    } else if let Some(color) = Color::parse_x11(spec) {
+                        self.color_requests.push(ColorRequest::Set(slot, color));
+                    }
+                }
+            }
+            110..=112 => {
+                if let Some(slot) = DynamicColor::from_osc(code - 100) {
+                    self.color_requests.push(ColorRequest::Reset(slot));
+                }
+            }
+            // OSC 7;file://host/path reports the working directory
+            7 => {
+                if let Some(path) = text.split_once(';').and_then(|(_, uri)| parse_file_uri(uri)) {
+                    self.cwd_reports.push(path);
+                }
+            }
+            // OSC 52;<targets>;<base64> writes the clipboard; reads aren't supported
+            52 => {
+                if let Some(content) = parts.nth(1) {
+                    if content != "?" {
+                        self.clipboard_writes.push(content.to_string());
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
     fn get_cursor_from_screen(&self, screen: &vt100::Screen) -> Cursor {
         let (row, col) = screen.cursor_position();
         Cursor {
-            position: CursorPosition {
-                row: row as u16,
-                col: col as u16,
-            },
+            position: CursorPosition { row, col },
             visible: !screen.hide_cursor(),
             shape: CursorShape::Block, // vt100 doesn't track cursor shape
+            color: None,
+            origin_position: self.origin_mode.then(|| self.origin_position(screen)),
         }
     }
 
@@ -167,19 +1682,239 @@ impl Terminal {
         }
     }
 
+    /// Convert a cell color, applying any override for its index.
+    fn resolve_color(&self, color: vt100::Color, default: Color) -> Color {
+        if let vt100::Color::Idx(idx) = color {
+            if let Some(&over) = self.color_overrides.get(&idx) {
+                return over;
+            }
+        }
+        Color::from_vt100(color, default)
+    }
+
     fn convert_cell(&self, cell: &vt100::Cell) -> Cell {
         let default_fg = Color::new(255, 255, 255);
         let default_bg = Color::new(0, 0, 0);
 
+        let bg = self.resolve_color(cell.bgcolor(), default_bg);
+        let mut fg = self.resolve_color(cell.fgcolor(), default_fg);
+        if let Some(ratio) = self.min_contrast {
+            fg = fg.ensure_contrast(&bg, ratio);
+        }
+
+        let contents = cell.contents();
+        let substituted = contents.chars().any(is_undisplayable);
         Cell {
-            char: cell.contents().to_string(),
-            fg: Color::from_vt100(cell.fgcolor(), default_fg),
-            bg: Color::from_vt100(cell.bgcolor(), default_bg),
+            char: if substituted {
+                self.replacement_char.to_string()
+            } else {
+                contents.to_string()
+            },
+            fg,
+            bg,
             attrs: CellAttributes::from_vt100_cell(cell),
+            width: if cell.is_wide() {
+                2
+            } else if cell.is_wide_continuation() {
+                0
+            } else {
+                1
+            },
+            substituted,
+        }
+    }
+}
+
+/// Control characters and Unicode noncharacters, which have no glyph.
+fn is_undisplayable(c: char) -> bool {
+    let code = c as u32;
+    c.is_control() || (0xFDD0..=0xFDEF).contains(&code) || code & 0xFFFE == 0xFFFE
+}
+
+/// Entries kept per kitty keyboard flag stack; the oldest are dropped.
+const KITTY_STACK_DEPTH: usize = 16;
+
+/// Kitty keyboard flags defined by the protocol.
+const KITTY_FLAGS_MASK: u8 = 0b1_1111;
+
+/// Number of scrollback lines currently retained by the parser.
+///
+/// vt100 only exposes the viewport offset, so this scrolls to the top to
+/// find the limit; callers restore the offset afterwards.
+fn scrollback_len(parser: &mut Parser) -> usize {
+    parser.screen_mut().set_scrollback(usize::MAX);
+    parser.screen().scrollback()
+}
+
+/// Number of scrollback lines above the screen, leaving the viewport as it was.
+fn buffer_offset(parser: &mut Parser) -> usize {
+    let saved = parser.screen().scrollback();
+    let len = scrollback_len(parser);
+    parser.screen_mut().set_scrollback(saved);
+    len
+}
+
+/// URLs recognized in screen text when the program didn't mark them with OSC 8.
+fn url_pattern() -> &'static Regex {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    PATTERN.get_or_init(|| Regex::new(r#"\b(?:https?|ftp|file)://[^\s<>"'`]+"#).expect("valid URL pattern"))
+}
+
+/// Parse an iTerm2 image dimension (`N` cells, `Npx`, `N%`) into cells.
+/// `auto` and unparsable values give `None`.
+fn image_extent(spec: &str, cells: u16, cell_px: u16) -> Option<u16> {
+    if let Some(px) = spec.strip_suffix("px") {
+        let px: u32 = px.parse().ok()?;
+        Some(px.div_ceil(cell_px.max(1) as u32).min(u16::MAX as u32) as u16)
+    } else if let Some(percent) = spec.strip_suffix('%') {
+        let percent: u32 = percent.parse().ok()?;
+        Some((cells as u32 * percent.min(100) / 100) as u16)
+    } else {
+        spec.parse().ok()
+    }
+}
+
+/// Fill in whichever of `width`/`height` (in cells) wasn't requested, from
+/// the image's native size and, if asked, its aspect ratio.
+fn fit_image(
+    width: Option<u16>,
+    height: Option<u16>,
+    pixels: PixelSize,
+    cell: (u16, u16),
+    preserve_aspect: bool,
+) -> (u16, u16) {
+    let (img_w, img_h) = (pixels.width as u64, pixels.height as u64);
+    let (cell_w, cell_h) = (cell.0.max(1) as u64, cell.1.max(1) as u64);
+    let cells = |px: u64, cell_px: u64| px.div_ceil(cell_px).min(u16::MAX as u64) as u16;
+    let known = img_w > 0 && img_h > 0;
+
+    match (width, height) {
+        (Some(w), Some(h)) => (w, h),
+        (Some(w), None) if preserve_aspect && known => (w, cells(w as u64 * cell_w * img_h / img_w, cell_h)),
+        (None, Some(h)) if preserve_aspect && known => (cells(h as u64 * cell_h * img_w / img_h, cell_w), h),
+        (w, h) => (
+            w.unwrap_or_else(|| cells(img_w, cell_w)),
+            h.unwrap_or_else(|| cells(img_h, cell_h)),
+        ),
+    }
+}
+
+/// Native pixel size from a PNG or GIF header; zero when unrecognized.
+fn image_pixel_size(payload: &[u8]) -> PixelSize {
+    // Both headers fit in the first few dozen bytes
+    let head = base64_decode(&payload[..payload.len().min(64)]);
+    let clamp = |v: u32| v.min(u16::MAX as u32) as u16;
+
+    if head.starts_with(b"\x89PNG\r\n\x1a\n") && head.len() >= 24 {
+        PixelSize {
+            width: clamp(u32::from_be_bytes([head[16], head[17], head[18], head[19]])),
+            height: clamp(u32::from_be_bytes([head[20], head[21], head[22], head[23]])),
         }
+    } else if head.starts_with(b"GIF8") && head.len() >= 10 {
+        PixelSize {
+            width: u16::from_le_bytes([head[6], head[7]]),
+            height: u16::from_le_bytes([head[8], head[9]]),
+        }
+    } else {
+        PixelSize::default()
     }
 }
 
+/// Path of a `file://host/path` URI, percent-decoded.
+fn parse_file_uri(uri: &str) -> Option<String> {
+    let rest = uri.strip_prefix("file://")?;
+    let path = &rest.as_bytes()[rest.find('/')?..];
+
+    let mut out = Vec::with_capacity(path.len());
+    let mut i = 0;
+    while i < path.len() {
+        let escaped = (path[i] == b'%')
+            .then(|| path.get(i + 1..i + 3))
+            .flatten()
+            .and_then(|hex| u8::from_str_radix(std::str::from_utf8(hex).ok()?, 16).ok());
+        match escaped {
+            Some(b) => {
+                out.push(b);
+                i += 3;
+            }
+            None => {
+                out.push(path[i]);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8(out).ok()
+}
+
+/// Decode standard base64, skipping characters outside the alphabet and
+/// stopping at padding.
+fn base64_decode(input: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(input.len() / 4 * 3);
+    let (mut acc, mut bits) = (0u32, 0u32);
+
+    for &c in input {
+        let value = match c {
+            b'A'..=b'Z' => c - b'A',
+            b'a'..=b'z' => c - b'a' + 26,
+            b'0'..=b'9' => c - b'0' + 52,
+            b'+' => 62,
+            b'/' => 63,
+            b'=' => break,
+            _ => continue,
+        };
+        acc = (acc << 6) | value as u32;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            out.push((acc >> bits) as u8);
+            acc &= (1 << bits) - 1;
+        }
+    }
+
+    out
+}
+
+/// Default tab stop flags for columns `from..to`: every 8 columns.
+fn default_tab_stops(from: u16, to: u16) -> Vec<bool> {
+    (from..to).map(|col| col > 0 && col % 8 == 0).collect()
+}
+
+/// Diff `grid` against `prev` row by row, emitting every cell of changed rows.
+fn diff_rows(prev: &[Row], grid: &[Row]) -> Vec<CellChange> {
+    let mut changes = Vec::new();
+
+    for (row, cells) in grid.iter().enumerate() {
+        if prev.get(row) == Some(cells) {
+            continue;
+        }
+        for (col, cell) in cells.iter().enumerate() {
+            changes.push(CellChange {
+                row: row as u16,
+                col: col as u16,
+                cell: cell.clone(),
+            });
+        }
+    }
+
+    changes
+}
+
+/// Whether a DEC private mode vt100 tracks is set; `None` if it isn't tracked.
+fn dec_mode_set(screen: &vt100::Screen, mode: u16) -> Option<bool> {
+    let set = match mode {
+        1 => screen.application_cursor(),
+        25 => !screen.hide_cursor(),
+        47 | 1047 | 1049 => screen.alternate_screen(),
+        1000 => screen.mouse_protocol_mode() == vt100::MouseProtocolMode::PressRelease,
+        1002 => screen.mouse_protocol_mode() == vt100::MouseProtocolMode::ButtonMotion,
+        1003 => screen.mouse_protocol_mode() == vt100::MouseProtocolMode::AnyMotion,
+        1006 => screen.mouse_protocol_encoding() == vt100::MouseProtocolEncoding::Sgr,
+        2004 => screen.bracketed_paste(),
+        _ => return None,
+    };
+    Some(set)
+}
+
 impl Clone for Terminal {
     fn clone(&self) -> Self {
         // Clone creates a fresh terminal with same size
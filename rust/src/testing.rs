@@ -0,0 +1,99 @@
+//! Headless harness for driving sessions without a Tauri `AppHandle`.
+//!
+//! Enabled with the `testing` feature.
+//!
+//! ```rust,ignore
+//! use tauri_plugin_terminal::testing::TestHarness;
+//!
+//! let mut harness = TestHarness::new();
+//! let id = harness.spawn(Default::default())?;
+//! harness.feed(&id, b"\x1b[31mhello")?;
+//! assert!(harness.screen_text(&id)?.contains("hello"));
+//! ```
+
+use crate::error::Result;
+use crate::events::{EventReceiver, TerminalEvent};
+use crate::session::{SessionConfig, SessionId, SessionManager};
+use crate::types::{Screen, ScreenUpdate};
+use std::time::{Duration, Instant};
+
+/// Drives a `SessionManager` directly and collects its events.
+pub struct TestHarness {
+    manager: SessionManager,
+    events: EventReceiver,
+}
+
+impl Default for TestHarness {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TestHarness {
+    /// Create a harness with an empty, headless session manager.
+    pub fn new() -> Self {
+        let (manager, events) = SessionManager::new_headless();
+        Self { manager, events }
+    }
+
+    /// Create a harness whose manager is set up by `configure`, e.g.
+    /// `|manager| manager.with_max_sessions(Some(1))`.
+    pub fn with_manager(configure: impl FnOnce(SessionManager) -> SessionManager) -> Self {
+        let (manager, events) = SessionManager::new_headless();
+        Self { manager: configure(manager), events }
+    }
+
+    /// Access the underlying session manager.
+    pub fn manager(&self) -> &SessionManager {
+        &self.manager
+    }
+
+    /// Spawn a session.
+    pub fn spawn(&self, config: SessionConfig) -> Result<SessionId> {
+        self.manager.create(config)
+    }
+
+    /// Feed bytes straight into a session's emulator, bypassing the PTY.
+    pub fn feed(&self, id: &str, data: &[u8]) -> Result<Option<ScreenUpdate>> {
+        self.manager.feed(id, data)
+    }
+
+    /// Process one chunk of pending PTY output for a session.
+    pub fn pump(&self, id: &str) -> Result<Option<ScreenUpdate>> {
+        self.manager.process(id)
+    }
+
+    /// Pump PTY output until `predicate` holds for the screen or `timeout` elapses.
+    ///
+    /// Returns whether the predicate was satisfied.
+    pub fn pump_until<F>(&self, id: &str, timeout: Duration, predicate: F) -> Result<bool>
+    where
+        F: Fn(&Screen) -> bool,
+    {
+        let deadline = Instant::now() + timeout;
+        loop {
+            while self.manager.process(id)?.is_some() {}
+            if predicate(&self.manager.get_screen(id)?) {
+                return Ok(true);
+            }
+            if Instant::now() >= deadline {
+                return Ok(false);
+            }
+            std::thread::sleep(Duration::from_millis(5));
+        }
+    }
+
+    /// Visible screen text, one trimmed line per row.
+    pub fn screen_text(&self, id: &str) -> Result<String> {
+        self.manager.screen_text(id)
+    }
+
+    /// Drain all events emitted so far.
+    pub fn events(&mut self) -> Vec<TerminalEvent> {
+        let mut events = Vec::new();
+        while let Ok(event) = self.events.try_recv() {
+            events.push(event);
+        }
+        events
+    }
+}
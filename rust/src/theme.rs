@@ -1,6 +1,6 @@
 //! Terminal themes including popular iTerm2 themes.
 
-use crate::types::Color;
+use crate::types::{idx_to_color, Color, DynamicColor};
 use serde::{Deserialize, Serialize};
 
 /// Terminal color theme.
@@ -203,6 +203,141 @@ pub static ONE_DARK: Theme = Theme {
     bright_white: Color::new(255, 255, 255),
 };
 
+/// Gruvbox Dark theme.
+pub static GRUVBOX_DARK: Theme = Theme {
+    name: String::new(),
+    foreground: Color::new(235, 219, 178),
+    background: Color::new(40, 40, 40),
+    cursor: Color::new(235, 219, 178),
+    cursor_text: Color::new(40, 40, 40),
+    selection: Color::new(80, 73, 69),
+    selection_text: Color::new(235, 219, 178),
+    black: Color::new(40, 40, 40),
+    red: Color::new(204, 36, 29),
+    green: Color::new(152, 151, 26),
+    yellow: Color::new(215, 153, 33),
+    blue: Color::new(69, 133, 136),
+    magenta: Color::new(177, 98, 134),
+    cyan: Color::new(104, 157, 106),
+    white: Color::new(168, 153, 132),
+    bright_black: Color::new(146, 131, 116),
+    bright_red: Color::new(251, 73, 52),
+    bright_green: Color::new(184, 187, 38),
+    bright_yellow: Color::new(250, 189, 47),
+    bright_blue: Color::new(131, 165, 152),
+    bright_magenta: Color::new(211, 134, 155),
+    bright_cyan: Color::new(142, 192, 124),
+    bright_white: Color::new(235, 219, 178),
+};
+
+/// Gruvbox Light theme.
+pub static GRUVBOX_LIGHT: Theme = Theme {
+    name: String::new(),
+    foreground: Color::new(60, 56, 54),
+    background: Color::new(251, 241, 199),
+    cursor: Color::new(60, 56, 54),
+    cursor_text: Color::new(251, 241, 199),
+    selection: Color::new(213, 196, 161),
+    selection_text: Color::new(60, 56, 54),
+    black: Color::new(251, 241, 199),
+    red: Color::new(204, 36, 29),
+    green: Color::new(152, 151, 26),
+    yellow: Color::new(215, 153, 33),
+    blue: Color::new(69, 133, 136),
+    magenta: Color::new(177, 98, 134),
+    cyan: Color::new(104, 157, 106),
+    white: Color::new(124, 111, 100),
+    bright_black: Color::new(146, 131, 116),
+    bright_red: Color::new(157, 0, 6),
+    bright_green: Color::new(121, 116, 14),
+    bright_yellow: Color::new(181, 118, 20),
+    bright_blue: Color::new(7, 102, 120),
+    bright_magenta: Color::new(143, 63, 113),
+    bright_cyan: Color::new(66, 123, 88),
+    bright_white: Color::new(60, 56, 54),
+};
+
+/// Tokyo Night theme.
+pub static TOKYO_NIGHT: Theme = Theme {
+    name: String::new(),
+    foreground: Color::new(192, 202, 245),
+    background: Color::new(26, 27, 38),
+    cursor: Color::new(192, 202, 245),
+    cursor_text: Color::new(26, 27, 38),
+    selection: Color::new(40, 52, 87),
+    selection_text: Color::new(192, 202, 245),
+    black: Color::new(21, 22, 30),
+    red: Color::new(247, 118, 142),
+    green: Color::new(158, 206, 106),
+    yellow: Color::new(224, 175, 104),
+    blue: Color::new(122, 162, 247),
+    magenta: Color::new(187, 154, 247),
+    cyan: Color::new(125, 207, 255),
+    white: Color::new(169, 177, 214),
+    bright_black: Color::new(65, 72, 104),
+    bright_red: Color::new(247, 118, 142),
+    bright_green: Color::new(158, 206, 106),
+    bright_yellow: Color::new(224, 175, 104),
+    bright_blue: Color::new(122, 162, 247),
+    bright_magenta: Color::new(187, 154, 247),
+    bright_cyan: Color::new(125, 207, 255),
+    bright_white: Color::new(192, 202, 245),
+};
+
+/// Catppuccin Mocha theme.
+pub static CATPPUCCIN_MOCHA: Theme = Theme {
+    name: String::new(),
+    foreground: Color::new(205, 214, 244),
+    background: Color::new(30, 30, 46),
+    cursor: Color::new(245, 224, 220),
+    cursor_text: Color::new(30, 30, 46),
+    selection: Color::new(245, 224, 220),
+    selection_text: Color::new(30, 30, 46),
+    black: Color::new(69, 71, 90),
+    red: Color::new(243, 139, 168),
+    green: Color::new(166, 227, 161),
+    yellow: Color::new(249, 226, 175),
+    blue: Color::new(137, 180, 250),
+    magenta: Color::new(245, 194, 231),
+    cyan: Color::new(148, 226, 213),
+    white: Color::new(186, 194, 222),
+    bright_black: Color::new(88, 91, 112),
+    bright_red: Color::new(243, 139, 168),
+    bright_green: Color::new(166, 227, 161),
+    bright_yellow: Color::new(249, 226, 175),
+    bright_blue: Color::new(137, 180, 250),
+    bright_magenta: Color::new(245, 194, 231),
+    bright_cyan: Color::new(148, 226, 213),
+    bright_white: Color::new(166, 173, 200),
+};
+
+/// Catppuccin Latte theme.
+pub static CATPPUCCIN_LATTE: Theme = Theme {
+    name: String::new(),
+    foreground: Color::new(76, 79, 105),
+    background: Color::new(239, 241, 245),
+    cursor: Color::new(220, 138, 120),
+    cursor_text: Color::new(239, 241, 245),
+    selection: Color::new(220, 138, 120),
+    selection_text: Color::new(239, 241, 245),
+    black: Color::new(92, 95, 119),
+    red: Color::new(210, 15, 57),
+    green: Color::new(64, 160, 43),
+    yellow: Color::new(223, 142, 29),
+    blue: Color::new(30, 102, 245),
+    magenta: Color::new(234, 118, 203),
+    cyan: Color::new(23, 146, 153),
+    white: Color::new(172, 176, 190),
+    bright_black: Color::new(108, 111, 133),
+    bright_red: Color::new(210, 15, 57),
+    bright_green: Color::new(64, 160, 43),
+    bright_yellow: Color::new(223, 142, 29),
+    bright_blue: Color::new(30, 102, 245),
+    bright_magenta: Color::new(234, 118, 203),
+    bright_cyan: Color::new(23, 146, 153),
+    bright_white: Color::new(188, 192, 204),
+};
+
 /// All available themes.
 pub static THEMES: &[(&str, &Theme)] = &[
     ("dark", &DARK),
@@ -211,6 +346,11 @@ pub static THEMES: &[(&str, &Theme)] = &[
     ("dracula", &DRACULA),
     ("nord", &NORD),
     ("one-dark", &ONE_DARK),
+    ("gruvbox-dark", &GRUVBOX_DARK),
+    ("gruvbox-light", &GRUVBOX_LIGHT),
+    ("tokyo-night", &TOKYO_NIGHT),
+    ("catppuccin-mocha", &CATPPUCCIN_MOCHA),
+    ("catppuccin-latte", &CATPPUCCIN_LATTE),
 ];
 
 impl Theme {
@@ -225,7 +365,95 @@ impl Theme {
             })
     }
 
-    /// Get color by ANSI index.
+    /// Interpolate every color towards `other`; `t` is clamped to `0.0..=1.0`.
+    ///
+    /// The result keeps this theme's name until `t` reaches 1.
+    pub fn lerp(&self, other: &Theme, t: f32) -> Theme {
+        let t = t.clamp(0.0, 1.0);
+        Theme {
+            name: if t < 1.0 { self.name.clone() } else { other.name.clone() },
+            foreground: self.foreground.lerp(&other.foreground, t),
+            background: self.background.lerp(&other.background, t),
+            cursor: self.cursor.lerp(&other.cursor, t),
+            cursor_text: self.cursor_text.lerp(&other.cursor_text, t),
+            selection: self.selection.lerp(&other.selection, t),
+            selection_text: self.selection_text.lerp(&other.selection_text, t),
+            black: self.black.lerp(&other.black, t),
+            red: self.red.lerp(&other.red, t),
+            green: self.green.lerp(&other.green, t),
+            yellow: self.yellow.lerp(&other.yellow, t),
+            blue: self.blue.lerp(&other.blue, t),
+            magenta: self.magenta.lerp(&other.magenta, t),
+            cyan: self.cyan.lerp(&other.cyan, t),
+            white: self.white.lerp(&other.white, t),
+            bright_black: self.bright_black.lerp(&other.bright_black, t),
+            bright_red: self.bright_red.lerp(&other.bright_red, t),
+            bright_green: self.bright_green.lerp(&other.bright_green, t),
+            bright_yellow: self.bright_yellow.lerp(&other.bright_yellow, t),
+            bright_blue: self.bright_blue.lerp(&other.bright_blue, t),
+            bright_magenta: self.bright_magenta.lerp(&other.bright_magenta, t),
+            bright_cyan: self.bright_cyan.lerp(&other.bright_cyan, t),
+            bright_white: self.bright_white.lerp(&other.bright_white, t),
+        }
+    }
+
+    /// Return a copy with text colors adjusted to reach `min_ratio` WCAG
+    /// contrast against the background (4.5 is the AA level for body text).
+    pub fn ensure_contrast(&self, min_ratio: f32) -> Theme {
+        let bg = self.background;
+        let fix = |c: Color| c.ensure_contrast(&bg, min_ratio);
+        Theme {
+            name: self.name.clone(),
+            foreground: fix(self.foreground),
+            background: bg,
+            cursor: fix(self.cursor),
+            cursor_text: self.cursor_text.ensure_contrast(&self.cursor, min_ratio),
+            selection: self.selection,
+            selection_text: self.selection_text.ensure_contrast(&self.selection, min_ratio),
+            black: fix(self.black),
+            red: fix(self.red),
+            green: fix(self.green),
+            yellow: fix(self.yellow),
+            blue: fix(self.blue),
+            magenta: fix(self.magenta),
+            cyan: fix(self.cyan),
+            white: fix(self.white),
+            bright_black: fix(self.bright_black),
+            bright_red: fix(self.bright_red),
+            bright_green: fix(self.bright_green),
+            bright_yellow: fix(self.bright_yellow),
+            bright_blue: fix(self.bright_blue),
+            bright_magenta: fix(self.bright_magenta),
+            bright_cyan: fix(self.bright_cyan),
+            bright_white: fix(self.bright_white),
+        }
+    }
+
+    /// Get a dynamic color (OSC 10/11/12 slot).
+    pub fn dynamic_color(&self, slot: DynamicColor) -> Color {
+        match slot {
+            DynamicColor::Foreground => self.foreground,
+            DynamicColor::Background => self.background,
+            DynamicColor::Cursor => self.cursor,
+        }
+    }
+
+    /// Set a dynamic color (OSC 10/11/12 slot).
+    pub fn set_dynamic_color(&mut self, slot: DynamicColor, color: Color) {
+        match slot {
+            DynamicColor::Foreground => self.foreground = color,
+            DynamicColor::Background => self.background = color,
+            DynamicColor::Cursor => self.cursor = color,
+        }
+    }
+
+    /// Resolve every 256-color index: 0-15 from the theme, 16-255 from the
+    /// xterm color cube and grayscale ramp.
+    pub fn palette_256(&self) -> [Color; 256] {
+        std::array::from_fn(|idx| self.color_by_index(idx as u8))
+    }
+
+    /// Get color by 256-color index.
     pub fn color_by_index(&self, idx: u8) -> Color {
         match idx {
             0 => self.black,
@@ -244,7 +472,7 @@ impl Theme {
             13 => self.bright_magenta,
             14 => self.bright_cyan,
             15 => self.bright_white,
-            _ => Color::new(255, 255, 255),
+            _ => idx_to_color(idx),
         }
     }
 }
@@ -0,0 +1,83 @@
+//! Per-session logging.
+//!
+//! Each session carries a verbosity the app can change at runtime. Messages
+//! go through `log`, or with the `tracing` feature become events inside a
+//! `session` span carrying the session id, which the PTY reader thread
+//! enters too.
+
+use std::fmt;
+use std::marker::PhantomData;
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::Arc;
+
+use crate::types::LogLevel;
+
+/// Log through a `SessionTrace` at a `log::Level` variant, e.g.
+/// `session_log!(self.trace, Debug, "spawned {}", pid)`.
+macro_rules! session_log {
+    ($trace:expr, $level:ident, $($arg:tt)+) => {
+        $trace.log(log::Level::$level, format_args!($($arg)+))
+    };
+}
+pub(crate) use session_log;
+
+/// A session's verbosity and span, shared with its reader thread.
+#[derive(Debug, Clone)]
+pub(crate) struct SessionTrace {
+    level: Arc<AtomicU8>,
+    #[cfg(feature = "tracing")]
+    span: tracing::Span,
+}
+
+/// Guard keeping a session's span entered; does nothing without `tracing`.
+pub(crate) struct Entered<'a> {
+    #[cfg(feature = "tracing")]
+    _entered: tracing::span::Entered<'a>,
+    _span: PhantomData<&'a ()>,
+}
+
+impl SessionTrace {
+    pub fn new(id: &str, level: LogLevel) -> Self {
+        #[cfg(not(feature = "tracing"))]
+        let _ = id;
+        Self {
+            level: Arc::new(AtomicU8::new(level as u8)),
+            #[cfg(feature = "tracing")]
+            span: tracing::info_span!("session", id = %id),
+        }
+    }
+
+    pub fn level(&self) -> LogLevel {
+        LogLevel::from_u8(self.level.load(Ordering::Relaxed))
+    }
+
+    pub fn set_level(&self, level: LogLevel) {
+        self.level.store(level as u8, Ordering::Relaxed);
+    }
+
+    /// Enter the session's span until the guard is dropped.
+    pub fn enter(&self) -> Entered<'_> {
+        Entered {
+            #[cfg(feature = "tracing")]
+            _entered: self.span.enter(),
+            _span: PhantomData,
+        }
+    }
+
+    /// Log a message if the session's verbosity allows `level`.
+    pub fn log(&self, level: log::Level, args: fmt::Arguments<'_>) {
+        if !self.level().allows(level) {
+            return;
+        }
+        #[cfg(feature = "tracing")]
+        match level {
+            log::Level::Error => tracing::error!(parent: &self.span, "{}", args),
+            log::Level::Warn => tracing::warn!(parent: &self.span, "{}", args),
+            log::Level::Info => tracing::info!(parent: &self.span, "{}", args),
+            log::Level::Debug => tracing::debug!(parent: &self.span, "{}", args),
+            log::Level::Trace => tracing::trace!(parent: &self.span, "{}", args),
+        }
+        #[cfg(not(feature = "tracing"))]
+        log::log!(level, "{}", args);
+    }
+}
@@ -1,6 +1,7 @@
 //! Common types used throughout the terminal plugin.
 
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
 /// Terminal dimensions.
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
@@ -15,6 +16,37 @@ impl Default for Size {
     }
 }
 
+/// Smallest grid `fit_cells` returns.
+pub const MIN_FIT_SIZE: Size = Size { cols: 2, rows: 1 };
+
+/// Largest grid of `cell_width` × `cell_height` pixel cells that fits in a
+/// `width_px` × `height_px` area, but at least `MIN_FIT_SIZE`.
+///
+/// Cell sizes may be fractional (HiDPI font metrics); a count that is whole
+/// up to float error, like 840 / 8.4, isn't rounded down a cell.
+pub fn fit_cells(width_px: u32, height_px: u32, cell_width: f64, cell_height: f64) -> Size {
+    fn fit(px: u32, cell: f64, min: u16) -> u16 {
+        if !cell.is_finite() || cell <= 0.0 {
+            return min;
+        }
+        let count = (px as f64 / cell + 1e-6).floor();
+        (count.min(u16::MAX as f64) as u16).max(min)
+    }
+    Size {
+        cols: fit(width_px, cell_width, MIN_FIT_SIZE.cols),
+        rows: fit(height_px, cell_height, MIN_FIT_SIZE.rows),
+    }
+}
+
+/// Window size in pixels reported to the child alongside the cell grid.
+///
+/// Zero means unknown, which is what programs see unless it's set.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq, Eq)]
+pub struct PixelSize {
+    pub width: u16,
+    pub height: u16,
+}
+
 /// Cursor position.
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq, Eq)]
 pub struct CursorPosition {
@@ -28,6 +60,13 @@ pub struct Cursor {
     pub position: CursorPosition,
     pub visible: bool,
     pub shape: CursorShape,
+    /// Cursor color overriding the theme's, if one is set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color: Option<Color>,
+    /// Position relative to the top margin while origin mode (DECOM) is on,
+    /// as the program addresses the cursor and as CPR reports it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub origin_position: Option<CursorPosition>,
 }
 
 impl Default for Cursor {
@@ -36,6 +75,8 @@ impl Default for Cursor {
             position: CursorPosition::default(),
             visible: true,
             shape: CursorShape::Block,
+            color: None,
+            origin_position: None,
         }
     }
 }
@@ -50,8 +91,122 @@ pub enum CursorShape {
     Bar,
 }
 
+/// How the terminal bell (BEL) is surfaced to the frontend.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum BellMode {
+    /// Emit `Bell` events only.
+    #[default]
+    Audible,
+    /// Emit `VisualBell` events only.
+    Visual,
+    /// Emit both `Bell` and `VisualBell` events.
+    Both,
+    /// Ignore the bell entirely.
+    None,
+}
+
+/// Output format of `export_to_file`.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ExportFormat {
+    /// Plain text, as `export_text` returns.
+    #[default]
+    Text,
+    /// ANSI-colored text, as `export_ansi_range` returns.
+    Ansi,
+}
+
+/// How bytes 0x80–0x9F from the program are interpreted.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum C1Mode {
+    /// Part of UTF-8 text.
+    #[default]
+    Utf8,
+    /// 8-bit C1 controls (e.g. 0x9B as CSI) when they aren't continuing a
+    /// UTF-8 character. S8C1T also switches replies to 8-bit controls.
+    EightBit,
+}
+
+impl BellMode {
+    /// Whether this mode emits the audible `Bell` event.
+    pub fn is_audible(&self) -> bool {
+        matches!(self, BellMode::Audible | BellMode::Both)
+    }
+
+    /// Whether this mode emits the `VisualBell` event.
+    pub fn is_visual(&self) -> bool {
+        matches!(self, BellMode::Visual | BellMode::Both)
+    }
+}
+
+/// How much a session logs, from nothing to every read and update.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
+pub enum LogLevel {
+    Off,
+    Error,
+    /// Failures the session recovers from, such as unanswerable queries.
+    #[default]
+    Warn,
+    Info,
+    /// Process lifecycle: spawn and end of output.
+    Debug,
+    /// Every read, processed chunk and emitted update.
+    Trace,
+}
+
+impl LogLevel {
+    /// Whether a message at `level` is logged at this verbosity.
+    pub fn allows(self, level: log::Level) -> bool {
+        level <= self.filter()
+    }
+
+    fn filter(self) -> log::LevelFilter {
+        match self {
+            LogLevel::Off => log::LevelFilter::Off,
+            LogLevel::Error => log::LevelFilter::Error,
+            LogLevel::Warn => log::LevelFilter::Warn,
+            LogLevel::Info => log::LevelFilter::Info,
+            LogLevel::Debug => log::LevelFilter::Debug,
+            LogLevel::Trace => log::LevelFilter::Trace,
+        }
+    }
+
+    pub(crate) fn from_u8(value: u8) -> Self {
+        match value {
+            0 => LogLevel::Off,
+            1 => LogLevel::Error,
+            2 => LogLevel::Warn,
+            3 => LogLevel::Info,
+            4 => LogLevel::Debug,
+            _ => LogLevel::Trace,
+        }
+    }
+}
+
+/// Why a session's process ended.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ExitReason {
+    /// The process exited on its own.
+    #[default]
+    Normal,
+    /// The process was terminated by a signal.
+    Signal,
+    /// The session was killed through the plugin.
+    Killed,
+    /// The session exceeded its idle timeout.
+    IdleTimeout,
+    /// The session exceeded its output byte limit.
+    OutputLimit,
+    /// Reading from the PTY failed and no exit status was available.
+    ReaderError,
+}
+
 /// RGB color.
-#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
 pub struct Color {
     pub r: u8,
     pub g: u8,
@@ -71,6 +226,95 @@ impl Color {
         format!("#{:02x}{:02x}{:02x}", self.r, self.g, self.b)
     }
 
+    /// Linearly interpolate towards `other`; `t` is clamped to `0.0..=1.0`.
+    pub fn lerp(&self, other: &Color, t: f32) -> Self {
+        let t = t.clamp(0.0, 1.0);
+        let mix = |a: u8, b: u8| (a as f32 + (b as f32 - a as f32) * t).round() as u8;
+        Color::new(mix(self.r, other.r), mix(self.g, other.g), mix(self.b, other.b))
+    }
+
+    /// WCAG relative luminance in `0.0..=1.0`.
+    pub fn luminance(&self) -> f32 {
+        let channel = |c: u8| {
+            let c = c as f32 / 255.0;
+            if c <= 0.03928 {
+                c / 12.92
+            } else {
+                ((c + 0.055) / 1.055).powf(2.4)
+            }
+        };
+        0.2126 * channel(self.r) + 0.7152 * channel(self.g) + 0.0722 * channel(self.b)
+    }
+
+    /// WCAG contrast ratio against `other`, from 1 (none) to 21 (black on white).
+    pub fn contrast_ratio(&self, other: &Color) -> f32 {
+        let (a, b) = (self.luminance(), other.luminance());
+        let (hi, lo) = if a > b { (a, b) } else { (b, a) };
+        (hi + 0.05) / (lo + 0.05)
+    }
+
+    /// Adjust this color as little as possible so its contrast against
+    /// `background` is at least `min_ratio`, moving towards black or white.
+    pub fn ensure_contrast(&self, background: &Color, min_ratio: f32) -> Color {
+        if self.contrast_ratio(background) >= min_ratio {
+            return *self;
+        }
+
+        let black = Color::new(0, 0, 0);
+        let white = Color::new(255, 255, 255);
+        let target = if black.contrast_ratio(background) > white.contrast_ratio(background) {
+            black
+        } else {
+            white
+        };
+
+        // Binary search for the smallest step towards the target that suffices
+        let (mut lo, mut hi) = (0.0f32, 1.0f32);
+        for _ in 0..12 {
+            let mid = (lo + hi) / 2.0;
+            if self.lerp(&target, mid).contrast_ratio(background) >= min_ratio {
+                hi = mid;
+            } else {
+                lo = mid;
+            }
+        }
+        self.lerp(&target, hi)
+    }
+
+    /// Format as an X11 `rgb:RRRR/GGGG/BBBB` spec, as used in OSC color replies.
+    pub fn to_x11(&self) -> String {
+        format!(
+            "rgb:{:02x}{:02x}/{:02x}{:02x}/{:02x}{:02x}",
+            self.r, self.r, self.g, self.g, self.b, self.b
+        )
+    }
+
+    /// Parse an X11 color spec (`rgb:R/G/B` or `#RGB` forms, 1-4 hex digits per channel).
+    pub fn parse_x11(spec: &str) -> Option<Self> {
+        if let Some(rest) = spec.strip_prefix("rgb:") {
+            let mut parts = rest.split('/');
+            let r = scale_hex_channel(parts.next()?)?;
+            let g = scale_hex_channel(parts.next()?)?;
+            let b = scale_hex_channel(parts.next()?)?;
+            if parts.next().is_some() {
+                return None;
+            }
+            Some(Color::new(r, g, b))
+        } else if let Some(hex) = spec.strip_prefix('#') {
+            if hex.is_empty() || hex.len() % 3 != 0 || hex.len() > 12 || !hex.is_ascii() {
+                return None;
+            }
+            let n = hex.len() / 3;
+            Some(Color::new(
+                scale_hex_channel(&hex[..n])?,
+                scale_hex_channel(&hex[n..2 * n])?,
+                scale_hex_channel(&hex[2 * n..])?,
+            ))
+        } else {
+            None
+        }
+    }
+
     /// Convert from vt100 color.
     pub fn from_vt100(c: vt100::Color, default: Color) -> Self {
         match c {
@@ -81,8 +325,18 @@ impl Color {
     }
 }
 
+/// Scale a 1-4 digit hex channel to 8 bits.
+fn scale_hex_channel(s: &str) -> Option<u8> {
+    if s.is_empty() || s.len() > 4 || !s.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return None;
+    }
+    let value = u32::from_str_radix(s, 16).ok()?;
+    let max = (1u32 << (4 * s.len())) - 1;
+    Some(((value * 255 + max / 2) / max) as u8)
+}
+
 /// Convert 256-color index to RGB.
-fn idx_to_color(idx: u8) -> Color {
+pub(crate) fn idx_to_color(idx: u8) -> Color {
     match idx {
         // Standard colors (0-15)
         0 => Color::new(0, 0, 0),        // Black
@@ -117,8 +371,61 @@ fn idx_to_color(idx: u8) -> Color {
     }
 }
 
+/// Identity reported in reply to device queries (DA, XTVERSION).
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(default)]
+pub struct DeviceReports {
+    /// Primary DA parameters, sent as `CSI ? <params> c`.
+    pub primary_da: String,
+    /// Secondary DA parameters, sent as `CSI > <params> c`.
+    pub secondary_da: String,
+    /// XTVERSION name, sent as `DCS > | <version> ST`.
+    pub version: String,
+}
+
+impl Default for DeviceReports {
+    fn default() -> Self {
+        Self {
+            // VT220 with ANSI color, like xterm-256color
+            primary_da: "62;22".to_string(),
+            secondary_da: "1;10;0".to_string(),
+            version: format!("tauri-plugin-terminal({})", env!("CARGO_PKG_VERSION")),
+        }
+    }
+}
+
+/// Dynamic color slots addressable via OSC 10/11/12.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum DynamicColor {
+    Foreground,
+    Background,
+    Cursor,
+}
+
+impl DynamicColor {
+    /// Map an OSC number (10, 11, 12) to its slot.
+    pub fn from_osc(code: u16) -> Option<Self> {
+        match code {
+            10 => Some(DynamicColor::Foreground),
+            11 => Some(DynamicColor::Background),
+            12 => Some(DynamicColor::Cursor),
+            _ => None,
+        }
+    }
+
+    /// The OSC number for this slot.
+    pub fn osc_code(&self) -> u16 {
+        match self {
+            DynamicColor::Foreground => 10,
+            DynamicColor::Background => 11,
+            DynamicColor::Cursor => 12,
+        }
+    }
+}
+
 /// Cell attributes (bold, italic, etc.).
-#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq, Eq, Hash)]
 pub struct CellAttributes {
     pub bold: bool,
     pub italic: bool,
@@ -138,19 +445,34 @@ impl CellAttributes {
             underline: cell.underline(),
             strikethrough: false,
             inverse: cell.inverse(),
-            dim: false,
+            dim: cell.dim(),
             blink: false,
         }
     }
 }
 
 /// A single terminal cell.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
 pub struct Cell {
     pub char: String,
     pub fg: Color,
     pub bg: Color,
     pub attrs: CellAttributes,
+    /// Columns occupied: 2 for a wide character, 0 for its continuation cell.
+    #[serde(default = "default_cell_width")]
+    pub width: u8,
+    /// `char` is the replacement character standing in for undisplayable
+    /// content (a control character or noncharacter).
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub substituted: bool,
+}
+
+fn default_cell_width() -> u8 {
+    1
+}
+
+fn is_false(value: &bool) -> bool {
+    !value
 }
 
 impl Default for Cell {
@@ -160,6 +482,8 @@ impl Default for Cell {
             fg: Color::new(255, 255, 255),
             bg: Color::new(0, 0, 0),
             attrs: CellAttributes::default(),
+            width: 1,
+            substituted: false,
         }
     }
 }
@@ -175,6 +499,54 @@ pub struct Screen {
     pub size: Size,
     pub scrollback_len: u32,
     pub title: String,
+    /// Hyperlinks on the screen, from OSC 8 and URLs detected in the text.
+    #[serde(default)]
+    pub links: Vec<LinkSpan>,
+}
+
+/// A hyperlink over part of a screen row.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct LinkSpan {
+    pub row: u16,
+    pub start_col: u16,
+    /// Column just past the end of the link.
+    pub end_col: u16,
+    pub url: String,
+    /// OSC 8 `id`, shared by the spans of one link; `None` for detected URLs.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+}
+
+/// Rows visible through the backend-tracked scrollback viewport.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Viewport {
+    /// Buffer line of the first visible row (0 = oldest scrollback line);
+    /// equal to `scrollback_len` when following the bottom.
+    pub top_line: u32,
+    pub scrollback_len: u32,
+    pub rows: Vec<Row>,
+}
+
+/// Which way `search_scrollback` looks from its starting position.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SearchDirection {
+    /// Toward the bottom of the buffer.
+    #[default]
+    Forward,
+    /// Toward the oldest scrollback line.
+    Backward,
+}
+
+/// A `search_scrollback` hit.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub struct SearchMatch {
+    /// Buffer line of the match (0 = oldest scrollback line).
+    pub line: u32,
+    /// Column of the first matched character.
+    pub start_col: u16,
+    /// Column just past the last matched character.
+    pub end_col: u16,
 }
 
 /// A change to a single cell (for incremental updates).
@@ -185,6 +557,17 @@ pub struct CellChange {
     pub cell: Cell,
 }
 
+/// A scroll within the DECSTBM region, applied before `changes`.
+///
+/// Rows `top..=bottom` move by `lines` (positive scrolls content up). The
+/// rows exposed by the shift are always included in `changes`.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ScrollRegionShift {
+    pub top: u16,
+    pub bottom: u16,
+    pub lines: i16,
+}
+
 /// Incremental screen update.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ScreenUpdate {
@@ -192,6 +575,155 @@ pub struct ScreenUpdate {
     pub changes: Vec<CellChange>,
     pub cursor: Cursor,
     pub title: Option<String>,
+    /// Scroll-region shift to apply before `changes`, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scroll: Option<ScrollRegionShift>,
+    /// Hint that the frontend should stick the viewport to the bottom.
+    #[serde(default)]
+    pub should_autoscroll: bool,
+}
+
+impl ScreenUpdate {
+    /// Whether `later` can be folded into this update without reordering.
+    ///
+    /// Updates carrying a scroll shift must be applied on their own.
+    pub fn can_merge(&self, later: &ScreenUpdate) -> bool {
+        self.session_id == later.session_id && later.scroll.is_none()
+    }
+
+    /// Fold a later update into this one; later cell values win.
+    pub fn merge(&mut self, later: ScreenUpdate) {
+        let mut index: HashMap<(u16, u16), usize> = self
+            .changes
+            .iter()
+            .enumerate()
+            .map(|(i, c)| ((c.row, c.col), i))
+            .collect();
+
+        for change in later.changes {
+            let key = (change.row, change.col);
+            match index.get(&key) {
+                Some(&i) => self.changes[i] = change,
+                None => {
+                    index.insert(key, self.changes.len());
+                    self.changes.push(change);
+                }
+            }
+        }
+
+        self.cursor = later.cursor;
+        self.should_autoscroll |= later.should_autoscroll;
+        if later.title.is_some() {
+            self.title = later.title;
+        }
+    }
+}
+
+/// Where an inline image (iTerm2 OSC 1337) sits in the buffer.
+///
+/// `row` is absolute like `Mark::row`. The image bytes are fetched
+/// separately so screen fetches stay small.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ImagePlacement {
+    pub id: u32,
+    pub row: u32,
+    pub col: u16,
+    /// Size in cells.
+    pub width: u16,
+    pub height: u16,
+    /// Native image size; zero when the format isn't recognized.
+    pub pixel_size: PixelSize,
+}
+
+/// Mouse events the running program asked to receive.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum MouseMode {
+    #[default]
+    None,
+    /// Button presses (X10, DECSET 9).
+    Press,
+    /// Presses and releases (DECSET 1000).
+    PressRelease,
+    /// Also motion while a button is held (DECSET 1002).
+    ButtonMotion,
+    /// Also all motion (DECSET 1003).
+    AnyMotion,
+}
+
+/// How mouse reports are encoded.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum MouseEncoding {
+    #[default]
+    Default,
+    /// DECSET 1005.
+    Utf8,
+    /// DECSET 1006.
+    Sgr,
+}
+
+/// What a session supports and which modes the running program has on.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Capabilities {
+    /// iTerm2 inline images (OSC 1337).
+    pub inline_images: bool,
+    /// Sixel graphics.
+    pub sixel: bool,
+    /// Clipboard writes through OSC 52.
+    pub clipboard_write: bool,
+    /// 24-bit color (advertised through `COLORTERM`).
+    pub truecolor: bool,
+    /// Whether the shell has emitted OSC 133 marks.
+    pub shell_integration: bool,
+    /// Maximum lines kept in scrollback.
+    pub scrollback_lines: usize,
+    /// `TERM` reported to the program.
+    pub term: String,
+    pub mouse_mode: MouseMode,
+    pub mouse_encoding: MouseEncoding,
+    pub alt_screen: bool,
+    pub bracketed_paste: bool,
+    pub application_cursor: bool,
+    /// Insert mode (IRM): typed text should push the rest of the line right.
+    pub insert_mode: bool,
+}
+
+/// The command a session's shell was started with, enough to launch an
+/// equivalent shell elsewhere (e.g. in the system terminal).
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct SpawnSpec {
+    pub program: String,
+    /// Arguments after the program name, login flags included.
+    pub args: Vec<String>,
+    /// Directory the shell started in.
+    pub cwd: Option<String>,
+    /// Full environment, inherited variables included.
+    pub env: HashMap<String, String>,
+}
+
+/// Approximate memory held by a session's buffers, in bytes unless noted.
+///
+/// Grid sizes are estimated from the cell count, so they track growth
+/// rather than exact allocations.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq, Eq)]
+pub struct BufferStats {
+    /// Lines retained in scrollback.
+    pub scrollback_lines: u64,
+    pub scrollback_bytes: u64,
+    /// Base64 payloads of retained inline images.
+    pub image_bytes: u64,
+    /// Primary and alternate screen grids.
+    pub grid_bytes: u64,
+}
+
+impl std::ops::AddAssign for BufferStats {
+    fn add_assign(&mut self, other: Self) {
+        self.scrollback_lines += other.scrollback_lines;
+        self.scrollback_bytes += other.scrollback_bytes;
+        self.image_bytes += other.image_bytes;
+        self.grid_bytes += other.grid_bytes;
+    }
 }
 
 /// Shell integration mark.
@@ -211,3 +743,108 @@ pub enum MarkType {
     CommandStart,
     CommandEnd,
 }
+
+/// Outcome of one line submitted by `run_script`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScriptLineResult {
+    pub command: String,
+    /// Exit code from shell integration; `None` without OSC 133 support.
+    pub exit_code: Option<i32>,
+    /// Whether the line succeeded; `None` when it was taken as finished
+    /// because output went quiet, without OSC 133 support, so its result
+    /// is unknown.
+    pub success: Option<bool>,
+    /// The command's output, from shell integration marks; `None`
+    /// without OSC 133 support.
+    #[serde(default)]
+    pub output: Option<String>,
+}
+
+/// What `run_script` does to a line still running at its timeout.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum TimeoutAction {
+    /// Leave it running.
+    #[default]
+    Leave,
+    /// Send Ctrl-C, interrupting the foreground job.
+    Interrupt,
+    /// Kill the session.
+    Kill,
+}
+
+/// Text found by `expect`, with offsets into the session's output after
+/// escape sequences are stripped.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct MatchRange {
+    pub start: u64,
+    pub end: u64,
+    pub text: String,
+}
+
+/// A key press from the frontend.
+///
+/// `key` uses DOM `KeyboardEvent.key` values (`"a"`, `"Enter"`, `"ArrowUp"`, `"F5"`).
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct KeyEvent {
+    pub key: String,
+    #[serde(default)]
+    pub ctrl: bool,
+    #[serde(default)]
+    pub shift: bool,
+    #[serde(default)]
+    pub alt: bool,
+    #[serde(default)]
+    pub meta: bool,
+    /// Press, repeat or release; only the kitty keyboard protocol reports
+    /// repeats and releases distinctly.
+    #[serde(default)]
+    pub event_type: KeyEventType,
+}
+
+/// Kind of key event.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum KeyEventType {
+    #[default]
+    Press,
+    Repeat,
+    Release,
+}
+
+/// Action triggered by a key binding instead of sending the key to the PTY.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum BindingAction {
+    Copy,
+    Paste,
+    Interrupt,
+    Custom(String),
+}
+
+/// Maps a key and exact modifier set to an action.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct KeyBinding {
+    pub key: String,
+    #[serde(default)]
+    pub ctrl: bool,
+    #[serde(default)]
+    pub shift: bool,
+    #[serde(default)]
+    pub alt: bool,
+    #[serde(default)]
+    pub meta: bool,
+    pub action: BindingAction,
+}
+
+impl KeyBinding {
+    /// Whether `event` triggers this binding. Keys compare case-insensitively
+    /// since Shift changes the reported character.
+    pub fn matches(&self, event: &KeyEvent) -> bool {
+        self.key.eq_ignore_ascii_case(&event.key)
+            && self.ctrl == event.ctrl
+            && self.shift == event.shift
+            && self.alt == event.alt
+            && self.meta == event.meta
+    }
+}
@@ -0,0 +1,49 @@
+//! 8-bit C1 control handling.
+
+use tauri_plugin_terminal::{C1Mode, Terminal};
+
+fn first_line(terminal: &Terminal) -> String {
+    terminal.screen_text().lines().next().unwrap_or_default().to_string()
+}
+
+#[test]
+fn eight_bit_csi_moves_the_cursor() {
+    let mut terminal = Terminal::new(10, 3);
+    terminal.set_c1_mode(C1Mode::EightBit);
+
+    terminal.process(b"ab\x9b1;1Hx");
+
+    assert_eq!(first_line(&terminal), "xb");
+}
+
+#[test]
+fn utf8_continuation_bytes_are_not_c1() {
+    let mut terminal = Terminal::new(10, 3);
+    terminal.set_c1_mode(C1Mode::EightBit);
+
+    // U+0100 is C4 80; the 0x80 continues the character rather than being PAD.
+    terminal.process("\u{100}z".as_bytes());
+
+    assert_eq!(first_line(&terminal), "\u{100}z");
+}
+
+#[test]
+fn c1_bytes_are_text_by_default() {
+    let mut terminal = Terminal::new(10, 3);
+
+    terminal.process(b"ab\x9b1;1Hx");
+
+    assert!(first_line(&terminal).starts_with("ab"));
+}
+
+#[test]
+fn s8c1t_switches_replies_to_eight_bit() {
+    let mut terminal = Terminal::new(10, 3);
+    terminal.set_c1_mode(C1Mode::EightBit);
+
+    terminal.process(b"\x1b G\x1b[6n");
+    assert_eq!(terminal.take_responses(), b"\x9b1;1R");
+
+    terminal.process(b"\x1b F\x1b[6n");
+    assert_eq!(terminal.take_responses(), b"\x1b[1;1R");
+}
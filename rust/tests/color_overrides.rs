@@ -0,0 +1,50 @@
+//! Per-session indexed color overrides.
+#![cfg(all(feature = "testing", unix))]
+
+use std::collections::HashMap;
+use tauri_plugin_terminal::testing::TestHarness;
+use tauri_plugin_terminal::{Color, SessionConfig};
+
+const ORANGE: Color = Color::new(255, 140, 0);
+
+fn session(harness: &TestHarness, color_overrides: HashMap<u8, Color>) -> String {
+    harness
+        .spawn(SessionConfig {
+            shell: Some("/bin/sh".into()),
+            shell_args: vec!["-c".into(), "sleep 10".into()],
+            color_overrides,
+            ..Default::default()
+        })
+        .unwrap()
+}
+
+fn first_fg(harness: &TestHarness, id: &str) -> Color {
+    harness.manager().get_screen(id).unwrap().cells[0][0].fg
+}
+
+#[test]
+fn configured_override_changes_red_text() {
+    let harness = TestHarness::new();
+    let plain = session(&harness, HashMap::new());
+    let remapped = session(&harness, HashMap::from([(1, ORANGE)]));
+
+    harness.feed(&plain, b"\x1b[31mx").unwrap();
+    harness.feed(&remapped, b"\x1b[31mx").unwrap();
+
+    assert_ne!(first_fg(&harness, &plain), ORANGE);
+    assert_eq!(first_fg(&harness, &remapped), ORANGE);
+}
+
+#[test]
+fn override_can_be_set_and_removed_at_runtime() {
+    let harness = TestHarness::new();
+    let id = session(&harness, HashMap::new());
+    harness.feed(&id, b"\x1b[31mx").unwrap();
+    let red = first_fg(&harness, &id);
+
+    harness.manager().set_color_override(&id, 1, Some(ORANGE)).unwrap();
+    assert_eq!(first_fg(&harness, &id), ORANGE);
+
+    harness.manager().set_color_override(&id, 1, None).unwrap();
+    assert_eq!(first_fg(&harness, &id), red);
+}
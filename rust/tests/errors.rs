@@ -0,0 +1,21 @@
+//! How errors cross to the frontend.
+
+use tauri_plugin_terminal::Error;
+
+#[test]
+fn errors_serialize_as_a_message_ending_in_the_code() {
+    let json = serde_json::to_string(&Error::SessionNotFound("abc".into())).unwrap();
+    assert_eq!(json, r#""Session not found: abc [session_not_found]""#);
+
+    let json = serde_json::to_string(&Error::WriteAfterExit).unwrap();
+    assert_eq!(json, r#""Process has exited [write_after_exit]""#);
+}
+
+#[test]
+fn deserializing_drops_the_code() {
+    let error: Error = serde_json::from_str(r#""Session not found: abc [session_not_found]""#).unwrap();
+    assert!(matches!(error, Error::TerminalError(message) if message == "Session not found: abc"));
+
+    let error: Error = serde_json::from_str(r#""Bad value [x, y]""#).unwrap();
+    assert!(matches!(error, Error::TerminalError(message) if message == "Bad value [x, y]"));
+}
@@ -0,0 +1,90 @@
+//! Exporting lines from the buffer.
+#![cfg(all(feature = "testing", unix))]
+
+use tauri_plugin_terminal::testing::TestHarness;
+use tauri_plugin_terminal::{CursorPosition, ExportFormat, SessionConfig, Terminal};
+
+/// A 20x5 session holding `lines` numbered lines, most of them in scrollback.
+fn session_with_lines(harness: &TestHarness, lines: usize) -> String {
+    let id = harness
+        .spawn(SessionConfig {
+            shell: Some("/bin/sh".into()),
+            shell_args: vec!["-c".into(), "sleep 10".into()],
+            cols: Some(20),
+            rows: Some(5),
+            ..Default::default()
+        })
+        .unwrap();
+    let text: String = (0..lines).map(|n| format!("line {}\r\n", n)).collect();
+    harness.feed(&id, text.as_bytes()).unwrap();
+    id
+}
+
+#[test]
+fn ansi_range_reads_deep_scrollback() {
+    let harness = TestHarness::new();
+    let id = session_with_lines(&harness, 100);
+
+    let ansi = harness.manager().export_ansi_range(&id, 10, 3, false).unwrap();
+
+    assert_eq!(
+        ansi,
+        "\x1b[mline 10\x1b[m\r\n\x1b[mline 11\x1b[m\r\n\x1b[mline 12\x1b[m\r\n"
+    );
+}
+
+#[test]
+fn file_export_matches_text_export_across_pages() {
+    let harness = TestHarness::new();
+    let id = session_with_lines(&harness, 1200);
+
+    let path = std::env::temp_dir().join(format!("terminal-export-{}.txt", std::process::id()));
+    let written = harness
+        .manager()
+        .export_to_file(&id, &path, ExportFormat::Text, false)
+        .unwrap();
+    let exported = std::fs::read_to_string(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(exported, harness.manager().export_text(&id, false).unwrap());
+    assert_eq!(exported.lines().count(), written);
+    assert!(exported.starts_with("line 0\n") && exported.contains("line 1199\n"));
+}
+
+#[test]
+fn wrapped_lines_are_rejoined_on_copy() {
+    let mut terminal = Terminal::new(80, 5);
+    let long: String = (0..200).map(|n| char::from(b'a' + (n % 26) as u8)).collect();
+    terminal.process(format!("{}\r\nnext", long).as_bytes());
+
+    let text = terminal.export_text(false);
+    assert!(text.starts_with(&format!("{}\nnext\n", long)), "{:?}", text);
+
+    let selected = terminal.selection_text(
+        CursorPosition { row: 0, col: 0 },
+        CursorPosition { row: 2, col: 39 },
+        false,
+    );
+    assert_eq!(selected, long);
+
+    let wrapped = terminal.export_text(true);
+    assert!(wrapped.starts_with(&format!("{}\n{}\n{}\n", &long[..80], &long[80..160], &long[160..])));
+}
+
+#[test]
+fn ansi_export_can_normalize_newlines() {
+    let harness = TestHarness::new();
+    let id = session_with_lines(&harness, 0);
+    harness.feed(&id, b"$ make\r\n50%\r100%\r\ndone\r\n").unwrap();
+
+    let raw = harness.manager().export_ansi_range(&id, 0, 3, false).unwrap();
+    let normalized = harness.manager().export_ansi_range(&id, 0, 3, true).unwrap();
+
+    assert_eq!(raw.matches("\r\n").count(), 3);
+    assert!(!normalized.contains('\r'));
+    assert_eq!(normalized, raw.replace("\r\n", "\n"));
+    assert_eq!(
+        normalized,
+        "\x1b[m$ make\x1b[m\n\x1b[m100%\x1b[m\n\x1b[mdone\x1b[m\n"
+    );
+}
@@ -0,0 +1,33 @@
+//! Fitting a cell grid to a pixel area.
+
+use tauri_plugin_terminal::{fit_cells, Size, MIN_FIT_SIZE};
+
+#[test]
+fn partial_cells_are_dropped() {
+    assert_eq!(fit_cells(805, 399, 8.0, 16.0), Size { cols: 100, rows: 24 });
+    assert_eq!(fit_cells(807, 400, 8.0, 16.0), Size { cols: 100, rows: 25 });
+}
+
+#[test]
+fn fractional_cells_that_fit_exactly_are_kept() {
+    // 249 / 8.3 and 513 / 17.1 are whole counts that float division lands just under
+    assert_eq!(fit_cells(249, 513, 8.3, 17.1), Size { cols: 30, rows: 30 });
+    assert_eq!(fit_cells(248, 512, 8.3, 17.1), Size { cols: 29, rows: 29 });
+}
+
+#[test]
+fn tiny_areas_get_the_minimum_size() {
+    assert_eq!(fit_cells(0, 0, 8.0, 16.0), MIN_FIT_SIZE);
+    assert_eq!(fit_cells(10, 10, 8.0, 16.0), MIN_FIT_SIZE);
+}
+
+#[test]
+fn degenerate_cell_sizes_get_the_minimum_size() {
+    assert_eq!(fit_cells(800, 600, 0.0, -1.0), MIN_FIT_SIZE);
+    assert_eq!(fit_cells(800, 600, f64::NAN, f64::INFINITY), MIN_FIT_SIZE);
+}
+
+#[test]
+fn huge_areas_saturate() {
+    assert_eq!(fit_cells(u32::MAX, u32::MAX, 0.001, 0.001), Size { cols: u16::MAX, rows: u16::MAX });
+}
@@ -0,0 +1,249 @@
+//! Session exit reporting and retention.
+#![cfg(all(feature = "testing", unix))]
+
+use std::time::{Duration, Instant};
+use tauri_plugin_terminal::testing::TestHarness;
+use tauri_plugin_terminal::{Error, ExitReason, SessionConfig, TerminalEvent};
+
+fn sh(script: &str) -> SessionConfig {
+    SessionConfig {
+        shell: Some("/bin/sh".into()),
+        shell_args: vec!["-c".into(), script.into()],
+        ..Default::default()
+    }
+}
+
+/// Drive the manager as the poll loop would until `predicate` matches an
+/// event, returning every event seen.
+fn run_until<F>(harness: &mut TestHarness, timeout: Duration, predicate: F) -> Vec<TerminalEvent>
+where
+    F: Fn(&TerminalEvent) -> bool,
+{
+    let deadline = Instant::now() + timeout;
+    let mut seen = Vec::new();
+    while Instant::now() < deadline {
+        harness.manager().process_all();
+        harness.manager().cleanup_dead();
+        seen.extend(harness.events());
+        if seen.iter().any(&predicate) {
+            break;
+        }
+        std::thread::sleep(Duration::from_millis(5));
+    }
+    seen
+}
+
+fn exit_code(events: &[TerminalEvent]) -> Option<Option<i32>> {
+    events.iter().find_map(|event| match event {
+        TerminalEvent::ProcessExit { exit_code, .. } => Some(*exit_code),
+        _ => None,
+    })
+}
+
+#[test]
+fn exit_code_is_reported() {
+    let mut harness = TestHarness::new();
+    let id = harness.spawn(sh("exit 7")).unwrap();
+
+    let events = run_until(&mut harness, Duration::from_secs(5), |event| {
+        matches!(event, TerminalEvent::ProcessExit { .. })
+    });
+
+    assert_eq!(exit_code(&events), Some(Some(7)));
+    assert!(harness.manager().get_screen(&id).is_ok());
+}
+
+#[test]
+fn exited_session_is_kept_until_destroyed() {
+    let mut harness = TestHarness::new();
+    let id = harness.spawn(sh("echo bye")).unwrap();
+
+    run_until(&mut harness, Duration::from_secs(5), |event| {
+        matches!(event, TerminalEvent::ProcessExit { .. })
+    });
+    for _ in 0..10 {
+        assert!(harness.manager().cleanup_dead().is_empty());
+    }
+    assert!(harness.screen_text(&id).unwrap().contains("bye"));
+
+    harness.manager().destroy(&id).unwrap();
+    assert!(harness.manager().get_screen(&id).is_err());
+}
+
+#[test]
+fn exited_session_is_removed_after_retention() {
+    let mut harness = TestHarness::new();
+    let id = harness
+        .spawn(SessionConfig {
+            exit_retention_ms: Some(0),
+            ..sh("exit 0")
+        })
+        .unwrap();
+
+    let events = run_until(&mut harness, Duration::from_secs(5), |event| {
+        matches!(event, TerminalEvent::SessionDestroyed { .. })
+    });
+
+    assert_eq!(exit_code(&events), Some(Some(0)));
+    assert!(events
+        .iter()
+        .any(|event| matches!(event, TerminalEvent::SessionDestroyed { session_id } if *session_id == id)));
+    assert!(harness.manager().get_screen(&id).is_err());
+}
+
+#[test]
+fn retained_exited_session_does_not_count_toward_the_limit() {
+    let mut harness = TestHarness::with_manager(|manager| manager.with_max_sessions(Some(1)));
+    let exited = harness.spawn(sh("exit 0")).unwrap();
+    assert!(matches!(harness.spawn(sh("sleep 10")), Err(Error::SessionLimitReached(1))));
+
+    run_until(&mut harness, Duration::from_secs(5), |event| {
+        matches!(event, TerminalEvent::ProcessExit { .. })
+    });
+    assert_eq!(harness.manager().remaining_capacity(), Some(1));
+
+    harness.spawn(sh("sleep 10")).unwrap();
+    assert!(harness.manager().get_screen(&exited).is_ok());
+    assert_eq!(harness.manager().remaining_capacity(), Some(0));
+    assert!(matches!(harness.spawn(sh("sleep 10")), Err(Error::SessionLimitReached(1))));
+}
+
+#[test]
+fn idle_session_is_reaped_and_destroyed() {
+    let mut harness = TestHarness::new();
+    let id = harness
+        .spawn(SessionConfig {
+            idle_timeout_ms: Some(50),
+            ..sh("sleep 10")
+        })
+        .unwrap();
+
+    let deadline = Instant::now() + Duration::from_secs(5);
+    let mut reaped = Vec::new();
+    while reaped.is_empty() && Instant::now() < deadline {
+        std::thread::sleep(Duration::from_millis(10));
+        reaped = harness.manager().reap_idle();
+    }
+
+    assert_eq!(reaped, vec![id.clone()]);
+    let events = harness.events();
+    assert!(events.iter().any(|event| matches!(
+        event,
+        TerminalEvent::ProcessExit { reason: ExitReason::IdleTimeout, .. }
+    )));
+    assert!(events
+        .iter()
+        .any(|event| matches!(event, TerminalEvent::SessionDestroyed { session_id } if *session_id == id)));
+    assert!(harness.manager().get_screen(&id).is_err());
+}
+
+#[test]
+fn terminating_signal_is_reported() {
+    let mut harness = TestHarness::new();
+    harness.spawn(sh("kill -USR1 $$")).unwrap();
+
+    let events = run_until(&mut harness, Duration::from_secs(5), |event| {
+        matches!(event, TerminalEvent::ProcessExit { .. })
+    });
+
+    let signal = events.iter().find_map(|event| match event {
+        TerminalEvent::ProcessExit { signal, .. } => Some(*signal),
+        _ => None,
+    });
+    assert_eq!(signal, Some(Some(libc::SIGUSR1)));
+}
+
+#[test]
+fn exit_is_reported_while_a_background_child_holds_the_pty() {
+    let mut harness = TestHarness::new();
+    harness.spawn(sh("sleep 5 & exit 3")).unwrap();
+
+    let start = Instant::now();
+    let events = run_until(&mut harness, Duration::from_secs(5), |event| {
+        matches!(event, TerminalEvent::ProcessExit { .. })
+    });
+
+    assert_eq!(exit_code(&events), Some(Some(3)));
+    assert!(start.elapsed() < Duration::from_secs(2), "exit took {:?}", start.elapsed());
+}
+
+#[test]
+fn child_exit_wakes_the_manager() {
+    let harness = TestHarness::new();
+    harness.spawn(sh("exit 0")).unwrap();
+
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_time()
+        .build()
+        .unwrap();
+    let woken = runtime.block_on(async {
+        tokio::time::timeout(Duration::from_secs(5), harness.manager().exit_notified()).await
+    });
+    assert!(woken.is_ok());
+}
+
+#[test]
+fn child_ignoring_sighup_is_killed() {
+    let mut harness = TestHarness::new();
+    let id = harness.spawn(sh("trap '' HUP; exec sleep 10")).unwrap();
+    std::thread::sleep(Duration::from_millis(100));
+
+    harness.manager().kill(&id).unwrap();
+    let events = run_until(&mut harness, Duration::from_secs(5), |event| {
+        matches!(event, TerminalEvent::ProcessExit { .. })
+    });
+
+    let signal = events.iter().find_map(|event| match event {
+        TerminalEvent::ProcessExit { signal, .. } => Some(*signal),
+        _ => None,
+    });
+    assert_eq!(signal, Some(Some(libc::SIGKILL)));
+}
+
+#[test]
+fn writing_to_an_exited_session_is_write_after_exit() {
+    let mut harness = TestHarness::new();
+    let id = harness.spawn(sh("exit 0")).unwrap();
+
+    run_until(&mut harness, Duration::from_secs(5), |event| {
+        matches!(event, TerminalEvent::ProcessExit { .. })
+    });
+
+    assert!(matches!(harness.manager().write(&id, b"ls\r"), Err(Error::WriteAfterExit)));
+    assert!(matches!(harness.manager().resize(&id, 100, 30), Err(Error::WriteAfterExit)));
+}
+
+#[test]
+fn final_output_is_sent_before_exit() {
+    let mut harness = TestHarness::new();
+    harness.spawn(sh("echo done; exit 0")).unwrap();
+
+    // Only the exit path runs, so the output must be drained by it.
+    let deadline = Instant::now() + Duration::from_secs(5);
+    let mut events = Vec::new();
+    while !events.iter().any(|event| matches!(event, TerminalEvent::ProcessExit { .. }))
+        && Instant::now() < deadline
+    {
+        harness.manager().cleanup_dead();
+        events.extend(harness.events());
+        std::thread::sleep(Duration::from_millis(5));
+    }
+
+    let exit = events
+        .iter()
+        .position(|event| matches!(event, TerminalEvent::ProcessExit { .. }))
+        .expect("no ProcessExit");
+    let shown: String = events[..exit]
+        .iter()
+        .flat_map(|event| match event {
+            TerminalEvent::ScreenUpdate(update) => {
+                update.changes.iter().map(|change| change.cell.char.clone()).collect()
+            }
+            TerminalEvent::ScreenRefresh { screen, .. } => {
+                screen.cells.iter().flatten().map(|cell| cell.char.clone()).collect()
+            }
+            _ => Vec::new(),
+        })
+        .collect();
+    assert!(shown.contains("done"), "{:?}", shown);
+}
@@ -0,0 +1,63 @@
+//! Tracking and reporting terminal modes.
+
+use tauri_plugin_terminal::Terminal;
+
+#[test]
+fn insert_mode_is_tracked() {
+    let mut terminal = Terminal::new(10, 2);
+    assert!(!terminal.insert_mode());
+
+    terminal.process(b"\x1b[4h");
+    assert!(terminal.insert_mode());
+    terminal.process(b"\x1b[4$p");
+    assert_eq!(terminal.take_responses(), b"\x1b[4;1$y");
+
+    terminal.process(b"\x1b[4l");
+    assert!(!terminal.insert_mode());
+    terminal.process(b"\x1b[4$p");
+    assert_eq!(terminal.take_responses(), b"\x1b[4;2$y");
+}
+
+#[test]
+fn reset_clears_insert_mode() {
+    let mut terminal = Terminal::new(10, 2);
+
+    terminal.process(b"\x1b[4h\x1bc");
+
+    assert!(!terminal.insert_mode());
+}
+
+#[test]
+fn origin_mode_reports_margin_relative_positions() {
+    let mut terminal = Terminal::new(20, 12);
+
+    terminal.process(b"\x1b[5;10r\x1b[?6h\x1b[2;3H\x1b[6n\x1b[?6n");
+
+    assert_eq!(terminal.take_responses(), b"\x1b[2;3R\x1b[?2;3;1R");
+    let cursor = terminal.get_cursor();
+    assert_eq!((cursor.position.row, cursor.position.col), (5, 2));
+    let origin = cursor.origin_position.expect("origin mode is set");
+    assert_eq!((origin.row, origin.col), (1, 2));
+}
+
+#[test]
+fn leaving_origin_mode_reports_absolute_positions() {
+    let mut terminal = Terminal::new(20, 12);
+
+    terminal.process(b"\x1b[5;10r\x1b[?6h\x1b[?6l\x1b[2;3H\x1b[6n");
+
+    assert_eq!(terminal.take_responses(), b"\x1b[2;3R");
+    assert_eq!(terminal.get_cursor().position.row, 1);
+    assert!(terminal.get_cursor().origin_position.is_none());
+}
+
+#[test]
+fn decrqm_reports_origin_and_autowrap_modes() {
+    let mut terminal = Terminal::new(20, 12);
+
+    terminal.process(b"\x1b[?6$p\x1b[?7$p");
+    assert_eq!(terminal.take_responses(), b"\x1b[?6;2$y\x1b[?7;1$y");
+
+    terminal.process(b"\x1b[?6h\x1b[?7l\x1b[?6$p\x1b[?7$p");
+    assert_eq!(terminal.take_responses(), b"\x1b[?6;1$y\x1b[?7;2$y");
+}
@@ -0,0 +1,94 @@
+//! Reading program output.
+#![cfg(all(feature = "testing", unix))]
+
+use std::time::{Duration, Instant};
+use tauri_plugin_terminal::testing::TestHarness;
+use tauri_plugin_terminal::{SessionConfig, TerminalEvent};
+
+fn base64_decode(text: &str) -> Vec<u8> {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut out = Vec::new();
+    for chunk in text.as_bytes().chunks(4) {
+        let sextets: Vec<u32> = chunk
+            .iter()
+            .take_while(|&&c| c != b'=')
+            .map(|c| ALPHABET.iter().position(|a| a == c).unwrap() as u32)
+            .collect();
+        let n = sextets.iter().fold(0, |n, s| n << 6 | s) << (6 * (4 - sextets.len()));
+        out.extend_from_slice(&n.to_be_bytes()[1..sextets.len()]);
+    }
+    out
+}
+
+/// Pump a raw session until its output ends with `end`, returning each
+/// `RawOutput` chunk; panics if a chunk isn't valid UTF-8 on its own.
+fn raw_chunks_until(harness: &mut TestHarness, id: &str, end: &str) -> Vec<String> {
+    let mut output: Vec<String> = Vec::new();
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while !output.concat().ends_with(end) && Instant::now() < deadline {
+        std::thread::sleep(Duration::from_millis(10));
+        while harness.pump(id).unwrap().is_some() {}
+        for event in harness.events() {
+            if let TerminalEvent::RawOutput { data, .. } = event {
+                output.push(String::from_utf8(base64_decode(&data)).expect("chunk splits a character"));
+            }
+        }
+    }
+    output
+}
+
+#[test]
+fn character_split_across_the_startup_window_is_sent_whole() {
+    let mut harness = TestHarness::new();
+    // The euro sign's first two bytes land in the startup window, the last after it
+    let id = harness
+        .spawn(SessionConfig {
+            shell: Some("/bin/sh".into()),
+            shell_args: vec!["-c".into(), r"printf '\342\202'; sleep 0.5; printf '\254!'; sleep 10".into()],
+            emulate: false,
+            initial_coalesce_ms: Some(200),
+            ..Default::default()
+        })
+        .unwrap();
+
+    let output = raw_chunks_until(&mut harness, &id, "!");
+    assert_eq!(output.concat(), "€!");
+}
+
+#[test]
+fn character_split_across_reads_is_sent_whole() {
+    let mut harness = TestHarness::new();
+    let id = harness
+        .spawn(SessionConfig {
+            shell: Some("/bin/sh".into()),
+            shell_args: vec!["-c".into(), r"printf '\360\237'; sleep 0.2; printf '\232\200!'; sleep 10".into()],
+            emulate: false,
+            ..Default::default()
+        })
+        .unwrap();
+
+    let output = raw_chunks_until(&mut harness, &id, "!");
+
+    assert_eq!(output.concat(), "\u{1f680}!");
+}
+
+#[test]
+fn character_split_across_reads_renders_whole() {
+    let harness = TestHarness::new();
+    let id = harness
+        .spawn(SessionConfig {
+            shell: Some("/bin/sh".into()),
+            shell_args: vec!["-c".into(), r"printf '\360\237'; sleep 0.2; printf '\232\200!'; sleep 10".into()],
+            ..Default::default()
+        })
+        .unwrap();
+
+    let shown = harness
+        .pump_until(&id, Duration::from_secs(5), |screen| {
+            screen.cells[0].iter().any(|cell| cell.char == "!")
+        })
+        .unwrap();
+
+    assert!(shown);
+    assert!(harness.screen_text(&id).unwrap().starts_with("\u{1f680}!"));
+}
@@ -0,0 +1,42 @@
+//! Dropping sessions must not leave threads or child processes behind.
+//!
+//! Kept in its own test binary so other tests' threads don't skew the count.
+#![cfg(all(feature = "testing", target_os = "linux"))]
+
+use std::path::Path;
+use std::time::{Duration, Instant};
+use tauri_plugin_terminal::testing::TestHarness;
+use tauri_plugin_terminal::SessionConfig;
+
+fn thread_count() -> usize {
+    std::fs::read_dir("/proc/self/task").unwrap().count()
+}
+
+#[test]
+fn destroyed_sessions_release_threads_and_processes() {
+    let harness = TestHarness::new();
+    let before = thread_count();
+
+    let mut pids = Vec::new();
+    for _ in 0..20 {
+        let id = harness
+            .spawn(SessionConfig {
+                shell: Some("/bin/sh".into()),
+                ..Default::default()
+            })
+            .unwrap();
+        pids.push(harness.manager().diagnostics(&id).unwrap().pid.unwrap());
+        harness.manager().destroy(&id).unwrap();
+    }
+
+    let reaped = |pid: &u32| !Path::new(&format!("/proc/{}", pid)).exists();
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while (thread_count() > before || !pids.iter().all(reaped)) && Instant::now() < deadline {
+        std::thread::sleep(Duration::from_millis(10));
+    }
+
+    assert!(thread_count() <= before, "{} threads left of {}", thread_count(), before);
+    for pid in &pids {
+        assert!(reaped(pid), "child {} was not reaped", pid);
+    }
+}
@@ -0,0 +1,33 @@
+//! Session ids stay unique when sessions are created concurrently.
+#![cfg(all(feature = "testing", unix))]
+
+use tauri_plugin_terminal::testing::TestHarness;
+use tauri_plugin_terminal::{Error, SessionConfig};
+
+#[test]
+fn concurrent_creates_with_one_id_register_once() {
+    let harness = TestHarness::new();
+    let manager = harness.manager();
+
+    let results: Vec<_> = std::thread::scope(|scope| {
+        let handles: Vec<_> = (0..8)
+            .map(|_| {
+                scope.spawn(|| {
+                    manager.create(SessionConfig {
+                        id: Some("shared".into()),
+                        shell: Some("/bin/sh".into()),
+                        ..Default::default()
+                    })
+                })
+            })
+            .collect();
+        handles.into_iter().map(|h| h.join().unwrap()).collect()
+    });
+
+    assert_eq!(results.iter().filter(|r| r.is_ok()).count(), 1);
+    assert!(results
+        .iter()
+        .filter_map(|r| r.as_ref().err())
+        .all(|e| matches!(e, Error::SessionAlreadyExists(id) if id == "shared")));
+    assert_eq!(manager.count(), 1);
+}
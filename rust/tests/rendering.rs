@@ -0,0 +1,89 @@
+//! How output maps to rendered cells.
+
+use tauri_plugin_terminal::Terminal;
+
+fn first_cells(terminal: &Terminal, count: u16) -> Vec<(String, bool)> {
+    (0..count)
+        .map(|col| {
+            let cell = terminal.get_cell(0, col);
+            (cell.char, cell.substituted)
+        })
+        .collect()
+}
+
+#[test]
+fn c0_control_between_text_leaves_no_cell() {
+    let mut terminal = Terminal::new(10, 2);
+
+    terminal.process(b"a\x01\x7fb");
+
+    assert_eq!(
+        first_cells(&terminal, 2),
+        [("a".to_string(), false), ("b".to_string(), false)]
+    );
+}
+
+#[test]
+fn undisplayable_characters_are_substituted() {
+    let mut terminal = Terminal::new(10, 2);
+
+    terminal.process("a\u{fdd0}b".as_bytes());
+
+    assert_eq!(
+        first_cells(&terminal, 3),
+        [("a".to_string(), false), ("\u{fffd}".to_string(), true), ("b".to_string(), false)]
+    );
+}
+
+#[test]
+fn replacement_character_is_configurable() {
+    let mut terminal = Terminal::new(10, 2);
+    terminal.set_replacement_char('?');
+
+    terminal.process("\u{ffff}".as_bytes());
+
+    assert_eq!(first_cells(&terminal, 1), [("?".to_string(), true)]);
+}
+
+#[test]
+fn dec_special_graphics_draw_a_box() {
+    let mut terminal = Terminal::new(10, 4);
+
+    terminal.process(b"\x1b(0lqqk\r\nx  x\r\nmqqj\x1b(B\r\nlqk");
+
+    assert_eq!(terminal.screen_text(), "┌──┐\n│  │\n└──┘\nlqk");
+}
+
+#[test]
+fn charset_designation_split_across_writes_is_honored() {
+    let mut terminal = Terminal::new(10, 2);
+
+    terminal.process(b"\x1b(");
+    terminal.process(b"0lqk");
+
+    assert_eq!(terminal.screen_text(), "┌─┐\n");
+}
+
+#[test]
+fn line_drawing_can_be_turned_off() {
+    let mut terminal = Terminal::new(10, 2);
+    terminal.set_line_drawing(false);
+
+    terminal.process(b"\x1b(0lqk");
+
+    assert_eq!(terminal.screen_text(), "lqk\n");
+}
+
+#[test]
+fn title_and_bell_are_reported() {
+    let mut terminal = Terminal::new(10, 2);
+
+    terminal.process(b"\x1b]2;build\x07\x07");
+    assert_eq!(terminal.title(), "build");
+    assert!(terminal.check_bell());
+
+    // An empty title keeps the last one, and the bell is per `process` call
+    terminal.process(b"\x1b]0;\x07x");
+    assert_eq!(terminal.title(), "build");
+    assert!(!terminal.check_bell());
+}
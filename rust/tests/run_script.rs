@@ -0,0 +1,102 @@
+//! Running scripts line by line with `run_script`.
+#![cfg(all(feature = "testing", unix))]
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+use tauri_plugin_terminal::testing::TestHarness;
+use tauri_plugin_terminal::{Error, Result, ScriptLineResult, SessionConfig, TimeoutAction};
+
+/// An interactive `sh`, with OSC 133 prompt marks in its prompt when `marked`.
+fn shell(marked: bool) -> SessionConfig {
+    let prompt = if marked { "\x1b]133;A\x07$ " } else { "$ " };
+    SessionConfig {
+        shell: Some("/bin/sh".into()),
+        env: HashMap::from([("PS1".to_string(), prompt.to_string())]),
+        ..Default::default()
+    }
+}
+
+/// Run `lines` while processing session output as the plugin's poll loop would.
+fn run_script(harness: &TestHarness, id: &str, lines: &[&str], timeout: Duration) -> Result<Vec<ScriptLineResult>> {
+    let manager = harness.manager();
+    let done = AtomicBool::new(false);
+    std::thread::scope(|scope| {
+        scope.spawn(|| {
+            while !done.load(Ordering::SeqCst) {
+                manager.process_all();
+                std::thread::sleep(Duration::from_millis(5));
+            }
+        });
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_time()
+            .build()
+            .unwrap();
+        let lines = lines.iter().map(|line| line.to_string()).collect();
+        let results = runtime.block_on(manager.run_script(id, lines, timeout, TimeoutAction::Interrupt));
+        done.store(true, Ordering::SeqCst);
+        results
+    })
+}
+
+fn wait_for_prompt(harness: &TestHarness, id: &str) {
+    assert!(harness
+        .pump_until(id, Duration::from_secs(5), |screen| {
+            screen.cells.iter().flatten().any(|cell| cell.char == "$")
+        })
+        .unwrap());
+}
+
+#[test]
+fn lines_finished_by_going_quiet_have_an_unknown_result() {
+    let harness = TestHarness::new();
+    let id = harness.spawn(shell(false)).unwrap();
+    wait_for_prompt(&harness, &id);
+
+    let results = run_script(&harness, &id, &["false", "echo done"], Duration::from_secs(5)).unwrap();
+
+    assert_eq!(results.len(), 2);
+    assert!(results.iter().all(|r| r.success.is_none() && r.exit_code.is_none()));
+    assert!(harness.screen_text(&id).unwrap().contains("done"));
+}
+
+#[test]
+fn marked_failure_stops_the_script() {
+    let harness = TestHarness::new();
+    let id = harness.spawn(shell(true)).unwrap();
+    wait_for_prompt(&harness, &id);
+
+    let results = run_script(
+        &harness,
+        &id,
+        &[
+            r"printf '\033]133;C\007'; true; printf '\033]133;D;0\007'",
+            r"printf '\033]133;C\007'; printf '\033]133;D;3\007'",
+            "echo never",
+        ],
+        Duration::from_secs(5),
+    )
+    .unwrap();
+
+    let outcomes: Vec<_> = results.iter().map(|r| (r.success, r.exit_code)).collect();
+    assert_eq!(outcomes, [(Some(true), Some(0)), (Some(false), Some(3))]);
+}
+
+#[test]
+fn quiet_foreground_job_runs_until_the_deadline() {
+    let harness = TestHarness::new();
+    let id = harness.spawn(shell(false)).unwrap();
+    wait_for_prompt(&harness, &id);
+
+    let started = Instant::now();
+    let result = run_script(&harness, &id, &["echo started; sleep 600"], Duration::from_secs(1));
+
+    assert!(started.elapsed() >= Duration::from_secs(1));
+    match result {
+        Err(Error::CommandTimeout { output }) => assert!(output.contains("started"), "{:?}", output),
+        other => panic!("expected CommandTimeout, got {:?}", other),
+    }
+    // Interrupted, so the shell runs the next line
+    let results = run_script(&harness, &id, &["echo after"], Duration::from_secs(5)).unwrap();
+    assert_eq!(results.len(), 1);
+}
@@ -0,0 +1,86 @@
+//! Choosing between cell-change updates and full refreshes.
+#![cfg(all(feature = "testing", unix))]
+
+use std::time::Duration;
+use tauri_plugin_terminal::testing::TestHarness;
+use tauri_plugin_terminal::{SessionConfig, TerminalEvent};
+
+/// A 20x5 session with a scroll region set, so small edits diff as a few cells.
+fn spawn(harness: &mut TestHarness) -> String {
+    let id = harness
+        .spawn(SessionConfig {
+            shell: Some("/bin/sh".into()),
+            shell_args: vec!["-c".into(), "sleep 10".into()],
+            cols: Some(20),
+            rows: Some(5),
+            ..Default::default()
+        })
+        .unwrap();
+    harness.feed(&id, b"\x1b[2;5r").unwrap();
+    harness.events();
+    id
+}
+
+fn frames(harness: &mut TestHarness) -> Vec<&'static str> {
+    harness
+        .events()
+        .into_iter()
+        .filter_map(|event| match event {
+            TerminalEvent::ScreenUpdate(_) => Some("update"),
+            TerminalEvent::ScreenRefresh { .. } => Some("refresh"),
+            _ => None,
+        })
+        .collect()
+}
+
+#[test]
+fn large_changes_are_sent_as_a_refresh() {
+    let mut harness = TestHarness::new();
+    let id = spawn(&mut harness);
+
+    harness.feed(&id, b"x").unwrap();
+    assert_eq!(frames(&mut harness), ["update"]);
+
+    harness.feed(&id, "y".repeat(100).as_bytes()).unwrap();
+    assert_eq!(frames(&mut harness), ["refresh"]);
+}
+
+#[test]
+fn replay_resends_what_was_sent() {
+    let mut harness = TestHarness::new();
+    let id = spawn(&mut harness);
+
+    harness.feed(&id, "y".repeat(100).as_bytes()).unwrap();
+    harness.manager().replay_last_update(&id).unwrap().unwrap();
+    assert_eq!(frames(&mut harness), ["refresh", "refresh"]);
+
+    harness.feed(&id, b"\x1b[Hx").unwrap();
+    harness.manager().replay_last_update(&id).unwrap().unwrap();
+    assert_eq!(frames(&mut harness), ["update", "update"]);
+}
+
+#[test]
+fn first_output_arrives_promptly_as_a_refresh() {
+    let mut harness = TestHarness::new();
+    let id = harness
+        .spawn(SessionConfig {
+            shell: Some("/bin/sh".into()),
+            shell_args: vec!["-c".into(), "sleep 0.2; printf '$ '; sleep 10".into()],
+            cols: Some(20),
+            rows: Some(5),
+            ..Default::default()
+        })
+        .unwrap();
+    // Without the forced refresh the two-cell prompt would diff as an update.
+    harness.feed(&id, b"\x1b[2;5r").unwrap();
+    harness.events();
+
+    let shown = harness
+        .pump_until(&id, Duration::from_secs(2), |screen| {
+            screen.cells.iter().flatten().any(|cell| cell.char == "$")
+        })
+        .unwrap();
+
+    assert!(shown);
+    assert_eq!(frames(&mut harness), ["refresh"]);
+}
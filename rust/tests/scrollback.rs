@@ -0,0 +1,60 @@
+//! Reading scrollback deeper than one screen.
+
+use tauri_plugin_terminal::{Cell, SearchDirection, Terminal};
+
+/// A 20x5 terminal holding `lines` numbered lines, most of them in scrollback.
+fn terminal_with_lines(lines: usize) -> Terminal {
+    let mut terminal = Terminal::new(20, 5);
+    let text: String = (0..lines).map(|n| format!("line {}\r\n", n)).collect();
+    terminal.process(text.as_bytes());
+    terminal
+}
+
+fn row_text(row: &[Cell]) -> String {
+    row.iter().map(|cell| cell.char.as_str()).collect::<String>().trim_end().to_string()
+}
+
+#[test]
+fn viewport_scrolls_deeper_than_one_screen() {
+    let terminal = terminal_with_lines(100);
+
+    let viewport = terminal.viewport(Some(10));
+
+    assert_eq!(viewport.top_line, 10);
+    assert_eq!(viewport.scrollback_len, 96);
+    let rows: Vec<String> = viewport.rows.iter().map(|row| row_text(row)).collect();
+    assert_eq!(rows, ["line 10", "line 11", "line 12", "line 13", "line 14"]);
+}
+
+#[test]
+fn viewport_at_the_oldest_line() {
+    let terminal = terminal_with_lines(100);
+
+    let viewport = terminal.viewport(Some(0));
+
+    assert_eq!(row_text(&viewport.rows[0]), "line 0");
+    assert_eq!(row_text(&terminal.viewport(None).rows[0]), "line 96");
+}
+
+#[test]
+fn search_finds_lines_deep_in_scrollback() {
+    for indexed in [false, true] {
+        let mut terminal = terminal_with_lines(1000);
+        terminal.set_search_index(indexed);
+
+        let found = terminal
+            .search("line 12", None, SearchDirection::Forward, true)
+            .expect("match");
+        assert_eq!((found.line, found.start_col, found.end_col), (12, 0, 7));
+
+        let next = terminal
+            .search("line 12", Some((12, 0)), SearchDirection::Forward, true)
+            .expect("next match");
+        assert_eq!(next.line, 120);
+
+        let last = terminal
+            .search("LINE 99", None, SearchDirection::Backward, false)
+            .expect("match from the bottom");
+        assert_eq!(last.line, 999);
+    }
+}
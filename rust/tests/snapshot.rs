@@ -0,0 +1,54 @@
+//! Exporting a session as a snapshot and importing it again.
+#![cfg(all(feature = "testing", unix))]
+
+use tauri_plugin_terminal::testing::TestHarness;
+use tauri_plugin_terminal::{MarkType, SessionConfig, TerminalEvent};
+
+#[test]
+fn import_restores_buffer_cursor_and_marks() {
+    let mut harness = TestHarness::new();
+    let original = harness
+        .spawn(SessionConfig {
+            shell: Some("/bin/sh".into()),
+            shell_args: vec!["-c".into(), "sleep 10".into()],
+            cols: Some(40),
+            rows: Some(10),
+            ..Default::default()
+        })
+        .unwrap();
+    let mut output: String = (0..30).map(|n| format!("line {}\r\n", n)).collect();
+    output.push_str("\x1b]133;A\x07$ \x1b]133;B\x07ls\r\n\x1b]133;C\x07a b\r\n\x1b]133;D;0\x07");
+    output.push_str("\x1b]133;A\x07$ ");
+    harness.feed(&original, output.as_bytes()).unwrap();
+
+    let snapshot = harness.manager().export_snapshot(&original).unwrap();
+    harness.events();
+
+    let restored = harness.manager().import_snapshot(snapshot, None).unwrap();
+
+    let text = |id: &str| harness.manager().export_text(id, true).unwrap();
+    assert_eq!(text(&restored), text(&original));
+    let cursor = |id: &str| harness.manager().get_screen(id).unwrap().cursor.position;
+    assert_eq!(cursor(&restored), cursor(&original));
+
+    let marks = |id: &str| -> Vec<(u32, MarkType)> {
+        harness.manager().marks(id).unwrap().into_iter().map(|m| (m.row, m.mark_type)).collect()
+    };
+    assert_eq!(marks(&restored), marks(&original));
+    assert_eq!(marks(&restored).last().unwrap().0, 30 + 2);
+
+    let events = harness.events();
+    let created = events
+        .iter()
+        .position(|e| matches!(e, TerminalEvent::SessionCreated { session_id } if *session_id == restored))
+        .unwrap();
+    let painted = events
+        .iter()
+        .position(|e| match e {
+            TerminalEvent::ScreenUpdate(update) => update.session_id == restored,
+            TerminalEvent::ScreenRefresh { session_id, .. } => *session_id == restored,
+            _ => false,
+        })
+        .unwrap();
+    assert!(created < painted);
+}
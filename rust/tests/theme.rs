@@ -0,0 +1,59 @@
+//! Bundled themes and contrast adjustment.
+
+use tauri_plugin_terminal::{Color, Terminal, Theme};
+
+#[test]
+fn bundled_palettes_resolve_by_name() {
+    let expected = [
+        ("gruvbox-dark", Color::new(251, 73, 52)),
+        ("gruvbox-light", Color::new(157, 0, 6)),
+        ("tokyo-night", Color::new(247, 118, 142)),
+        ("catppuccin-mocha", Color::new(243, 139, 168)),
+        ("catppuccin-latte", Color::new(210, 15, 57)),
+    ];
+
+    for (name, bright_red) in expected {
+        let theme = Theme::by_name(name).unwrap_or_else(|| panic!("{name} is not registered"));
+        assert_eq!(theme.name, name);
+        assert_eq!(theme.color_by_index(9), bright_red, "{name}");
+    }
+}
+
+#[test]
+fn contrast_ratio_of_known_pairs() {
+    let black = Color::new(0, 0, 0);
+    let white = Color::new(255, 255, 255);
+    let grey = Color::new(119, 119, 119);
+
+    assert!((black.contrast_ratio(&white) - 21.0).abs() < 0.01);
+    assert!((white.contrast_ratio(&black) - 21.0).abs() < 0.01);
+    assert!((grey.contrast_ratio(&grey) - 1.0).abs() < 0.01);
+    // #777 on white sits just under the 4.5 AA threshold.
+    assert!((grey.contrast_ratio(&white) - 4.48).abs() < 0.01);
+}
+
+#[test]
+fn ensure_contrast_lifts_low_contrast_colors() {
+    let theme = Theme {
+        blue: Color::new(10, 10, 60),
+        ..Theme::by_name("dark").unwrap()
+    };
+
+    let adjusted = theme.ensure_contrast(4.5);
+
+    assert!(adjusted.blue.contrast_ratio(&adjusted.background) >= 4.5);
+    assert_eq!(adjusted.background, theme.background);
+    // Colors that already pass are left alone.
+    assert_eq!(adjusted.foreground, theme.foreground);
+}
+
+#[test]
+fn min_contrast_applies_to_rendered_cells() {
+    let mut terminal = Terminal::new(10, 2);
+    terminal.set_min_contrast(Some(4.5));
+    terminal.process(b"\x1b[38;2;20;20;20mx");
+
+    let cell = terminal.get_cell(0, 0);
+    assert_ne!(cell.fg, Color::new(20, 20, 20));
+    assert!(cell.fg.contrast_ratio(&cell.bg) >= 4.5);
+}
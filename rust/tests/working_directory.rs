@@ -0,0 +1,44 @@
+//! Validating the working directory on session create.
+#![cfg(all(feature = "testing", unix))]
+
+use tauri_plugin_terminal::testing::TestHarness;
+use tauri_plugin_terminal::{Error, SessionConfig};
+
+const MISSING: &str = "/nonexistent/terminal-plugin-test";
+
+fn in_dir(cwd: &str) -> SessionConfig {
+    SessionConfig {
+        cwd: Some(cwd.into()),
+        shell: Some("/bin/sh".into()),
+        shell_args: vec!["-c".into(), "sleep 10".into()],
+        ..Default::default()
+    }
+}
+
+#[test]
+fn missing_cwd_is_an_invalid_config() {
+    let harness = TestHarness::new();
+
+    let err = harness.spawn(in_dir(MISSING)).unwrap_err();
+
+    assert!(
+        matches!(&err, Error::InvalidConfig(message) if message.contains(MISSING)),
+        "{:?}",
+        err
+    );
+}
+
+#[test]
+fn missing_cwd_falls_back_to_home() {
+    let harness = TestHarness::new();
+
+    let id = harness
+        .spawn(SessionConfig {
+            cwd_fallback: true,
+            ..in_dir(MISSING)
+        })
+        .unwrap();
+
+    let info = harness.manager().get_info(&id).unwrap();
+    assert_eq!(info.cwd, std::env::var("HOME").ok());
+}
@@ -0,0 +1,65 @@
+//! Concurrent writes to one session are delivered whole.
+#![cfg(all(feature = "testing", unix))]
+
+use parking_lot::Mutex;
+use std::io::Write;
+use std::os::unix::net::UnixStream;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tauri_plugin_terminal::testing::TestHarness;
+use tauri_plugin_terminal::{ExternalPty, SessionConfig};
+
+const WRITERS: u8 = 8;
+const CALLS: usize = 50;
+const CALL_LEN: usize = 256;
+
+/// Accepts a few bytes per call, like a transport under back-pressure, so
+/// a write that isn't serialized gets split.
+struct Trickle(Arc<Mutex<Vec<u8>>>);
+
+impl Write for Trickle {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let n = buf.len().min(7);
+        self.0.lock().extend_from_slice(&buf[..n]);
+        std::thread::yield_now();
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn concurrent_writes_do_not_interleave() {
+    let harness = TestHarness::new();
+    let written = Arc::new(Mutex::new(Vec::new()));
+    let (reader, _peer) = UnixStream::pair().unwrap();
+    let external = ExternalPty::new(Box::new(reader), Box::new(Trickle(written.clone())));
+    let id = harness.manager().attach(SessionConfig::default(), external).unwrap();
+
+    std::thread::scope(|scope| {
+        for writer in 0..WRITERS {
+            let id = &id;
+            let manager = harness.manager();
+            scope.spawn(move || {
+                let data = vec![b'a' + writer; CALL_LEN];
+                for _ in 0..CALLS {
+                    manager.write(id, &data).unwrap();
+                }
+            });
+        }
+    });
+
+    let total = WRITERS as usize * CALLS * CALL_LEN;
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while written.lock().len() < total && Instant::now() < deadline {
+        std::thread::sleep(Duration::from_millis(5));
+    }
+
+    let written = written.lock();
+    assert_eq!(written.len(), total);
+    for (n, call) in written.chunks(CALL_LEN).enumerate() {
+        assert!(call.iter().all(|&b| b == call[0]), "call {} was interleaved", n);
+    }
+}